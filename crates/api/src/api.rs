@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::data::{
+    detail::{ModDetailsRequest, ModDetailsResponse},
+    game::LatestReleases,
+    image::{Image, ImageEditRequest, ImageEditResponse, ImageUploadResponse},
+    portal::{ModFull, ModShort, Release, SearchQuery, SearchResponse},
+    publish::{PublishRequest, PublishResponse},
+    upload::UploadResponse,
+};
+
+/// Operations supported by the Factorio API clients.
+///
+/// This is implemented by both the async [`ApiClient`][crate::ApiClient] and the
+/// [`blocking::ApiClient`][crate::blocking::ApiClient], letting code be written
+/// once against either of them. The two differ only in what each method
+/// returns, which is described by [`Output`][FactorioApi::Output]: the blocking
+/// client returns the [`Result`] directly, while the async client returns a
+/// boxed future resolving to it.
+///
+/// See the inherent methods on the clients for documentation of
+/// each operation.
+pub trait FactorioApi {
+    /// The type returned by each method, wrapping a
+    /// <code>Result<T, [ApiError]></code>.
+    ///
+    /// [ApiError]: crate::error::ApiError
+    type Output<'a, T: 'a>
+    where
+        Self: 'a;
+
+    fn search<'a>(&'a self, query: &'a SearchQuery) -> Self::Output<'a, SearchResponse>;
+
+    fn info_short<'a>(&'a self, name: &'a str) -> Self::Output<'a, ModShort>;
+
+    fn info_full<'a>(&'a self, name: &'a str) -> Self::Output<'a, ModFull>;
+
+    fn upload<'a>(&'a self, name: &'a str, path: &'a Path) -> Self::Output<'a, UploadResponse>;
+
+    fn edit_details(&self, data: ModDetailsRequest) -> Self::Output<'_, ModDetailsResponse>;
+
+    fn images<'a>(&'a self, name: &'a str) -> Self::Output<'a, Vec<Image>>;
+
+    fn download_release<'a>(&'a self, release: &'a Release) -> Self::Output<'a, Vec<u8>>;
+
+    fn download_image<'a>(&'a self, image: &'a Image) -> Self::Output<'a, Vec<u8>>;
+
+    fn upload_image<'a>(
+        &'a self,
+        name: &'a str,
+        path: &'a Path,
+    ) -> Self::Output<'a, ImageUploadResponse>;
+
+    fn edit_images(&self, data: ImageEditRequest) -> Self::Output<'_, ImageEditResponse>;
+
+    fn publish<'a>(
+        &'a self,
+        name: &'a str,
+        data: PublishRequest,
+        path: &'a Path,
+    ) -> Self::Output<'a, PublishResponse>;
+
+    fn latest_releases(&self) -> Self::Output<'_, LatestReleases>;
+}
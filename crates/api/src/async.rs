@@ -8,5 +8,5 @@
 //! [async-book]: https://rust-lang.github.io/async-book/
 
 pub(crate) mod client;
-mod error;
-mod reqwest;
+#[cfg(feature = "reqwest")]
+pub(crate) mod reqwest;
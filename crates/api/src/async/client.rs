@@ -1,44 +1,57 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
-use reqwest::{RequestBuilder, header, multipart::Form};
-use serde::de::DeserializeOwned;
-use url::Url;
+use futures_core::{Stream, future::BoxFuture};
+use futures_util::stream;
+use tracing::debug;
 
 use crate::{
-    FactorioUrls,
+    FactorioApi, FactorioUrls,
     data::{
         detail::{ModDetailsRequest, ModDetailsResponse},
         game::LatestReleases,
-        image::{
-            self, Image, ImageAddResponse, ImageEditRequest, ImageEditResponse, ImageUploadResponse,
+        image::{Image, ImageEditRequest, ImageEditResponse, ImageUploadResponse},
+        portal::{
+            Credentials, ModFull, ModShort, ModSummary, Release, SearchQuery, SearchResponse,
         },
-        portal::{SearchQuery, SearchResponse, SearchResult},
-        publish::{InitPublishResponse, PublishRequest, PublishResponse},
-        upload::{InitUploadResponse, UploadResponse},
+        publish::{PublishRequest, PublishResponse},
+        upload::UploadResponse,
     },
-    error::{ApiError, ApiErrorKind},
-    reqwest::FormContainer,
+    endpoint::{self, Call},
+    error::ApiError,
+    retry::RetryPolicy,
+    search::SearchPager,
+    transport::{AsyncTransport, HttpOptions, Progress, TransportError},
 };
 
-use super::{error, reqwest::AsyncFormFile};
-
 pub struct ApiClient {
-    client: reqwest::Client,
+    transport: Box<dyn AsyncTransport>,
     urls: FactorioUrls,
     api_key: Option<String>,
+    credentials: Option<Credentials>,
+    user_agent: String,
+    retry_policy: RetryPolicy,
 }
 
 type Result<T> = core::result::Result<T, ApiError>;
 
 impl ApiClient {
+    /// Constructs a new [`ApiClient`] with no API key configured.
+    ///
+    /// A non-mut client constructed in this manner will only be able to use
+    /// APIs that do not require authentication.
     pub fn new() -> Self {
         Self {
-            client: Default::default(),
+            transport: default_transport(&Default::default())
+                .expect("Failed to set up the default transport"),
             urls: Default::default(),
             api_key: None,
+            credentials: None,
+            user_agent: crate::DEFAULT_USER_AGENT.to_owned(),
+            retry_policy: Default::default(),
         }
     }
 
+    /// Constructs a new [`ApiClient`] with the given API key.
     pub fn with_api_key<T: Into<String>>(api_key: T) -> Self {
         Self {
             api_key: Some(api_key.into()),
@@ -46,28 +59,70 @@ impl ApiClient {
         }
     }
 
+    /// Constructs a new [`ApiClientBuilder`], letting you customize details
+    /// of all the client properties.
     pub fn builder() -> ApiClientBuilder {
         ApiClientBuilder::new()
     }
 
+    /// Search for mods on the Factorio mod portal.
+    ///
+    /// Each result is a [`ModSummary`], which only includes the fields
+    /// the portal lists for every mod. Use [`info_short`][ApiClient::info_short]
+    /// or [`info_full`][ApiClient::info_full] for more details on a mod.
     pub async fn search(&self, query: &SearchQuery) -> Result<SearchResponse> {
-        self.get(self.portal_api_url("mods")?, false, |r| r.query(query))
-            .await
+        self.execute(endpoint::search(&self.urls, query)?).await
     }
 
-    pub async fn info_short(&self, name: &str) -> Result<SearchResult> {
-        self.get(self.portal_api_url(format!("mods/{}", name))?, false, |r| r)
-            .await
+    /// Search for mods on the Factorio mod portal, automatically fetching
+    /// every page of results.
+    ///
+    /// Pages are fetched lazily as the returned stream is polled, starting from
+    /// [`SearchQuery::page`]. Queries with a long [`namelist`][SearchQuery::namelist]
+    /// are split up into several requests.
+    ///
+    /// If fetching a page fails, the error is yielded and the stream ends.
+    pub fn search_all(&self, query: SearchQuery) -> impl Stream<Item = Result<ModSummary>> + '_ {
+        stream::unfold(Some(SearchPager::new(query)), move |pager| async move {
+            let mut pager = pager?;
+
+            loop {
+                if let Some(result) = pager.pop() {
+                    return Some((Ok(result), Some(pager)));
+                }
+
+                let query = pager.next_query()?;
+
+                match self.search(&query).await {
+                    Ok(response) => pager.push(response),
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+    }
+
+    /// Get brief information about a mod by its internal name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use facti_api::ApiClient;
+    /// #
+    /// # async fn run() -> Result<(), Box<dyn Error>> {
+    /// let client = ApiClient::new();
+    /// let result = client.info_short("cybersyn-combinator").await?;
+    /// dbg!(result);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn info_short(&self, name: &str) -> Result<ModShort> {
+        self.execute(endpoint::info_short(&self.urls, name)?).await
     }
 
     /// Get detailed information about a mod by its internal name.
-    pub async fn info_full(&self, name: &str) -> Result<SearchResult> {
-        self.get(
-            self.portal_api_url(format!("mods/{}/full", name))?,
-            false,
-            |r| r,
-        )
-        .await
+    pub async fn info_full(&self, name: &str) -> Result<ModFull> {
+        self.execute(endpoint::info_full(&self.urls, name)?).await
     }
 
     pub async fn upload<S: Into<String>, P: AsRef<Path>>(
@@ -75,36 +130,52 @@ impl ApiClient {
         name: S,
         path: P,
     ) -> Result<UploadResponse> {
-        let init = self.init_upload(name).await?;
-        let url = init.upload_url;
-        let form = Form::new().file("file", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read mod file {:?}", e),
-                None,
-            )
-        })?;
-
-        self.send(self.client.post(url).multipart(form), false)
+        self.upload_inner(name.into(), path.as_ref(), None).await
+    }
+
+    /// Like [`upload`][ApiClient::upload], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub async fn upload_with_progress<S, P, F>(
+        &self,
+        name: S,
+        path: P,
+        progress: F,
+    ) -> Result<UploadResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.upload_inner(name.into(), path.as_ref(), Some(Arc::new(progress)))
             .await
     }
 
     pub async fn edit_details(&self, data: ModDetailsRequest) -> Result<ModDetailsResponse> {
-        let container: FormContainer<Form> = data.into();
-        let form = container.into_inner();
-        self.post(self.portal_api_url("v2/mods/edit_details")?, true, |r| {
-            r.multipart(form)
-        })
-        .await
+        self.execute(endpoint::edit_details(&self.urls, data)?)
+            .await
     }
 
     pub async fn images(&self, name: &str) -> Result<Vec<Image>> {
-        let url = self.portal_url(format!("mod/{}", name))?;
-        let page = self.client.get(url.to_owned()).send().await?;
-        let html = page.text().await?;
-        let images = image::parse_html_images(&html);
+        self.execute(endpoint::images(&self.urls, name)?).await
+    }
 
-        Ok(images)
+    /// Downloads a release of a mod, returning the contents of its zip file.
+    ///
+    /// This requires the [`Credentials`] of a Factorio account to be
+    /// configured on the client, see [`ApiClientBuilder::credentials`].
+    pub async fn download_release(&self, release: &Release) -> Result<Vec<u8>> {
+        self.execute(endpoint::download_release(
+            &self.urls,
+            release,
+            self.credentials.as_ref(),
+        )?)
+        .await
+    }
+
+    /// Downloads the full size version of one of a mod's images,
+    /// as returned by [`images`][ApiClient::images].
+    pub async fn download_image(&self, image: &Image) -> Result<Vec<u8>> {
+        self.execute(endpoint::download_image(image)?).await
     }
 
     pub async fn upload_image<S: Into<String>, P: AsRef<Path>>(
@@ -112,27 +183,29 @@ impl ApiClient {
         name: S,
         path: P,
     ) -> Result<ImageUploadResponse> {
-        let add = self.add_image(name).await?;
-        let url = add.upload_url;
-        let form = Form::new().file("image", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read image file: {:?}", e),
-                None,
-            )
-        })?;
-
-        self.send(self.client.post(url).multipart(form), false)
+        self.upload_image_inner(name.into(), path.as_ref(), None)
+            .await
+    }
+
+    /// Like [`upload_image`][ApiClient::upload_image], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub async fn upload_image_with_progress<S, P, F>(
+        &self,
+        name: S,
+        path: P,
+        progress: F,
+    ) -> Result<ImageUploadResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.upload_image_inner(name.into(), path.as_ref(), Some(Arc::new(progress)))
             .await
     }
 
     pub async fn edit_images(&self, data: ImageEditRequest) -> Result<ImageEditResponse> {
-        let container: FormContainer<Form> = data.into();
-        let form = container.into_inner();
-        self.post(self.portal_api_url("v2/mods/images/edit")?, true, |r| {
-            r.multipart(form)
-        })
-        .await
+        self.execute(endpoint::edit_images(&self.urls, data)?).await
     }
 
     pub async fn publish<S: Into<String>, P: AsRef<Path>>(
@@ -141,127 +214,163 @@ impl ApiClient {
         data: PublishRequest,
         path: P,
     ) -> Result<PublishResponse> {
-        let init = self.init_publish(name).await?;
-        let url = init.upload_url;
-        let container: FormContainer<Form> = data.into();
-        let mut form = container.into_inner();
-        form = form.file("file", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read mod file {:?}", e),
-                None,
-            )
-        })?;
-
-        self.send(self.client.post(url).multipart(form), false)
+        self.publish_inner(name.into(), data, path.as_ref(), None)
+            .await
+    }
+
+    /// Like [`publish`][ApiClient::publish], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub async fn publish_with_progress<S, P, F>(
+        &self,
+        name: S,
+        data: PublishRequest,
+        path: P,
+        progress: F,
+    ) -> Result<PublishResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.publish_inner(name.into(), data, path.as_ref(), Some(Arc::new(progress)))
             .await
     }
 
     /// Get information about the latest available releases of the game.
     pub async fn latest_releases(&self) -> Result<LatestReleases> {
-        self.get(self.game_url("latest-releases")?, false, |r| r)
-            .await
+        self.execute(endpoint::latest_releases(&self.urls)?).await
     }
 
-    async fn init_upload<T: Into<String>>(&self, name: T) -> Result<InitUploadResponse> {
-        let form = Form::new().text("mod", name.into());
-        self.post(self.portal_api_url("v2/mods/upload")?, true, |r| {
-            r.multipart(form)
-        })
-        .await
+    async fn upload_inner(
+        &self,
+        name: String,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<UploadResponse> {
+        let init = self
+            .execute(endpoint::init_upload(&self.urls, &name)?)
+            .await?;
+        let call = endpoint::upload(init.upload_url, path)?.with_progress(progress);
+
+        self.execute(call).await
     }
 
-    async fn add_image<T: Into<String>>(&self, name: T) -> Result<ImageAddResponse> {
-        self.post(self.portal_api_url("v2/mods/images/add")?, true, |r| {
-            r.multipart(Form::new().text("mod", name.into()))
-        })
-        .await
+    async fn upload_image_inner(
+        &self,
+        name: String,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<ImageUploadResponse> {
+        let init = self
+            .execute(endpoint::add_image(&self.urls, &name)?)
+            .await?;
+        let call = endpoint::upload_image(init.upload_url, path)?.with_progress(progress);
+
+        self.execute(call).await
     }
 
-    async fn init_publish<T: Into<String>>(&self, name: T) -> Result<InitPublishResponse> {
-        let form = Form::new().text("mod", name.into());
-        self.post(self.portal_api_url("v2/mods/init_publish")?, true, |r| {
-            r.multipart(form)
-        })
-        .await
+    async fn publish_inner(
+        &self,
+        name: String,
+        data: PublishRequest,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<PublishResponse> {
+        let init = self
+            .execute(endpoint::init_publish(&self.urls, &name)?)
+            .await?;
+        let call = endpoint::publish(init.upload_url, data, path)?.with_progress(progress);
+
+        self.execute(call).await
     }
 
-    fn portal_api_url<T: AsRef<str>>(&self, path: T) -> Result<Url> {
-        self.urls.portal_api(path.as_ref()).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!("Failed to join base URL with path {}", path.as_ref()),
-                None,
-            )
-        })
+    /// Sends the request for a call, retrying it according to the configured
+    /// [`RetryPolicy`] if it fails with a transient error and is safe to repeat.
+    async fn execute<T>(&self, call: Call<T>) -> Result<T> {
+        let mut attempt = 1;
+
+        loop {
+            let request = call.request(self.api_key.as_deref(), &self.user_agent)?;
+            debug!(
+                "Sending {} request to {} (attempt {})",
+                call.method(),
+                call.url(),
+                attempt
+            );
+            let result = self.transport.execute(request).await;
+
+            match call.retry_delay(&self.retry_policy, attempt, &result) {
+                Some(delay) => self.transport.sleep(delay).await,
+                None => return call.parse(result),
+            }
+
+            attempt += 1;
+        }
     }
+}
 
-    fn portal_url<T: AsRef<str>>(&self, path: T) -> Result<Url> {
-        self.urls.portal(path.as_ref()).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!("Failed to join portal base URL with path {}", path.as_ref()),
-                None,
-            )
-        })
+impl FactorioApi for ApiClient {
+    type Output<'a, T: 'a> = BoxFuture<'a, Result<T>>;
+
+    fn search<'a>(&'a self, query: &'a SearchQuery) -> BoxFuture<'a, Result<SearchResponse>> {
+        Box::pin(ApiClient::search(self, query))
     }
 
-    fn game_url(&self, path: &str) -> Result<Url> {
-        self.urls.game(path).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!("Failed to join game base URL with path {}", path),
-                None,
-            )
-        })
+    fn info_short<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<ModShort>> {
+        Box::pin(ApiClient::info_short(self, name))
     }
 
-    async fn send<T>(&self, request: RequestBuilder, auth: bool) -> Result<T>
-    where
-        T: DeserializeOwned,
-    {
-        let mut request = request.header(header::USER_AGENT, "facti");
-        if auth {
-            if let Some(api_key) = &self.api_key {
-                request = request.bearer_auth(api_key)
-            } else {
-                return Err(ApiError::new(
-                    ApiErrorKind::MissingApiKey,
-                    "Missing API key",
-                    None,
-                ));
-            }
-        }
+    fn info_full<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<ModFull>> {
+        Box::pin(ApiClient::info_full(self, name))
+    }
 
-        let response = request.send().await?;
+    fn upload<'a>(
+        &'a self,
+        name: &'a str,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<UploadResponse>> {
+        Box::pin(ApiClient::upload(self, name, path))
+    }
 
-        if response.status().is_success() {
-            Ok(response.json::<T>().await?)
-        } else {
-            Err(error::from_response(response).await)
-        }
+    fn edit_details(&self, data: ModDetailsRequest) -> BoxFuture<'_, Result<ModDetailsResponse>> {
+        Box::pin(ApiClient::edit_details(self, data))
     }
 
-    async fn get<T, U, F>(&self, url: U, auth: bool, f: F) -> Result<T>
-    where
-        T: DeserializeOwned,
-        U: Into<Url>,
-        F: FnOnce(RequestBuilder) -> RequestBuilder,
-    {
-        let request = f(self.client.get(url.into()));
+    fn images<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<Image>>> {
+        Box::pin(ApiClient::images(self, name))
+    }
 
-        self.send::<T>(request, auth).await
+    fn download_release<'a>(&'a self, release: &'a Release) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(ApiClient::download_release(self, release))
     }
 
-    async fn post<T, U, F>(&self, url: U, auth: bool, f: F) -> Result<T>
-    where
-        T: DeserializeOwned,
-        U: Into<Url>,
-        F: FnOnce(RequestBuilder) -> RequestBuilder,
-    {
-        let request = f(self.client.post(url.into()));
+    fn download_image<'a>(&'a self, image: &'a Image) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(ApiClient::download_image(self, image))
+    }
 
-        self.send::<T>(request, auth).await
+    fn upload_image<'a>(
+        &'a self,
+        name: &'a str,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<ImageUploadResponse>> {
+        Box::pin(ApiClient::upload_image(self, name, path))
+    }
+
+    fn edit_images(&self, data: ImageEditRequest) -> BoxFuture<'_, Result<ImageEditResponse>> {
+        Box::pin(ApiClient::edit_images(self, data))
+    }
+
+    fn publish<'a>(
+        &'a self,
+        name: &'a str,
+        data: PublishRequest,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<PublishResponse>> {
+        Box::pin(ApiClient::publish(self, name, data, path))
+    }
+
+    fn latest_releases(&self) -> BoxFuture<'_, Result<LatestReleases>> {
+        Box::pin(ApiClient::latest_releases(self))
     }
 }
 
@@ -271,4 +380,20 @@ impl Default for ApiClient {
     }
 }
 
-api_client_builder!(reqwest::Client, ApiClient);
+#[cfg(feature = "reqwest")]
+fn default_transport(
+    options: &HttpOptions,
+) -> core::result::Result<Box<dyn AsyncTransport>, TransportError> {
+    Ok(Box::new(super::reqwest::ReqwestTransport::with_options(
+        options,
+    )?))
+}
+
+#[cfg(not(feature = "reqwest"))]
+fn default_transport(
+    _options: &HttpOptions,
+) -> core::result::Result<Box<dyn AsyncTransport>, TransportError> {
+    Ok(Box::new(crate::transport::MissingTransport))
+}
+
+api_client_builder!(AsyncTransport, ApiClient);
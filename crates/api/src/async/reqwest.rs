@@ -1,67 +1,216 @@
-use std::{borrow::Cow, fs, io, path::Path};
+use std::{
+    future::Future,
+    io::{self, Read},
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
 
-use reqwest::multipart::{Form, Part};
-use thiserror::Error;
+use async_trait::async_trait;
+use bytes::Bytes;
+use http_body::{Frame, SizeHint};
+use tokio::task::JoinHandle;
 
-use crate::reqwest::FormLike;
+use crate::transport::{
+    AsyncTransport, Body, BodyReader, DEFAULT_TIMEOUT, HttpOptions, Request, Response,
+    TransportError, TransportErrorKind,
+};
 
-/// Sets up wrapper methods to interact with [`Form`]
-/// when contained in a [`crate::reqwest::FormContainer`].
-#[doc(hidden)]
-impl FormLike for Form {
-    fn new() -> Self {
+/// The default [`AsyncTransport`], based on [`reqwest::Client`].
+///
+/// Retries are timed using [`tokio`], and uploaded files are read on its
+/// blocking thread pool, so the client must be used from within a Tokio
+/// runtime with the time driver enabled.
+///
+/// # Optional but default
+///
+/// This requires the `reqwest` feature to be enabled,
+/// which is part of the default enabled features.
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    /// Timeout for requests that do not upload files, or [`None`]
+    /// to leave timeouts to the client.
+    timeout: Option<Duration>,
+}
+
+impl ReqwestTransport {
+    /// Constructs a new [`ReqwestTransport`] using a default client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like
+    /// [`reqwest::Client::new`].
+    pub fn new() -> Self {
+        Self::with_options(&HttpOptions::default()).expect("Failed to build the HTTP client")
+    }
+
+    /// Constructs a new [`ReqwestTransport`] with a client configured
+    /// according to the given options.
+    ///
+    /// The read timeout (30 seconds if not set) limits the whole of each
+    /// request that does not upload files. Uploads are not limited, as
+    /// sending a large mod can take much longer than that.
+    pub fn with_options(options: &HttpOptions) -> Result<Self, TransportError> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+        }
+
+        for pem in &options.root_certificates {
+            for certificate in reqwest::Certificate::from_pem_bundle(pem)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            timeout: Some(options.read_timeout.unwrap_or(DEFAULT_TIMEOUT)),
+        })
+    }
+
+    /// Constructs a new [`ReqwestTransport`] using the given client.
+    ///
+    /// Requests use the timeout configured on the client, if any.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            timeout: None,
+        }
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
         Self::new()
     }
+}
+
+#[async_trait]
+impl AsyncTransport for ReqwestTransport {
+    async fn execute(&self, request: Request) -> Result<Response, TransportError> {
+        let is_upload = request.body().has_files();
+        let request = request.map(|body| {
+            if body.is_empty() {
+                reqwest::Body::from(Vec::new())
+            } else {
+                reqwest::Body::wrap(StreamingBody::new(&body))
+            }
+        });
+        let mut request = reqwest::Request::try_from(request)
+            .map_err(|e| TransportError::new(TransportErrorKind::Other, e))?;
+        if !is_upload {
+            *request.timeout_mut() = self.timeout;
+        }
+        let response = self.client.execute(request).await?;
+
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+
+        builder
+            .body(response.bytes().await?.to_vec())
+            .map_err(|e| TransportError::new(TransportErrorKind::Other, e))
+    }
 
-    fn text<T, U>(self, name: T, value: U) -> Self
-    where
-        T: Into<Cow<'static, str>>,
-        U: Into<Cow<'static, str>>,
-    {
-        self.text(name, value)
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
     }
 }
 
-// Workaround for weird decision in reqwest API design
+impl super::client::ApiClientBuilder {
+    /// Sets the underlying [`reqwest::Client`] to use.
+    ///
+    /// This is a shorthand for setting a [`ReqwestTransport`] with the given client.
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    pub fn client(&mut self, client: reqwest::Client) -> &mut Self {
+        self.transport(ReqwestTransport::with_client(client))
+    }
+}
 
-#[doc(hidden)]
-#[derive(Error, Debug)]
-pub(crate) enum AsyncFormFileError {
-    #[error("IO error during form file operation")]
-    Io(io::Error),
+/// Size of the chunks to read request bodies in.
+const CHUNK_SIZE: usize = 64 * 1024;
 
-    #[error("reqwest error during form file operation")]
-    Reqwest(reqwest::Error),
+/// Streams a [`Body`] to reqwest in chunks.
+///
+/// Bodies are read with blocking I/O (they may contain files), so every
+/// chunk is read on Tokio's blocking thread pool to keep the runtime free.
+struct StreamingBody {
+    /// The reader, unless it was moved into a pending read.
+    reader: Option<BodyReader>,
+    read: Option<JoinHandle<(BodyReader, io::Result<Vec<u8>>)>>,
+    remaining: u64,
 }
 
-#[doc(hidden)]
-pub(crate) trait AsyncFormFile {
-    fn file<T, U>(self, name: T, path: U) -> Result<Form, AsyncFormFileError>
-    where
-        T: Into<Cow<'static, str>>,
-        U: AsRef<Path>;
+impl StreamingBody {
+    fn new(body: &Body) -> Self {
+        Self {
+            reader: Some(body.reader()),
+            read: None,
+            remaining: body.len(),
+        }
+    }
 }
 
-impl AsyncFormFile for Form {
-    fn file<T, U>(self, name: T, path: U) -> Result<Form, AsyncFormFileError>
-    where
-        T: Into<Cow<'static, str>>,
-        U: AsRef<Path>,
-    {
-        let path = path.as_ref();
-        let file_name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_default();
-        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-        let mime = mime_guess::from_ext(ext).first_or_octet_stream();
-        let mime_str = mime.essence_str();
-        let file = fs::read(path).map_err(AsyncFormFileError::Io)?;
-        let file_part = Part::bytes(file)
-            .file_name(file_name)
-            .mime_str(mime_str)
-            .map_err(AsyncFormFileError::Reqwest)?;
-
-        Ok(self.part(name, file_part))
+impl http_body::Body for StreamingBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        if this.read.is_none() {
+            let Some(mut reader) = this.reader.take() else {
+                return Poll::Ready(None);
+            };
+            this.read = Some(tokio::task::spawn_blocking(move || {
+                let mut chunk = vec![0; CHUNK_SIZE];
+                let result = reader.read(&mut chunk).map(|read| {
+                    chunk.truncate(read);
+                    chunk
+                });
+                (reader, result)
+            }));
+        }
+
+        let Some(read) = this.read.as_mut() else {
+            unreachable!("a read was just started");
+        };
+        let joined = ready!(Pin::new(read).poll(cx));
+        this.read = None;
+
+        let (reader, result) = match joined {
+            Ok(joined) => joined,
+            Err(e) => return Poll::Ready(Some(Err(io::Error::other(e)))),
+        };
+        this.reader = Some(reader);
+
+        match result {
+            Ok(chunk) if chunk.is_empty() => Poll::Ready(None),
+            Ok(chunk) => {
+                this.remaining = this.remaining.saturating_sub(chunk.len() as u64);
+                Poll::Ready(Some(Ok(Frame::data(chunk.into()))))
+            }
+            Err(e) => Poll::Ready(Some(Err(e))),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.remaining)
     }
 }
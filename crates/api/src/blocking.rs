@@ -5,7 +5,11 @@
 //! This requires the optional `blocking` feature to be enabled.
 
 mod client;
-mod error;
+#[cfg(feature = "reqwest")]
 mod reqwest;
+mod search;
 
 pub use client::{ApiClient, ApiClientBuilder};
+#[cfg(feature = "reqwest")]
+pub use reqwest::ReqwestTransport;
+pub use search::SearchAll;
@@ -1,28 +1,25 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc, thread};
 
-use reqwest::{
-    blocking::{RequestBuilder, multipart::Form},
-    header,
-};
-use serde::de::DeserializeOwned;
-use url::Url;
+use tracing::debug;
 
 use crate::{
-    FactorioUrls,
+    FactorioApi, FactorioUrls,
     data::{
         detail::{ModDetailsRequest, ModDetailsResponse},
         game::LatestReleases,
-        image::{
-            self, Image, ImageAddResponse, ImageEditRequest, ImageEditResponse, ImageUploadResponse,
-        },
-        portal::{SearchQuery, SearchResponse, SearchResult},
-        publish::{InitPublishResponse, PublishRequest, PublishResponse},
-        upload::{InitUploadResponse, UploadResponse},
+        image::{Image, ImageEditRequest, ImageEditResponse, ImageUploadResponse},
+        portal::{Credentials, ModFull, ModShort, Release, SearchQuery, SearchResponse},
+        publish::{PublishRequest, PublishResponse},
+        upload::UploadResponse,
     },
-    error::{ApiError, ApiErrorKind},
-    reqwest::FormContainer,
+    endpoint::{self, Call},
+    error::ApiError,
+    retry::RetryPolicy,
+    transport::{HttpOptions, Progress, Transport, TransportError},
 };
 
+use super::SearchAll;
+
 /// A blocking [`ApiClient`] to make requests to the Factorio APIs with.
 ///
 /// The minimal client you can construct is one that does not use an API key:
@@ -62,18 +59,28 @@ use crate::{
 /// all permissions for your API key. If you know you will only use a limited
 /// subset of the methods, you can of course limit the scope of your key.
 ///
-/// If you want to customize the base URL or the underlying [`reqwest::blocking::Client`]
-/// used, please construct an [`ApiClientBuilder`] by calling [`ApiClient::builder`],
+/// If you want to customize the base URL or the [`Transport`] used to send
+/// requests, please construct an [`ApiClientBuilder`] by calling [`ApiClient::builder`],
 /// where you can set (or not set) any of the client properties. Those that are
 /// not set will get their default values.
 ///
 /// Default URLs for the Factorio APIs can be obtained from constants:
 /// - [`crate::DEFAULT_PORTAL_BASE_URL`] for the mod portal API base URL.
 /// - [`crate::DEFAULT_GAME_BASE_URL`] for the game API base URL.
+///
+/// Requests that are safe to repeat are retried on transient failures
+/// according to the client's [`RetryPolicy`], which can also be configured
+/// on the builder.
+///
+/// The same methods are available through the [`FactorioApi`] trait,
+/// which is also implemented by the async client.
 pub struct ApiClient {
-    client: reqwest::blocking::Client,
+    transport: Box<dyn Transport>,
     urls: FactorioUrls,
     api_key: Option<String>,
+    credentials: Option<Credentials>,
+    user_agent: String,
+    retry_policy: RetryPolicy,
 }
 
 type Result<T> = core::result::Result<T, ApiError>;
@@ -85,9 +92,13 @@ impl ApiClient {
     /// APIs that do not require authentication.
     pub fn new() -> Self {
         Self {
-            client: Default::default(),
+            transport: default_transport(&Default::default())
+                .expect("Failed to set up the default transport"),
             urls: Default::default(),
             api_key: None,
+            credentials: None,
+            user_agent: crate::DEFAULT_USER_AGENT.to_owned(),
+            retry_policy: Default::default(),
         }
     }
 
@@ -107,22 +118,37 @@ impl ApiClient {
 
     /// Search for mods on the Factorio mod portal.
     ///
-    /// The following fields in [`SearchResult`] may be set in each result
-    /// in the returned [`SearchResponse::results`]:
+    /// Each result is a [`ModSummary`][crate::data::portal::ModSummary], which only includes the fields
+    /// the portal lists for every mod. Use [`info_short`][ApiClient::info_short]
+    /// or [`info_full`][ApiClient::info_full] for more details on a mod.
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResponse> {
+        self.execute(endpoint::search(&self.urls, query)?)
+    }
+
+    /// Search for mods on the Factorio mod portal, automatically fetching
+    /// every page of results.
+    ///
+    /// Pages are fetched lazily as the iterator is advanced,
+    /// starting from [`SearchQuery::page`]. Queries with a long
+    /// [`namelist`][SearchQuery::namelist] are split up into several
+    /// requests.
     ///
-    /// - [`download_count`][SearchResult::download_count]
-    /// - [`latest_release`][SearchResult::latest_release]
-    /// - [`name`][SearchResult::name]
-    /// - [`owner`][SearchResult::owner]
-    /// - [`releases`][SearchResult::releases]
-    /// - [`summary`][SearchResult::summary]
-    /// - [`title`][SearchResult::title]
-    /// - [`category`][SearchResult::category]
+    /// Each result is a [`ModSummary`][crate::data::portal::ModSummary], as for [`search`][ApiClient::search].
     ///
-    /// Any other fields on [`SearchResult`] will *never* have a value set when
-    /// constructed as a result of calling this method.
-    pub fn search(&self, query: &SearchQuery) -> Result<SearchResponse> {
-        self.get(self.portal_api_url("mods")?, false, |r| r.query(query))
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use facti_api::{blocking::ApiClient, data::portal::SearchQuery};
+    /// #
+    /// let client = ApiClient::new();
+    /// for result in client.search_all(SearchQuery::default()) {
+    ///     println!("{}", result?);
+    /// }
+    /// # Ok::<(), Box<dyn Error>>(())
+    /// ```
+    pub fn search_all(&self, query: SearchQuery) -> SearchAll<'_> {
+        SearchAll::new(self, query)
     }
 
     /// Get brief information about a mod by its internal name.
@@ -138,17 +164,13 @@ impl ApiClient {
     /// dbg!(result);
     /// # Ok::<(), Box<dyn Error>>(())
     /// ```
-    pub fn info_short(&self, name: &str) -> Result<SearchResult> {
-        self.get(self.portal_api_url(format!("mods/{}", name))?, false, |r| r)
+    pub fn info_short(&self, name: &str) -> Result<ModShort> {
+        self.execute(endpoint::info_short(&self.urls, name)?)
     }
 
     /// Get detailed information about a mod by its internal name.
-    pub fn info_full(&self, name: &str) -> Result<SearchResult> {
-        self.get(
-            self.portal_api_url(format!("mods/{}/full", name))?,
-            false,
-            |r| r,
-        )
+    pub fn info_full(&self, name: &str) -> Result<ModFull> {
+        self.execute(endpoint::info_full(&self.urls, name)?)
     }
 
     pub fn upload<S: Into<String>, P: AsRef<Path>>(
@@ -156,32 +178,49 @@ impl ApiClient {
         name: S,
         path: P,
     ) -> Result<UploadResponse> {
-        let init = self.init_upload(name)?;
-        let url = init.upload_url;
-        let form = Form::new().file("file", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read mod file {:?}", e),
-                None,
-            )
-        })?;
+        self.upload_inner(name.into(), path.as_ref(), None)
+    }
 
-        self.send(self.client.post(url).multipart(form), false)
+    /// Like [`upload`][ApiClient::upload], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub fn upload_with_progress<S, P, F>(
+        &self,
+        name: S,
+        path: P,
+        progress: F,
+    ) -> Result<UploadResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.upload_inner(name.into(), path.as_ref(), Some(Arc::new(progress)))
     }
 
     pub fn edit_details(&self, data: ModDetailsRequest) -> Result<ModDetailsResponse> {
-        let container: FormContainer<Form> = data.into();
-        let form = container.into_inner();
-        self.post("v2/mods/edit_details", true, |r| r.multipart(form))
+        self.execute(endpoint::edit_details(&self.urls, data)?)
     }
 
     pub fn images(&self, name: &str) -> Result<Vec<Image>> {
-        let url = self.portal_url(format!("mod/{}", name))?;
-        let page = self.client.get(url.to_owned()).send()?;
-        let html = page.text()?;
-        let images = image::parse_html_images(&html);
+        self.execute(endpoint::images(&self.urls, name)?)
+    }
+
+    /// Downloads a release of a mod, returning the contents of its zip file.
+    ///
+    /// This requires the [`Credentials`] of a Factorio account to be
+    /// configured on the client, see [`ApiClientBuilder::credentials`].
+    pub fn download_release(&self, release: &Release) -> Result<Vec<u8>> {
+        self.execute(endpoint::download_release(
+            &self.urls,
+            release,
+            self.credentials.as_ref(),
+        )?)
+    }
 
-        Ok(images)
+    /// Downloads the full size version of one of a mod's images,
+    /// as returned by [`images`][ApiClient::images].
+    pub fn download_image(&self, image: &Image) -> Result<Vec<u8>> {
+        self.execute(endpoint::download_image(image)?)
     }
 
     pub fn upload_image<S: Into<String>, P: AsRef<Path>>(
@@ -189,23 +228,27 @@ impl ApiClient {
         name: S,
         path: P,
     ) -> Result<ImageUploadResponse> {
-        let init = self.add_image(name)?;
-        let url = init.upload_url;
-        let form = Form::new().file("image", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read image file: {:?}", e),
-                None,
-            )
-        })?;
+        self.upload_image_inner(name.into(), path.as_ref(), None)
+    }
 
-        self.send(self.client.post(url).multipart(form), false)
+    /// Like [`upload_image`][ApiClient::upload_image], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub fn upload_image_with_progress<S, P, F>(
+        &self,
+        name: S,
+        path: P,
+        progress: F,
+    ) -> Result<ImageUploadResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.upload_image_inner(name.into(), path.as_ref(), Some(Arc::new(progress)))
     }
 
     pub fn edit_images(&self, data: ImageEditRequest) -> Result<ImageEditResponse> {
-        let container: FormContainer<Form> = data.into();
-        let form = container.into_inner();
-        self.post("v2/mods/images/edit", true, |r| r.multipart(form))
+        self.execute(endpoint::edit_images(&self.urls, data)?)
     }
 
     pub fn publish<S: Into<String>, P: AsRef<Path>>(
@@ -214,121 +257,147 @@ impl ApiClient {
         data: PublishRequest,
         path: P,
     ) -> Result<PublishResponse> {
-        let init = self.init_publish(name)?;
-        let url = init.upload_url;
-        let container: FormContainer<Form> = data.into();
-        let mut form = container.into_inner();
-        form = form.file("file", path).map_err(|e| {
-            ApiError::new(
-                ApiErrorKind::ImageIo,
-                format!("Could not read mod file {:?}", e),
-                None,
-            )
-        })?;
+        self.publish_inner(name.into(), data, path.as_ref(), None)
+    }
 
-        self.send(self.client.post(url).multipart(form), false)
+    /// Like [`publish`][ApiClient::publish], but calls `progress` with the
+    /// amount of bytes sent so far and the total as the file is uploaded.
+    pub fn publish_with_progress<S, P, F>(
+        &self,
+        name: S,
+        data: PublishRequest,
+        path: P,
+        progress: F,
+    ) -> Result<PublishResponse>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.publish_inner(name.into(), data, path.as_ref(), Some(Arc::new(progress)))
     }
 
     /// Get information about the latest available releases of the game.
     pub fn latest_releases(&self) -> Result<LatestReleases> {
-        self.get(self.game_url("latest-releases")?, false, |r| r)
+        self.execute(endpoint::latest_releases(&self.urls)?)
     }
 
-    fn init_upload<T: Into<String>>(&self, name: T) -> Result<InitUploadResponse> {
-        let form = Form::new().text("mod", name.into());
-        self.post("v2/mods/upload", true, |r| r.multipart(form))
+    fn upload_inner(
+        &self,
+        name: String,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<UploadResponse> {
+        let init = self.execute(endpoint::init_upload(&self.urls, &name)?)?;
+        let call = endpoint::upload(init.upload_url, path)?.with_progress(progress);
+
+        self.execute(call)
     }
 
-    fn add_image<T: Into<String>>(&self, name: T) -> Result<ImageAddResponse> {
-        self.post("v2/mods/images/add", true, |r| {
-            r.multipart(Form::new().text("mod", name.into()))
-        })
+    fn upload_image_inner(
+        &self,
+        name: String,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<ImageUploadResponse> {
+        let init = self.execute(endpoint::add_image(&self.urls, &name)?)?;
+        let call = endpoint::upload_image(init.upload_url, path)?.with_progress(progress);
+
+        self.execute(call)
     }
 
-    fn init_publish<T: Into<String>>(&self, name: T) -> Result<InitPublishResponse> {
-        let form = Form::new().text("mod", name.into());
-        self.post("v2/mods/init_publish", true, |r| r.multipart(form))
+    fn publish_inner(
+        &self,
+        name: String,
+        data: PublishRequest,
+        path: &Path,
+        progress: Option<Progress>,
+    ) -> Result<PublishResponse> {
+        let init = self.execute(endpoint::init_publish(&self.urls, &name)?)?;
+        let call = endpoint::publish(init.upload_url, data, path)?.with_progress(progress);
+
+        self.execute(call)
     }
 
-    fn portal_url<T: AsRef<str>>(&self, path: T) -> Result<Url> {
-        self.urls.portal(path.as_ref()).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!("Failed to join portal base URL with path {}", path.as_ref()),
-                None,
-            )
-        })
+    /// Sends the request for a call, retrying it according to the configured
+    /// [`RetryPolicy`] if it fails with a transient error and is safe to repeat.
+    fn execute<T>(&self, call: Call<T>) -> Result<T> {
+        let mut attempt = 1;
+
+        loop {
+            let request = call.request(self.api_key.as_deref(), &self.user_agent)?;
+            debug!(
+                "Sending {} request to {} (attempt {})",
+                call.method(),
+                call.url(),
+                attempt
+            );
+            let result = self.transport.execute(request);
+
+            match call.retry_delay(&self.retry_policy, attempt, &result) {
+                Some(delay) => thread::sleep(delay),
+                None => return call.parse(result),
+            }
+
+            attempt += 1;
+        }
     }
+}
+
+impl FactorioApi for ApiClient {
+    type Output<'a, T: 'a> = Result<T>;
 
-    fn portal_api_url<T: AsRef<str>>(&self, path: T) -> Result<Url> {
-        self.urls.portal_api(path.as_ref()).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!(
-                    "Failed to join portal API base URL with path {}",
-                    path.as_ref()
-                ),
-                None,
-            )
-        })
+    fn search<'a>(&'a self, query: &'a SearchQuery) -> Result<SearchResponse> {
+        ApiClient::search(self, query)
     }
 
-    fn game_url(&self, path: &str) -> Result<Url> {
-        self.urls.game(path).map_err(|_| {
-            ApiError::new(
-                ApiErrorKind::UrlParseFailed,
-                format!("Failed to join game base URL with path {}", path),
-                None,
-            )
-        })
+    fn info_short<'a>(&'a self, name: &'a str) -> Result<ModShort> {
+        ApiClient::info_short(self, name)
     }
 
-    fn send<T>(&self, request: RequestBuilder, auth: bool) -> Result<T>
-    where
-        T: DeserializeOwned,
-    {
-        let mut request = request.header(header::USER_AGENT, "facti");
-        if auth {
-            if let Some(api_key) = &self.api_key {
-                request = request.bearer_auth(api_key)
-            } else {
-                return Err(ApiError::new(
-                    ApiErrorKind::MissingApiKey,
-                    "Missing API key",
-                    None,
-                ));
-            }
-        }
+    fn info_full<'a>(&'a self, name: &'a str) -> Result<ModFull> {
+        ApiClient::info_full(self, name)
+    }
 
-        let response = request.send()?;
+    fn upload<'a>(&'a self, name: &'a str, path: &'a Path) -> Result<UploadResponse> {
+        ApiClient::upload(self, name, path)
+    }
 
-        if response.status().is_success() {
-            Ok(response.json::<T>()?)
-        } else {
-            Err(response.into())
-        }
+    fn edit_details(&self, data: ModDetailsRequest) -> Result<ModDetailsResponse> {
+        ApiClient::edit_details(self, data)
     }
 
-    fn get<T, U, F>(&self, url: U, auth: bool, f: F) -> Result<T>
-    where
-        T: DeserializeOwned,
-        U: Into<Url>,
-        F: FnOnce(RequestBuilder) -> RequestBuilder,
-    {
-        let request = f(self.client.get(url.into()));
+    fn images<'a>(&'a self, name: &'a str) -> Result<Vec<Image>> {
+        ApiClient::images(self, name)
+    }
 
-        self.send::<T>(request, auth)
+    fn download_release<'a>(&'a self, release: &'a Release) -> Result<Vec<u8>> {
+        ApiClient::download_release(self, release)
     }
 
-    fn post<T, F>(&self, path: &str, auth: bool, f: F) -> Result<T>
-    where
-        T: DeserializeOwned,
-        F: FnOnce(RequestBuilder) -> RequestBuilder,
-    {
-        let url = self.portal_api_url(path)?;
-        let request = f(self.client.post(url));
+    fn download_image<'a>(&'a self, image: &'a Image) -> Result<Vec<u8>> {
+        ApiClient::download_image(self, image)
+    }
+
+    fn upload_image<'a>(&'a self, name: &'a str, path: &'a Path) -> Result<ImageUploadResponse> {
+        ApiClient::upload_image(self, name, path)
+    }
 
-        self.send::<T>(request, auth)
+    fn edit_images(&self, data: ImageEditRequest) -> Result<ImageEditResponse> {
+        ApiClient::edit_images(self, data)
+    }
+
+    fn publish<'a>(
+        &'a self,
+        name: &'a str,
+        data: PublishRequest,
+        path: &'a Path,
+    ) -> Result<PublishResponse> {
+        ApiClient::publish(self, name, data, path)
+    }
+
+    fn latest_releases(&self) -> Result<LatestReleases> {
+        ApiClient::latest_releases(self)
     }
 }
 
@@ -338,4 +407,18 @@ impl Default for ApiClient {
     }
 }
 
-api_client_builder!(reqwest::blocking::Client, ApiClient);
+#[cfg(feature = "reqwest")]
+fn default_transport(
+    options: &HttpOptions,
+) -> core::result::Result<Box<dyn Transport>, TransportError> {
+    Ok(Box::new(super::ReqwestTransport::with_options(options)?))
+}
+
+#[cfg(not(feature = "reqwest"))]
+fn default_transport(
+    _options: &HttpOptions,
+) -> core::result::Result<Box<dyn Transport>, TransportError> {
+    Ok(Box::new(crate::transport::MissingTransport))
+}
+
+api_client_builder!(Transport, ApiClient);
@@ -1,20 +1,117 @@
-use std::borrow::Cow;
+use std::time::Duration;
 
-use crate::reqwest::FormLike;
+use crate::transport::{
+    DEFAULT_TIMEOUT, HttpOptions, Request, Response, Transport, TransportError, TransportErrorKind,
+};
 
-/// Sets up wrapper methods to interact with [`reqwest::blocking::multipart::Form`]
-/// when contained in a [`crate::reqwest::FormContainer`].
-#[doc(hidden)]
-impl FormLike for reqwest::blocking::multipart::Form {
-    fn new() -> Self {
+/// The default blocking [`Transport`], based on [`reqwest::blocking::Client`].
+///
+/// # Optional but default
+///
+/// This requires the `reqwest` feature to be enabled,
+/// which is part of the default enabled features.
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+    /// Timeout for requests that do not upload files, or [`None`]
+    /// to leave timeouts to the client.
+    timeout: Option<Duration>,
+}
+
+impl ReqwestTransport {
+    /// Constructs a new [`ReqwestTransport`] using a default client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the TLS backend cannot be initialized, like
+    /// [`reqwest::blocking::Client::new`].
+    pub fn new() -> Self {
+        Self::with_options(&HttpOptions::default()).expect("Failed to build the HTTP client")
+    }
+
+    /// Constructs a new [`ReqwestTransport`] with a client configured
+    /// according to the given options.
+    ///
+    /// The read timeout (30 seconds if not set) limits the whole of each
+    /// request that does not upload files. Uploads are not limited, as
+    /// sending a large mod can take much longer than that.
+    pub fn with_options(options: &HttpOptions) -> Result<Self, TransportError> {
+        // Timeouts are set per request instead
+        let mut builder = reqwest::blocking::Client::builder().timeout(None);
+
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+        }
+
+        for pem in &options.root_certificates {
+            for certificate in reqwest::Certificate::from_pem_bundle(pem)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            timeout: Some(options.read_timeout.unwrap_or(DEFAULT_TIMEOUT)),
+        })
+    }
+
+    /// Constructs a new [`ReqwestTransport`] using the given client.
+    ///
+    /// Requests use the timeout configured on the client, if any.
+    pub fn with_client(client: reqwest::blocking::Client) -> Self {
+        Self {
+            client,
+            timeout: None,
+        }
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
         Self::new()
     }
+}
+
+impl Transport for ReqwestTransport {
+    fn execute(&self, request: Request) -> Result<Response, TransportError> {
+        let is_upload = request.body().has_files();
+        let request = request.map(|body| {
+            if body.is_empty() {
+                reqwest::blocking::Body::from(Vec::new())
+            } else {
+                reqwest::blocking::Body::sized(body.reader(), body.len())
+            }
+        });
+        let mut request = reqwest::blocking::Request::try_from(request)
+            .map_err(|e| TransportError::new(TransportErrorKind::Other, e))?;
+        if !is_upload {
+            *request.timeout_mut() = self.timeout;
+        }
+        let response = self.client.execute(request)?;
+
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+
+        builder
+            .body(response.bytes()?.to_vec())
+            .map_err(|e| TransportError::new(TransportErrorKind::Other, e))
+    }
+}
 
-    fn text<T, U>(self, name: T, value: U) -> Self
-    where
-        T: Into<Cow<'static, str>>,
-        U: Into<Cow<'static, str>>,
-    {
-        self.text(name, value)
+impl super::ApiClientBuilder {
+    /// Sets the underlying [`reqwest::blocking::Client`] to use.
+    ///
+    /// This is a shorthand for setting a [`ReqwestTransport`] with the given client.
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    pub fn client(&mut self, client: reqwest::blocking::Client) -> &mut Self {
+        self.transport(ReqwestTransport::with_client(client))
     }
 }
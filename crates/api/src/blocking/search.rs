@@ -0,0 +1,54 @@
+use crate::{
+    data::portal::{ModSummary, SearchQuery},
+    error::ApiError,
+    search::SearchPager,
+};
+
+use super::ApiClient;
+
+/// An [`Iterator`] over all results of a search on the mod portal.
+///
+/// Pages are fetched lazily as the iterator is advanced.
+/// If fetching a page fails, the error is yielded and iteration stops.
+///
+/// Constructed by calling [`ApiClient::search_all`].
+pub struct SearchAll<'a> {
+    client: &'a ApiClient,
+    pager: Option<SearchPager>,
+}
+
+impl<'a> SearchAll<'a> {
+    pub(crate) fn new(client: &'a ApiClient, query: SearchQuery) -> Self {
+        Self {
+            client,
+            pager: Some(SearchPager::new(query)),
+        }
+    }
+}
+
+impl Iterator for SearchAll<'_> {
+    type Item = Result<ModSummary, ApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let pager = self.pager.as_mut()?;
+
+            if let Some(result) = pager.pop() {
+                return Some(Ok(result));
+            }
+
+            let Some(query) = pager.next_query() else {
+                self.pager = None;
+                return None;
+            };
+
+            match self.client.search(&query) {
+                Ok(response) => pager.push(response),
+                Err(e) => {
+                    self.pager = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 macro_rules! api_client_builder {
-    ($base_client:ty, $api_client:ident) => {
+    ($transport:ident, $api_client:ident) => {
         /// A builder for
         #[doc = concat!("[`", stringify!($api_client), "`].")]
         ///
@@ -7,11 +7,15 @@ macro_rules! api_client_builder {
         #[doc = concat!("[`", stringify!($api_client), "::builder()`].")]
         #[derive(Default)]
         pub struct ApiClientBuilder {
-            client: Option<$base_client>,
-            portal_base_url: Option<Url>,
-            portal_api_base_url: Option<Url>,
-            game_base_url: Option<Url>,
+            transport: Option<Box<dyn $transport>>,
+            portal_base_url: Option<::url::Url>,
+            portal_api_base_url: Option<::url::Url>,
+            game_base_url: Option<::url::Url>,
             api_key: Option<String>,
+            credentials: Option<$crate::data::portal::Credentials>,
+            user_agent: Option<String>,
+            http_options: $crate::transport::HttpOptions,
+            retry_policy: Option<$crate::retry::RetryPolicy>,
         }
 
         impl ApiClientBuilder {
@@ -19,18 +23,20 @@ macro_rules! api_client_builder {
                 Default::default()
             }
 
-            /// Sets the underlying [`reqwest`] client to use.
+            /// Sets the transport used to send requests.
             ///
-            /// If this is not configured, it will use the default client.
-            pub fn client(&mut self, client: $base_client) -> &mut Self {
-                self.client = Some(client);
+            /// If this is not configured, it will use a transport based on
+            /// [`reqwest`](https://docs.rs/reqwest) when the `reqwest` feature
+            /// is enabled, otherwise every request will fail.
+            pub fn transport<T: $transport + 'static>(&mut self, transport: T) -> &mut Self {
+                self.transport = Some(Box::new(transport));
                 self
             }
 
             /// Configures the base URL for the mod portal (non-API resources).
             ///
             /// If not configured, it will default to [`crate::DEFAULT_PORTAL_BASE_URL`].
-            pub fn portal_base_url<T: Into<Url>>(&mut self, base_url: T) -> &mut Self {
+            pub fn portal_base_url<T: Into<::url::Url>>(&mut self, base_url: T) -> &mut Self {
                 self.portal_base_url = Some(base_url.into());
                 self
             }
@@ -38,7 +44,7 @@ macro_rules! api_client_builder {
             /// Configures the base URL for the mod portal API.
             ///
             /// If not configured, it will default to [`crate::DEFAULT_PORTAL_API_BASE_URL`].
-            pub fn portal_api_base_url<T: Into<Url>>(&mut self, base_url: T) -> &mut Self {
+            pub fn portal_api_base_url<T: Into<::url::Url>>(&mut self, base_url: T) -> &mut Self {
                 self.portal_api_base_url = Some(base_url.into());
                 self
             }
@@ -46,7 +52,7 @@ macro_rules! api_client_builder {
             /// Configures the base URL for the game API.
             ///
             /// If not configured, it will default to [`crate::DEFAULT_GAME_BASE_URL`].
-            pub fn game_base_url<T: Into<Url>>(&mut self, base_url: T) -> &mut Self {
+            pub fn game_base_url<T: Into<::url::Url>>(&mut self, base_url: T) -> &mut Self {
                 self.game_base_url = Some(base_url.into());
                 self
             }
@@ -59,31 +65,115 @@ macro_rules! api_client_builder {
                 self
             }
 
+            /// Configures the credentials of a Factorio account,
+            /// used to download releases of mods.
+            ///
+            /// If not configured, releases cannot be downloaded.
+            pub fn credentials<U: Into<String>, T: Into<String>>(
+                &mut self,
+                username: U,
+                token: T,
+            ) -> &mut Self {
+                self.credentials = Some($crate::data::portal::Credentials {
+                    username: username.into(),
+                    token: token.into(),
+                });
+                self
+            }
+
+            /// Configures the `User-Agent` header to send with requests.
+            ///
+            /// If not configured, it will default to [`crate::DEFAULT_USER_AGENT`].
+            pub fn user_agent<T: Into<String>>(&mut self, user_agent: T) -> &mut Self {
+                self.user_agent = Some(user_agent.into());
+                self
+            }
+
+            /// Configures a proxy to send all requests through.
+            ///
+            /// Like the other HTTP options, this only applies to the default
+            /// transport and is ignored if a custom transport is set.
+            pub fn proxy<T: Into<::url::Url>>(&mut self, proxy: T) -> &mut Self {
+                self.http_options.proxy = Some(proxy.into());
+                self
+            }
+
+            /// Adds PEM encoded root certificates to trust,
+            /// in addition to the system ones.
+            ///
+            /// This can be called several times to add more certificates.
+            pub fn root_certificate<T: Into<Vec<u8>>>(&mut self, pem: T) -> &mut Self {
+                self.http_options.root_certificates.push(pem.into());
+                self
+            }
+
+            /// Configures the timeout for connecting to the server.
+            pub fn connect_timeout(&mut self, timeout: ::std::time::Duration) -> &mut Self {
+                self.http_options.connect_timeout = Some(timeout);
+                self
+            }
+
+            /// Configures the timeout for requests, except those uploading files.
+            pub fn read_timeout(&mut self, timeout: ::std::time::Duration) -> &mut Self {
+                self.http_options.read_timeout = Some(timeout);
+                self
+            }
+
+            /// Configures how requests should be retried on transient failures.
+            ///
+            /// Only requests that are safe to repeat will ever be retried.
+            ///
+            /// If not configured, it will default to [`RetryPolicy::new`][crate::retry::RetryPolicy::new].
+            /// Use [`RetryPolicy::none`][crate::retry::RetryPolicy::none] to disable retries.
+            pub fn retry_policy(&mut self, retry_policy: $crate::retry::RetryPolicy) -> &mut Self {
+                self.retry_policy = Some(retry_policy);
+                self
+            }
+
             /// Builds a finished
             #[doc = concat!("[`", stringify!($api_client), "`].")]
-            pub fn build(self) -> $api_client {
-                let client = self.client.unwrap_or_default();
+            ///
+            /// # Errors
+            ///
+            /// Fails if the default transport could not be set up with the
+            /// configured HTTP options, for example due to an invalid certificate.
+            pub fn build(self) -> ::core::result::Result<$api_client, $crate::error::ApiError> {
+                let transport = match self.transport {
+                    Some(transport) => transport,
+                    None => default_transport(&self.http_options).map_err(|e| {
+                        $crate::error::ApiError::new(
+                            $crate::error::ApiErrorKind::ClientBuildFailed,
+                            format!("Failed to set up HTTP client: {}", e),
+                            Some(Box::new(e)),
+                        )
+                    })?,
+                };
                 let portal_base_url = self
                     .portal_base_url
-                    .unwrap_or(Url::parse($crate::DEFAULT_PORTAL_BASE_URL).unwrap());
+                    .unwrap_or(::url::Url::parse($crate::DEFAULT_PORTAL_BASE_URL).unwrap());
                 let portal_api_base_url = self
                     .portal_api_base_url
-                    .unwrap_or(Url::parse($crate::DEFAULT_PORTAL_API_BASE_URL).unwrap());
+                    .unwrap_or(::url::Url::parse($crate::DEFAULT_PORTAL_API_BASE_URL).unwrap());
                 let game_base_url = self
                     .game_base_url
-                    .unwrap_or(Url::parse($crate::DEFAULT_GAME_BASE_URL).unwrap());
+                    .unwrap_or(::url::Url::parse($crate::DEFAULT_GAME_BASE_URL).unwrap());
 
-                let urls = FactorioUrls {
+                let urls = $crate::FactorioUrls {
                     portal_base_url,
                     portal_api_base_url,
                     game_base_url,
                 };
 
-                $api_client {
-                    client,
+                Ok($api_client {
+                    transport,
                     urls,
                     api_key: self.api_key,
-                }
+                    credentials: self.credentials,
+                    user_agent: self
+                        .user_agent
+                        .unwrap_or_else(|| $crate::DEFAULT_USER_AGENT.to_owned()),
+                    retry_policy: self.retry_policy.unwrap_or_default(),
+                })
             }
         }
     };
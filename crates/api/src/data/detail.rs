@@ -1,10 +1,10 @@
-use std::fmt::Display;
+use std::{convert::Infallible, fmt::Display, str::FromStr};
 
-use serde::{Deserialize, Serialize, Serializer};
-use strum::Display;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum::{Display, EnumString};
 use url::Url;
 
-use crate::reqwest::{FormContainer, FormLike};
+use crate::form::Form;
 
 /// Describes a request to modify details for a mod.
 ///
@@ -12,10 +12,15 @@ use crate::reqwest::{FormContainer, FormLike};
 /// all other fields can be set to modify that particular property of the mod.
 ///
 /// [You can find more information about the Mod details structure on the Factorio wiki.](https://wiki.factorio.com/Mod_details_API)
-#[derive(Clone, Debug, Serialize)]
+///
+/// It can also be deserialized, e.g. to read the changes from a file.
+/// Unknown fields are rejected to catch typos, and the `mod` field may be
+/// left out, in which case `name` is empty and has to be set afterwards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ModDetailsRequest {
     /// Internal name of the mod whose details are to be changed.
-    #[serde(rename = "mod")]
+    #[serde(rename = "mod", default)]
     pub name: String,
 
     /// Display name of the mod.
@@ -87,9 +92,9 @@ impl ModDetailsRequest {
     }
 }
 
-impl<T: FormLike> From<ModDetailsRequest> for FormContainer<T> {
+impl From<ModDetailsRequest> for Form {
     fn from(value: ModDetailsRequest) -> Self {
-        let mut form = T::new().text("mod", value.name);
+        let mut form = Form::new().text("mod", value.name);
 
         if let Some(title) = value.title {
             form = form.text("title", title);
@@ -108,6 +113,11 @@ impl<T: FormLike> From<ModDetailsRequest> for FormContainer<T> {
         }
 
         if let Some(tags) = value.tags {
+            // Without any tags field the portal keeps the existing tags,
+            // so clearing them needs an explicit empty value
+            if tags.is_empty() {
+                form = form.text("tags", "");
+            }
             for tag in tags {
                 form = form.text("tags", tag.to_string());
             }
@@ -133,7 +143,7 @@ impl<T: FormLike> From<ModDetailsRequest> for FormContainer<T> {
             form = form.text("faq", faq);
         }
 
-        FormContainer(form)
+        form
     }
 }
 
@@ -241,13 +251,15 @@ pub struct ModDetailsResponse {
 /// Categories a mod can belong to.
 ///
 /// A mod can only have a single category assigned.
-#[derive(Default, Copy, Clone, Display, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+///
+/// Categories added to the portal after this version of the crate was
+/// released are kept as [`Unknown`][Category::Unknown], so they survive
+/// being deserialized and serialized again.
+#[derive(Default, Clone, Display, EnumString, Debug, Eq, PartialEq, Hash)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Category {
     #[default]
-    #[serde(rename = "no-category", alias = "")]
-    #[strum(serialize = "no-category")]
+    #[strum(to_string = "no-category", serialize = "")]
     None,
 
     /// Mods introducing new content into the game.
@@ -275,13 +287,40 @@ pub enum Category {
     /// Lua libraries for use by other mods and submods that are parts of a
     /// larger mod.
     Internal,
+
+    /// A category not known to this version of the crate.
+    #[strum(default, to_string = "{0}")]
+    Unknown(String),
+}
+
+impl Category {
+    /// All categories known to this version of the crate.
+    pub const KNOWN: &'static [Category] = &[
+        Category::None,
+        Category::Content,
+        Category::Overhaul,
+        Category::Tweaks,
+        Category::Utilities,
+        Category::Scenarios,
+        Category::ModPacks,
+        Category::Localizations,
+        Category::Internal,
+    ];
+
+    /// Checks whether this is one of the [known][Category::KNOWN] categories.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Category::Unknown(_))
+    }
 }
 
 /// Tags a mod can have.
 ///
 /// Mods can have several tags assigned.
-#[derive(Copy, Clone, Display, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+///
+/// Tags added to the portal after this version of the crate was released
+/// are kept as [`Unknown`][Tag::Unknown], so they survive being
+/// deserialized and serialized again.
+#[derive(Clone, Display, EnumString, Debug, Eq, PartialEq, Hash)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Tag {
     /// Transportation of the player, be it vehicles or teleporters.
@@ -331,6 +370,37 @@ pub enum Tag {
 
     /// Play it your way.
     Cheats,
+
+    /// A tag not known to this version of the crate.
+    #[strum(default, to_string = "{0}")]
+    Unknown(String),
+}
+
+impl Tag {
+    /// All tags known to this version of the crate.
+    pub const KNOWN: &'static [Tag] = &[
+        Tag::Transportation,
+        Tag::Logistics,
+        Tag::Trains,
+        Tag::Combat,
+        Tag::Armor,
+        Tag::Enemies,
+        Tag::Environment,
+        Tag::Mining,
+        Tag::Fluids,
+        Tag::LogisticNetwork,
+        Tag::CircuitNetwork,
+        Tag::Manufacturing,
+        Tag::Power,
+        Tag::Storage,
+        Tag::Blueprints,
+        Tag::Cheats,
+    ];
+
+    /// Checks whether this is one of the [known][Tag::KNOWN] tags.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Tag::Unknown(_))
+    }
 }
 
 /// Licenses a mod can use.
@@ -338,13 +408,12 @@ pub enum Tag {
 /// Any other license is also possible to use by way of the `Custom` variant.
 ///
 /// If using the `Custom` variant, supply the identifier of the license in lowercase.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum License {
     /// A permissive license that is short and to the point.
     /// It lets people do anything with your code with proper attribution and without warranty.
     ///
     /// [MIT license](https://opensource.org/licenses/MIT)
-    #[serde(rename = "default_mit")]
     MIT,
 
     /// The GNU GPL is the most widely used free software license and has a
@@ -353,7 +422,6 @@ pub enum License {
     /// made available under the same license.
     ///
     /// [GNU GPLv3 license](https://opensource.org/licenses/gpl-3.0)
-    #[serde(rename = "default_gnugplv3")]
     GPLv3,
 
     /// Version 3 of the GNU LGPL is an additional set of permissions to the
@@ -362,7 +430,6 @@ pub enum License {
     /// restriction.
     ///
     /// [GNU LGPLv3 license](https://opensource.org/licenses/lgpl-3.0)
-    #[serde(rename = "default_gnulgplv3")]
     LGPLv3,
 
     /// The Mozilla Public License (MPL 2.0) is maintained by the Mozilla foundation.
@@ -370,14 +437,12 @@ pub enum License {
     /// license and the reciprocal GPL license.
     ///
     /// [Mozilla Public License 2.0](https://opensource.org/licenses/mpl-2.0)
-    #[serde(rename = "default_mozilla2")]
     MPL2,
 
     /// A permissive license that also provides an express grant of patent
     /// rights from contributors to users.
     ///
     /// [Apache License 2.0](https://opensource.org/licenses/apache-2.0)
-    #[serde(rename = "default_apache2")]
     Apache2,
 
     /// Because copyright is automatic in most countries, the Unlicense is a
@@ -387,7 +452,6 @@ pub enum License {
     /// It also includes the no-warranty statement from the MIT/X11 license.
     ///
     /// [The Unlicense](https://unlicense.org/)
-    #[serde(rename = "default_unlicense")]
     Unlicense,
 
     /// Custom license.
@@ -398,12 +462,11 @@ pub enum License {
     /// `mods.factorio.com/licenses/edit/$ID`
     ///
     /// [my-licenses]: https://mods.factorio.com/licenses
-    #[serde(
-        untagged,
-        serialize_with = "custom_license_serialize",
-        deserialize_with = "custom_license_deserialize"
-    )]
     Custom(String),
+
+    /// A license not known to this version of the crate,
+    /// holding its identifier as-is.
+    Unknown(String),
 }
 
 impl License {
@@ -413,31 +476,6 @@ impl License {
     }
 }
 
-/// Custom license serializer.
-///
-/// Used to properly serialize [`License::Custom`].
-fn custom_license_serialize<S: Serializer>(id: &String, serializer: S) -> Result<S::Ok, S::Error> {
-    serializer.serialize_str(format!("custom_{}", id).as_str())
-}
-
-/// Custom license deserializer.
-///
-/// Used to properly deserialize [`License::Custom`].
-fn custom_license_deserialize<'de, D: serde::Deserializer<'de>>(
-    deserializer: D,
-) -> Result<String, D::Error> {
-    let s = String::deserialize(deserializer)?;
-
-    if let Some(custom) = s.strip_prefix("custom_") {
-        Ok(custom.to_string())
-    } else {
-        Err(serde::de::Error::custom(format!(
-            "invalid custom license: {}",
-            s
-        )))
-    }
-}
-
 impl Display for License {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -448,6 +486,130 @@ impl Display for License {
             License::Apache2 => write!(f, "default_apache2"),
             License::Unlicense => write!(f, "default_unlicense"),
             License::Custom(id) => write!(f, "custom_{}", id),
+            License::Unknown(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+impl FromStr for License {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "default_mit" => License::MIT,
+            "default_gnugplv3" => License::GPLv3,
+            "default_gnulgplv3" => License::LGPLv3,
+            "default_mozilla2" => License::MPL2,
+            "default_apache2" => License::Apache2,
+            "default_unlicense" => License::Unlicense,
+            other => match other.strip_prefix("custom_") {
+                Some(id) => License::Custom(id.to_owned()),
+                None => License::Unknown(other.to_owned()),
+            },
+        })
+    }
+}
+
+/// Implements [`Serialize`] and [`Deserialize`] for enums with a catch-all
+/// variant, using their [`Display`] and (infallible) [`FromStr`] impls.
+macro_rules! string_serde {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let s = String::deserialize(deserializer)?;
+                    s.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+string_serde!(Category, Tag, License);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_values_round_trip() {
+        for category in Category::KNOWN {
+            let json = serde_json::to_string(category).unwrap();
+            assert_eq!(&serde_json::from_str::<Category>(&json).unwrap(), category);
         }
+
+        for tag in Tag::KNOWN {
+            let json = serde_json::to_string(tag).unwrap();
+            assert_eq!(&serde_json::from_str::<Tag>(&json).unwrap(), tag);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&Tag::LogisticNetwork).unwrap(),
+            r#""logistic-network""#
+        );
+        assert_eq!(
+            serde_json::to_string(&Category::None).unwrap(),
+            r#""no-category""#
+        );
+    }
+
+    #[test]
+    fn test_empty_category() {
+        assert_eq!(
+            serde_json::from_str::<Category>(r#""""#).unwrap(),
+            Category::None
+        );
+    }
+
+    #[test]
+    fn test_unknown_values_round_trip() {
+        let category: Category = serde_json::from_str(r#""space-age""#).unwrap();
+        assert_eq!(category, Category::Unknown("space-age".to_owned()));
+        assert!(!category.is_known());
+        assert_eq!(serde_json::to_string(&category).unwrap(), r#""space-age""#);
+
+        let tag: Tag = serde_json::from_str(r#""planets""#).unwrap();
+        assert_eq!(tag, Tag::Unknown("planets".to_owned()));
+        assert_eq!(serde_json::to_string(&tag).unwrap(), r#""planets""#);
+
+        let license: License = serde_json::from_str(r#""default_cc0""#).unwrap();
+        assert_eq!(license, License::Unknown("default_cc0".to_owned()));
+        assert_eq!(serde_json::to_string(&license).unwrap(), r#""default_cc0""#);
+    }
+
+    #[test]
+    fn test_license() {
+        assert_eq!(
+            serde_json::from_str::<License>(r#""default_mit""#).unwrap(),
+            License::MIT
+        );
+        assert_eq!(
+            serde_json::from_str::<License>(r#""custom_abc123""#).unwrap(),
+            License::custom("abc123")
+        );
+        assert_eq!(
+            serde_json::to_string(&License::custom("abc123")).unwrap(),
+            r#""custom_abc123""#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_details_request() {
+        let request: ModDetailsRequest = serde_json::from_str(
+            r#"{"title": "My Mod", "tags": ["trains"], "license": "default_mit"}"#,
+        )
+        .unwrap();
+        assert_eq!(request.name, "");
+        assert_eq!(request.title.as_deref(), Some("My Mod"));
+        assert_eq!(request.tags, Some(vec![Tag::Trains]));
+        assert_eq!(request.license, Some(License::MIT));
+
+        assert!(serde_json::from_str::<ModDetailsRequest>(r#"{"titel": "Typo"}"#).is_err());
     }
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 use url::Url;
 
-use crate::reqwest::{FormContainer, FormLike};
+use crate::form::Form;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Image {
@@ -28,6 +28,20 @@ impl Image {
             .unwrap(),
         }
     }
+
+    /// Creates an image from the absolute URL of its thumbnail,
+    /// which is next to the full size image.
+    fn from_thumbnail_url(id: &str, thumbnail_url: Url) -> Self {
+        let mut url = thumbnail_url.clone();
+        let path = url.path().replace(".thumb.", ".");
+        url.set_path(&path);
+
+        Self {
+            id: id.to_owned(),
+            url,
+            thumbnail_url,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,16 +53,16 @@ pub struct ImageUploadResponse {
     pub thumbnail_url: Url,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ImageEditRequest {
     pub name: String,
     pub images: Vec<String>,
 }
 
-impl<T: FormLike> From<ImageEditRequest> for FormContainer<T> {
+impl From<ImageEditRequest> for Form {
     fn from(request: ImageEditRequest) -> Self {
         let images = request.images.join(",");
-        FormContainer(T::new().text("mod", request.name).text("images", images))
+        Form::new().text("mod", request.name).text("images", images)
     }
 }
 
@@ -74,7 +88,11 @@ pub(crate) fn parse_html_images(html: &str) -> Vec<Image> {
         let elem = element.value();
         if let Some(id) = elem.attr("data-filename") {
             debug!("Found image with ID {}", id);
-            images.push(Image::new(id));
+            let image = match elem.attr("src").map(Url::parse) {
+                Some(Ok(thumbnail_url)) => Image::from_thumbnail_url(id, thumbnail_url),
+                _ => Image::new(id),
+            };
+            images.push(image);
         }
     }
 
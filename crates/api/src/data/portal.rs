@@ -12,7 +12,7 @@ use super::{
     sorting::{SortMode, SortOrder},
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SearchQuery {
     pub hide_deprecated: bool,
     pub page: u32,
@@ -50,9 +50,123 @@ where
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SearchResponse {
     pub pagination: Pagination,
-    pub results: Vec<SearchResult>,
+    pub results: Vec<ModSummary>,
 }
 
+/// A mod as it is listed in search results.
+///
+/// Returned by [`ApiClient::search`][crate::blocking::ApiClient::search].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModSummary {
+    #[serde(rename = "downloads_count")]
+    pub download_count: u32,
+
+    /// The latest release of the mod.
+    ///
+    /// Not set when searching for specific mods with
+    /// [`SearchQuery::namelist`], use [`releases`][Self::releases] instead.
+    pub latest_release: Option<Release>,
+
+    pub name: String,
+    pub owner: String,
+
+    /// All releases of the mod.
+    ///
+    /// Only set when searching for specific mods with
+    /// [`SearchQuery::namelist`].
+    pub releases: Option<Vec<Release>>,
+
+    #[serde(default)]
+    pub summary: String,
+
+    #[serde(default)]
+    pub title: String,
+
+    #[serde(default)]
+    pub category: Category,
+
+    #[serde(rename = "thumbnail")]
+    pub thumbnail_path: Option<String>,
+}
+
+/// Short information about a mod, including all of its releases.
+///
+/// Returned by [`ApiClient::info_short`][crate::blocking::ApiClient::info_short].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModShort {
+    #[serde(rename = "downloads_count")]
+    pub download_count: u32,
+
+    pub name: String,
+    pub owner: String,
+    pub releases: Vec<Release>,
+
+    #[serde(default)]
+    pub summary: String,
+
+    #[serde(default)]
+    pub title: String,
+
+    #[serde(default)]
+    pub category: Category,
+
+    #[serde(rename = "thumbnail")]
+    pub thumbnail_path: Option<String>,
+}
+
+/// Full information about a mod.
+///
+/// Returned by [`ApiClient::info_full`][crate::blocking::ApiClient::info_full].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModFull {
+    #[serde(rename = "downloads_count")]
+    pub download_count: u32,
+
+    pub name: String,
+    pub owner: String,
+    pub releases: Vec<Release>,
+
+    #[serde(default)]
+    pub summary: String,
+
+    #[serde(default)]
+    pub title: String,
+
+    #[serde(default)]
+    pub category: Category,
+
+    #[serde(rename = "thumbnail")]
+    pub thumbnail_path: Option<String>,
+
+    pub changelog: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    /// Long description of the mod in markdown format.
+    #[serde(default)]
+    pub description: String,
+
+    pub source_url: Option<Url>,
+    pub homepage: Option<String>,
+
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+
+    pub license: Option<License>,
+
+    /// Whether the owner has marked the mod as deprecated.
+    ///
+    /// The portal only includes this field for deprecated mods.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+}
+
+/// Result of any of the search and info endpoints, with every field that
+/// any of them may return.
+///
+/// Superseded by [`ModSummary`], [`ModShort`] and [`ModFull`], which only
+/// have the fields the respective endpoint returns. All of them can be
+/// converted into this type with [`From`].
+#[deprecated(note = "Use `ModSummary`, `ModShort` or `ModFull` instead")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     #[serde(rename = "downloads_count")]
@@ -82,32 +196,221 @@ pub struct SearchResult {
     pub license: Option<License>,
 }
 
-impl Display for SearchResult {
+/// The release with the highest version.
+fn latest(releases: &[Release]) -> Option<&Release> {
+    releases.iter().max_by_key(|r| r.version)
+}
+
+fn fmt_mod(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    version: Option<Version>,
+    owner: &str,
+) -> std::fmt::Result {
+    f.write_str(name)?;
+
+    if let Some(version) = version {
+        write!(f, " v{}", version)?;
+    }
+
+    write!(f, " by {}", owner)
+}
+
+impl ModSummary {
+    /// The latest release of the mod, from either
+    /// [`latest_release`][Self::latest_release] or [`releases`][Self::releases].
+    pub fn latest(&self) -> Option<&Release> {
+        self.latest_release
+            .as_ref()
+            .or_else(|| self.releases.as_deref().and_then(latest))
+    }
+}
+
+impl ModShort {
+    /// The release with the highest version, if there are any releases.
+    pub fn latest_release(&self) -> Option<&Release> {
+        latest(&self.releases)
+    }
+}
+
+impl ModFull {
+    /// The release with the highest version, if there are any releases.
+    pub fn latest_release(&self) -> Option<&Release> {
+        latest(&self.releases)
+    }
+}
+
+impl Display for ModSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_mod(f, &self.name, self.latest().map(|r| r.version), &self.owner)
+    }
+}
+
+impl Display for ModShort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_mod(
+            f,
+            &self.name,
+            self.latest_release().map(|r| r.version),
+            &self.owner,
+        )
+    }
+}
+
+impl Display for ModFull {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.name)?;
+        fmt_mod(
+            f,
+            &self.name,
+            self.latest_release().map(|r| r.version),
+            &self.owner,
+        )
+    }
+}
 
-        let version: Option<Version> = match &self.latest_release {
+#[allow(deprecated)]
+impl Display for SearchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let version = match &self.latest_release {
             Some(release) => Some(release.version),
-            None => match &self.releases {
-                Some(releases) if !releases.is_empty() => {
-                    let mut clone = releases.to_vec();
-                    clone.sort_by_key(|r| r.version);
-                    Some(clone[0].version)
-                }
-                _ => None,
-            },
+            None => self.releases.as_deref().and_then(latest).map(|r| r.version),
         };
 
-        if let Some(version) = version {
-            write!(f, " v{}", version)?;
+        fmt_mod(f, &self.name, version, &self.owner)
+    }
+}
+
+impl From<ModFull> for ModShort {
+    fn from(value: ModFull) -> Self {
+        Self {
+            download_count: value.download_count,
+            name: value.name,
+            owner: value.owner,
+            releases: value.releases,
+            summary: value.summary,
+            title: value.title,
+            category: value.category,
+            thumbnail_path: value.thumbnail_path,
         }
+    }
+}
 
-        write!(f, " by {}", self.owner)?;
+impl From<ModShort> for ModSummary {
+    fn from(value: ModShort) -> Self {
+        Self {
+            download_count: value.download_count,
+            latest_release: value.latest_release().cloned(),
+            name: value.name,
+            owner: value.owner,
+            releases: Some(value.releases),
+            summary: value.summary,
+            title: value.title,
+            category: value.category,
+            thumbnail_path: value.thumbnail_path,
+        }
+    }
+}
 
-        Ok(())
+impl From<ModFull> for ModSummary {
+    fn from(value: ModFull) -> Self {
+        ModShort::from(value).into()
     }
 }
 
+#[allow(deprecated)]
+impl From<ModSummary> for SearchResult {
+    fn from(value: ModSummary) -> Self {
+        Self {
+            download_count: value.download_count,
+            latest_release: value.latest_release,
+            name: value.name,
+            owner: value.owner,
+            releases: value.releases,
+            summary: Some(value.summary),
+            title: Some(value.title),
+            category: Some(value.category),
+            thumbnail_path: value.thumbnail_path,
+            changelog: None,
+            created_at: None,
+            description: None,
+            source_url: None,
+            github_path: None,
+            homepage: None,
+            tags: None,
+            license: None,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<ModShort> for SearchResult {
+    fn from(value: ModShort) -> Self {
+        Self {
+            latest_release: None,
+            ..ModSummary::from(value).into()
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<ModFull> for SearchResult {
+    fn from(value: ModFull) -> Self {
+        let ModFull {
+            download_count,
+            name,
+            owner,
+            releases,
+            summary,
+            title,
+            category,
+            thumbnail_path,
+            changelog,
+            created_at,
+            description,
+            source_url,
+            homepage,
+            tags,
+            license,
+            deprecated: _,
+        } = value;
+
+        let short = ModShort {
+            download_count,
+            name,
+            owner,
+            releases,
+            summary,
+            title,
+            category,
+            thumbnail_path,
+        };
+
+        Self {
+            changelog,
+            created_at: Some(created_at),
+            description: Some(description),
+            source_url,
+            homepage,
+            tags: Some(tags),
+            license,
+            ..short.into()
+        }
+    }
+}
+
+/// Credentials of a Factorio account, needed to download releases of mods.
+///
+/// The token is the service token shown on the [Factorio profile][profile]
+/// page (or stored as `service-token` in the game's `player-data.json`),
+/// which is *not* the same as an API key.
+///
+/// [profile]: https://factorio.com/profile
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Credentials {
+    pub username: String,
+    pub token: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Release {
     #[serde(rename = "download_url")]
@@ -152,3 +455,81 @@ impl Default for SearchQuery {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn release(version: &str) -> serde_json::Value {
+        json!({
+            "download_url": format!("/download/my-mod/{}", version),
+            "file_name": format!("my-mod_{}.zip", version),
+            "info_json": { "factorio_version": "1.1" },
+            "released_at": "2023-01-01T00:00:00Z",
+            "version": version,
+            "sha1": "abc",
+        })
+    }
+
+    #[test]
+    fn test_summary_without_optional_fields() {
+        let summary: ModSummary = serde_json::from_value(json!({
+            "downloads_count": 3,
+            "name": "my-mod",
+            "owner": "me",
+        }))
+        .unwrap();
+
+        assert_eq!(summary.title, "");
+        assert_eq!(summary.category, Category::None);
+        assert!(summary.latest().is_none());
+        assert_eq!(summary.to_string(), "my-mod by me");
+    }
+
+    #[test]
+    fn test_full_into_summary() {
+        let full: ModFull = serde_json::from_value(json!({
+            "downloads_count": 3,
+            "name": "my-mod",
+            "owner": "me",
+            "releases": [release("1.2.0"), release("1.10.0"), release("1.3.0")],
+            "title": "My Mod",
+            "category": "tweaks",
+            "created_at": "2022-01-01T00:00:00Z",
+            "tags": ["trains"],
+        }))
+        .unwrap();
+
+        assert_eq!(full.to_string(), "my-mod v1.10.0 by me");
+
+        let summary = ModSummary::from(full);
+        assert_eq!(summary.title, "My Mod");
+        assert_eq!(summary.category, Category::Tweaks);
+        assert_eq!(
+            summary.latest_release.unwrap().version,
+            Version::new(1, 10, 0)
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_full_into_search_result() {
+        let full: ModFull = serde_json::from_value(json!({
+            "downloads_count": 3,
+            "name": "my-mod",
+            "owner": "me",
+            "releases": [release("1.0.0")],
+            "created_at": "2022-01-01T00:00:00Z",
+            "description": "Long description",
+        }))
+        .unwrap();
+
+        let result = SearchResult::from(full);
+        assert!(result.latest_release.is_none());
+        assert_eq!(result.releases.unwrap().len(), 1);
+        assert_eq!(result.description.as_deref(), Some("Long description"));
+        assert!(result.created_at.is_some());
+    }
+}
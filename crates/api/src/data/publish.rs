@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use url::Url;
 
-use crate::reqwest::{FormContainer, FormLike};
+use crate::form::Form;
 
 use super::detail::{Category, License};
 
@@ -46,9 +46,9 @@ impl PublishRequest {
     }
 }
 
-impl<T: FormLike> From<PublishRequest> for FormContainer<T> {
+impl From<PublishRequest> for Form {
     fn from(req: PublishRequest) -> Self {
-        let mut form = T::new();
+        let mut form = Form::new();
 
         if let Some(description) = req.description {
             form = form.text("description", description);
@@ -66,7 +66,7 @@ impl<T: FormLike> From<PublishRequest> for FormContainer<T> {
             form = form.text("source_url", source_url.to_string());
         }
 
-        FormContainer(form)
+        form
     }
 }
 
@@ -0,0 +1,368 @@
+//! Transport-agnostic descriptions of the requests made by the API clients.
+//!
+//! This contains no I/O: every client method is built from the [`Call`]s in
+//! here and the clients only differ in how they send them, which keeps the
+//! blocking and async clients from drifting apart.
+
+use std::{io, path::Path, sync::Arc, time::Duration};
+
+use http::{Method, header};
+use serde::de::DeserializeOwned;
+use tracing::warn;
+use url::Url;
+
+use crate::{
+    FactorioUrls,
+    data::{
+        detail::{ModDetailsRequest, ModDetailsResponse},
+        game::LatestReleases,
+        image::{
+            self, Image, ImageAddResponse, ImageEditRequest, ImageEditResponse, ImageUploadResponse,
+        },
+        portal::{Credentials, ModFull, ModShort, Release, SearchQuery, SearchResponse},
+        publish::{InitPublishResponse, PublishRequest, PublishResponse},
+        upload::{InitUploadResponse, UploadResponse},
+    },
+    error::{self, ApiError, ApiErrorKind},
+    form::Form,
+    retry::RetryPolicy,
+    transport::{Body, Progress, Request, Response, TransportError},
+};
+
+type Result<T> = core::result::Result<T, ApiError>;
+
+/// A single request to one of the Factorio APIs,
+/// along with how to interpret its response.
+pub(crate) struct Call<T> {
+    method: Method,
+    url: Url,
+    auth: bool,
+    retryable: bool,
+    form: Option<Form>,
+    progress: Option<Progress>,
+    parse: fn(&[u8]) -> Result<T>,
+}
+
+impl<T> Call<T> {
+    fn get(url: Url, parse: fn(&[u8]) -> Result<T>) -> Self {
+        Self {
+            method: Method::GET,
+            url,
+            auth: false,
+            retryable: true,
+            form: None,
+            progress: None,
+            parse,
+        }
+    }
+
+    fn post(url: Url, form: Form, parse: fn(&[u8]) -> Result<T>) -> Self {
+        Self {
+            method: Method::POST,
+            url,
+            auth: true,
+            retryable: true,
+            form: Some(form),
+            progress: None,
+            parse,
+        }
+    }
+
+    /// Marks the call as a one-time upload, which is not safe to repeat
+    /// and authorized by the upload URL itself rather than the API key.
+    fn once(mut self) -> Self {
+        self.retryable = false;
+        self.auth = false;
+        self
+    }
+
+    /// Sets a callback to report the progress of sending the request body to.
+    pub fn with_progress(mut self, progress: Option<Progress>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Decides whether to retry the call after the given (1-based) attempt
+    /// finished with `result`, returning how long to wait before retrying.
+    pub fn retry_delay(
+        &self,
+        policy: &RetryPolicy,
+        attempt: u32,
+        result: &core::result::Result<Response, TransportError>,
+    ) -> Option<Duration> {
+        if !self.retryable {
+            return None;
+        }
+
+        let delay = policy.retry_delay(attempt, result)?;
+        match result {
+            Ok(response) => warn!(
+                "{} request to {} failed with status {}, retrying in {:?}",
+                self.method,
+                self.url,
+                response.status(),
+                delay
+            ),
+            Err(e) => warn!(
+                "{} request to {} failed ({}), retrying in {:?}",
+                self.method, self.url, e, delay
+            ),
+        }
+
+        Some(delay)
+    }
+
+    /// Builds the HTTP request for (an attempt at) the call.
+    pub fn request(&self, api_key: Option<&str>, user_agent: &str) -> Result<Request> {
+        let mut builder = http::Request::builder()
+            .method(self.method.clone())
+            .uri(self.url.as_str())
+            .header(header::USER_AGENT, user_agent);
+
+        if self.auth {
+            let Some(api_key) = api_key else {
+                return Err(ApiError::new(
+                    ApiErrorKind::MissingApiKey,
+                    "Missing API key",
+                    None,
+                ));
+            };
+
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", api_key));
+        }
+
+        let mut body = match &self.form {
+            Some(form) => {
+                let (content_type, body) = form.encode();
+                builder = builder
+                    .header(header::CONTENT_TYPE, content_type)
+                    .header(header::CONTENT_LENGTH, body.len());
+                body
+            }
+            None => Body::empty(),
+        };
+
+        if let Some(progress) = &self.progress {
+            body.set_progress(Arc::clone(progress));
+        }
+
+        builder.body(body).map_err(|e| {
+            ApiError::new(
+                ApiErrorKind::InvalidRequest,
+                format!("Failed to build request for {}", self.url),
+                Some(Box::new(e)),
+            )
+        })
+    }
+
+    /// Interprets the outcome of (the last attempt at) the call.
+    ///
+    /// Errors are annotated with the endpoint that was called, and the
+    /// status of the response if there was one.
+    pub fn parse(&self, result: core::result::Result<Response, TransportError>) -> Result<T> {
+        let result = match result {
+            Ok(response) if response.status().is_success() => {
+                (self.parse)(response.body()).map_err(|e| e.with_status(response.status()))
+            }
+            Ok(response) => Err(error::from_response(&response)),
+            Err(e) => Err(e.into()),
+        };
+
+        result.map_err(|e| e.with_endpoint(&self.method, &self.url))
+    }
+}
+
+pub(crate) fn search(urls: &FactorioUrls, query: &SearchQuery) -> Result<Call<SearchResponse>> {
+    let mut url = portal_api_url(urls, "mods")?;
+    let query = serde_urlencoded::to_string(query).map_err(|e| {
+        ApiError::new(
+            ApiErrorKind::InvalidRequest,
+            "Failed to encode search query",
+            Some(Box::new(e)),
+        )
+    })?;
+    url.set_query(Some(&query));
+
+    Ok(Call::get(url, json))
+}
+
+pub(crate) fn info_short(urls: &FactorioUrls, name: &str) -> Result<Call<ModShort>> {
+    Ok(Call::get(
+        portal_api_url(urls, &format!("mods/{}", name))?,
+        json,
+    ))
+}
+
+pub(crate) fn info_full(urls: &FactorioUrls, name: &str) -> Result<Call<ModFull>> {
+    Ok(Call::get(
+        portal_api_url(urls, &format!("mods/{}/full", name))?,
+        json,
+    ))
+}
+
+pub(crate) fn init_upload(urls: &FactorioUrls, name: &str) -> Result<Call<InitUploadResponse>> {
+    Ok(Call::post(
+        portal_api_url(urls, "v2/mods/upload")?,
+        Form::new().text("mod", name),
+        json,
+    ))
+}
+
+pub(crate) fn upload(upload_url: Url, path: &Path) -> Result<Call<UploadResponse>> {
+    let form = Form::new()
+        .file("file", path)
+        .map_err(|e| file_error("mod", e))?;
+
+    Ok(Call::post(upload_url, form, json).once())
+}
+
+pub(crate) fn edit_details(
+    urls: &FactorioUrls,
+    data: ModDetailsRequest,
+) -> Result<Call<ModDetailsResponse>> {
+    Ok(Call::post(
+        portal_api_url(urls, "v2/mods/edit_details")?,
+        data.into(),
+        json,
+    ))
+}
+
+/// Gets the images of a mod by scraping its page on the portal,
+/// as there is no API for it.
+pub(crate) fn images(urls: &FactorioUrls, name: &str) -> Result<Call<Vec<Image>>> {
+    let url = urls.portal(&format!("mod/{}", name)).map_err(|_| {
+        url_error(format!(
+            "Failed to join portal base URL with path mod/{}",
+            name
+        ))
+    })?;
+
+    Ok(Call::get(url, |body| {
+        Ok(image::parse_html_images(&String::from_utf8_lossy(body)))
+    }))
+}
+
+pub(crate) fn download_release(
+    urls: &FactorioUrls,
+    release: &Release,
+    credentials: Option<&Credentials>,
+) -> Result<Call<Vec<u8>>> {
+    let Some(credentials) = credentials else {
+        return Err(ApiError::new(
+            ApiErrorKind::MissingCredentials,
+            "Missing username and token",
+            None,
+        ));
+    };
+
+    let mut url = urls.portal(&release.download_path).map_err(|_| {
+        url_error(format!(
+            "Failed to join portal base URL with path {}",
+            release.download_path
+        ))
+    })?;
+    url.query_pairs_mut()
+        .append_pair("username", &credentials.username)
+        .append_pair("token", &credentials.token);
+
+    Ok(Call::get(url, |body| Ok(body.to_vec())))
+}
+
+/// Downloads the full size version of an image.
+pub(crate) fn download_image(image: &Image) -> Result<Call<Vec<u8>>> {
+    Ok(Call::get(image.url.clone(), |body| Ok(body.to_vec())))
+}
+
+pub(crate) fn add_image(urls: &FactorioUrls, name: &str) -> Result<Call<ImageAddResponse>> {
+    Ok(Call::post(
+        portal_api_url(urls, "v2/mods/images/add")?,
+        Form::new().text("mod", name),
+        json,
+    ))
+}
+
+pub(crate) fn upload_image(upload_url: Url, path: &Path) -> Result<Call<ImageUploadResponse>> {
+    let form = Form::new()
+        .file("image", path)
+        .map_err(|e| file_error("image", e))?;
+
+    Ok(Call::post(upload_url, form, json).once())
+}
+
+pub(crate) fn edit_images(
+    urls: &FactorioUrls,
+    data: ImageEditRequest,
+) -> Result<Call<ImageEditResponse>> {
+    Ok(Call::post(
+        portal_api_url(urls, "v2/mods/images/edit")?,
+        data.into(),
+        json,
+    ))
+}
+
+pub(crate) fn init_publish(urls: &FactorioUrls, name: &str) -> Result<Call<InitPublishResponse>> {
+    Ok(Call::post(
+        portal_api_url(urls, "v2/mods/init_publish")?,
+        Form::new().text("mod", name),
+        json,
+    ))
+}
+
+pub(crate) fn publish(
+    upload_url: Url,
+    data: PublishRequest,
+    path: &Path,
+) -> Result<Call<PublishResponse>> {
+    let form = Form::from(data)
+        .file("file", path)
+        .map_err(|e| file_error("mod", e))?;
+
+    Ok(Call::post(upload_url, form, json).once())
+}
+
+pub(crate) fn latest_releases(urls: &FactorioUrls) -> Result<Call<LatestReleases>> {
+    let url = urls
+        .game("latest-releases")
+        .map_err(|_| url_error("Failed to join game base URL with path latest-releases"))?;
+
+    Ok(Call::get(url, json))
+}
+
+fn json<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    serde_json::from_slice(body).map_err(|e| {
+        ApiError::new(
+            ApiErrorKind::DeserializationFailed,
+            format!("Failed to deserialize response: {}", e),
+            Some(Box::new(e)),
+        )
+    })
+}
+
+fn portal_api_url(urls: &FactorioUrls, path: &str) -> Result<Url> {
+    urls.portal_api(path).map_err(|_| {
+        url_error(format!(
+            "Failed to join portal API base URL with path {}",
+            path
+        ))
+    })
+}
+
+fn url_error<T: Into<String>>(message: T) -> ApiError {
+    ApiError::new(ApiErrorKind::UrlParseFailed, message, None)
+}
+
+fn file_error(what: &str, error: io::Error) -> ApiError {
+    ApiError::new(
+        ApiErrorKind::ImageIo,
+        format!("Could not read {} file: {}", what, error),
+        Some(Box::new(error)),
+    )
+}
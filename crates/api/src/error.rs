@@ -1,19 +1,47 @@
-use std::str::FromStr;
+use std::{error::Error, str::FromStr};
 
+use http::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
 use strum::Display;
 use thiserror::Error;
+use url::Url;
 
+use crate::{
+    retry,
+    transport::{Response, TransportError},
+};
+
+/// An error returned by the API clients.
+///
+/// Besides the [`kind`][ApiError::kind] of error, it carries as much context
+/// as was available when it happened: the endpoint that was called, the HTTP
+/// status of the response and the error body returned by the API.
 #[derive(Error, Debug)]
 #[error("{kind}")]
 pub struct ApiError {
     kind: ApiErrorKind,
     message: String,
+    // Boxed to keep `Result`s with this error small
+    endpoint: Option<Box<(Method, Url)>>,
+    status: Option<StatusCode>,
+    body: Option<Box<ErrorBody>>,
+    retryable: bool,
 
     #[source]
-    source: Option<reqwest::Error>,
+    source: Option<Box<dyn Error + Send + Sync>>,
 }
 
-#[derive(Display, Debug, Copy, Clone, Eq, PartialEq)]
+/// The error body returned by the Factorio APIs for failed requests.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// The error code, e.g. `UnknownMod`.
+    pub error: String,
+
+    /// Human readable description of the error.
+    pub message: String,
+}
+
+#[derive(Display, Debug, Clone, Eq, PartialEq)]
 pub enum ApiErrorKind {
     #[strum(to_string = "Missing or invalid API key for the current endpoint")]
     InvalidApiKey,
@@ -42,6 +70,9 @@ pub enum ApiErrorKind {
     #[strum(to_string = "API key has not been set")]
     MissingApiKey,
 
+    #[strum(to_string = "Username and token for downloading mods have not been set")]
+    MissingCredentials,
+
     #[strum(to_string = "Failed to parse URL")]
     UrlParseFailed,
 
@@ -60,30 +91,95 @@ pub enum ApiErrorKind {
     #[strum(to_string = "Invalid sort order, must be 'asc(ending)' or 'desc(ending)'")]
     InvalidSortOrder,
 
-    #[strum(to_string = "Unknown error, please try again later")]
-    Unknown,
+    #[strum(to_string = "Failed to set up the HTTP client")]
+    ClientBuildFailed,
+
+    /// No response could be obtained from the server,
+    /// see the [source][Error::source] for details.
+    #[strum(to_string = "Failed to send the request")]
+    RequestFailed,
+
+    /// The server responded with an error that did not include
+    /// an error code, see the [status][ApiError::status].
+    #[strum(to_string = "Unexpected response from the server")]
+    UnexpectedResponse,
+
+    /// The server responded with an error code that is not known
+    /// to this version of the crate, which is kept as-is.
+    #[strum(to_string = "Unknown error ({0}), please try again later")]
+    Unknown(String),
 }
 
 impl ApiError {
     pub fn new<T: Into<String>>(
         kind: ApiErrorKind,
         message: T,
-        source: Option<reqwest::Error>,
+        source: Option<Box<dyn Error + Send + Sync>>,
     ) -> Self {
         Self {
             kind,
             message: message.into(),
+            endpoint: None,
+            status: None,
+            body: None,
+            retryable: false,
             source,
         }
     }
 
-    pub fn kind(&self) -> ApiErrorKind {
-        self.kind
+    pub fn kind(&self) -> &ApiErrorKind {
+        &self.kind
     }
 
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    /// The HTTP method of the request that failed, if one was made.
+    pub fn method(&self) -> Option<&Method> {
+        self.endpoint.as_ref().map(|endpoint| &endpoint.0)
+    }
+
+    /// The URL of the endpoint that was called, if a request was made.
+    pub fn endpoint(&self) -> Option<&Url> {
+        self.endpoint.as_ref().map(|endpoint| &endpoint.1)
+    }
+
+    /// The HTTP status of the response, if one was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// The error body returned by the API, if there was one.
+    ///
+    /// This contains the error code and message exactly as sent by the
+    /// server, which is useful if the [kind][ApiError::kind] is
+    /// [`Unknown`][ApiErrorKind::Unknown].
+    pub fn body(&self) -> Option<&ErrorBody> {
+        self.body.as_deref()
+    }
+
+    /// Checks whether the error is transient, meaning the same request may
+    /// succeed if it is tried again later.
+    ///
+    /// This is the case for rate limiting, server errors, timeouts and
+    /// connection failures. Note that the clients already retry such requests
+    /// according to their [`RetryPolicy`][crate::retry::RetryPolicy] before
+    /// giving up with an error.
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// Records the endpoint the error happened for.
+    pub(crate) fn with_endpoint(mut self, method: &Method, endpoint: &Url) -> Self {
+        self.endpoint = Some(Box::new((method.clone(), endpoint.clone())));
+        self
+    }
+
+    pub(crate) fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status);
+        self
+    }
 }
 
 impl ApiErrorKind {
@@ -94,36 +190,40 @@ impl ApiErrorKind {
     }
 }
 
-// async fn from_response(response: reqwest::Response) -> ApiError {
-//     #[derive(Debug, Deserialize)]
-//     struct ApiErrorResponse {
-//         error: String,
-//         message: String,
-//     }
-
-//     let source = match response.error_for_status_ref() {
-//         Ok(_) => None,
-//         Err(e) => Some(e),
-//     };
-
-//     if let Ok(error_response) = response.json::<ApiErrorResponse>().await {
-//         ApiError::new(
-//             ApiErrorKind::parse(error_response.error),
-//             error_response.message,
-//             source,
-//         )
-//     } else {
-//         ApiError::new(
-//             ApiErrorKind::Unknown,
-//             "Failed to parse error response",
-//             source,
-//         )
-//     }
-// }
-
-impl From<reqwest::Error> for ApiError {
-    fn from(error: reqwest::Error) -> Self {
-        Self::new(ApiErrorKind::Unknown, "Unknown error", Some(error))
+/// Converts an unsuccessful response from one of the APIs to an [`ApiError`],
+/// using the error details in the body if there are any.
+pub(crate) fn from_response(response: &Response) -> ApiError {
+    let status = response.status();
+
+    let mut error = match serde_json::from_slice::<ErrorBody>(response.body()) {
+        Ok(body) => {
+            let mut error =
+                ApiError::new(ApiErrorKind::parse(&body.error), body.message.clone(), None);
+            error.body = Some(Box::new(body));
+            error
+        }
+        Err(_) => ApiError::new(
+            ApiErrorKind::UnexpectedResponse,
+            format!("Failed to parse error response (status {})", status),
+            None,
+        ),
+    };
+
+    error.retryable =
+        retry::is_retryable_status(status) || error.kind == ApiErrorKind::InternalError;
+    error.with_status(status)
+}
+
+impl From<TransportError> for ApiError {
+    fn from(error: TransportError) -> Self {
+        let retryable = retry::is_retryable_error(&error);
+        let mut error = Self::new(
+            ApiErrorKind::RequestFailed,
+            error.to_string(),
+            Some(Box::new(error)),
+        );
+        error.retryable = retryable;
+        error
     }
 }
 
@@ -142,8 +242,59 @@ impl FromStr for ApiErrorKind {
             "InvalidModUpload" => InvalidModUpload,
             "InvalidImageUpload" => InvalidImageUpload,
             "UnknownMod" => UnknownMod,
-            "Unknown" => Unknown,
-            _ => Unknown,
+            other => Unknown(other.to_owned()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body.as_bytes().to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_from_response_known_code() {
+        let error = from_response(&response(
+            404,
+            r#"{ "error": "UnknownMod", "message": "Mod not found" }"#,
+        ));
+
+        assert_eq!(error.kind(), &ApiErrorKind::UnknownMod);
+        assert_eq!(error.message(), "Mod not found");
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(error.body().unwrap().error, "UnknownMod");
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_from_response_unknown_code() {
+        let error = from_response(&response(
+            400,
+            r#"{ "error": "ShinyNewError", "message": "Something new" }"#,
+        ));
+
+        assert_eq!(
+            error.kind(),
+            &ApiErrorKind::Unknown("ShinyNewError".to_owned())
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unknown error (ShinyNewError), please try again later"
+        );
+    }
+
+    #[test]
+    fn test_from_response_without_body() {
+        let error = from_response(&response(503, "<html>Service Unavailable</html>"));
+
+        assert_eq!(error.kind(), &ApiErrorKind::UnexpectedResponse);
+        assert!(error.body().is_none());
+        assert!(error.is_retryable());
+    }
+}
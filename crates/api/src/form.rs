@@ -0,0 +1,170 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::transport::Body;
+
+/// A `multipart/form-data` body, built independently of any HTTP library
+/// so that it can be encoded once and handed to any transport.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Form {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Data,
+}
+
+#[derive(Clone, Debug)]
+enum Data {
+    Text(String),
+    /// A file to be streamed from disk, along with its size.
+    File(PathBuf, u64),
+}
+
+impl Form {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a text field.
+    pub fn text<T, U>(mut self, name: T, value: U) -> Self
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: Data::Text(value.into()),
+        });
+        self
+    }
+
+    /// Adds a file field with the contents of the file at `path`.
+    ///
+    /// The file is not read until the encoded body is, but it must exist.
+    /// The MIME type is guessed from the file extension.
+    pub fn file<T, P>(mut self, name: T, path: P) -> io::Result<Self>
+    where
+        T: Into<String>,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        let len = fs::metadata(path)?.len();
+
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: Some(file_name),
+            content_type: Some(mime.essence_str().to_owned()),
+            data: Data::File(path.to_owned(), len),
+        });
+
+        Ok(self)
+    }
+
+    /// Encodes the form, returning the value to use for the `Content-Type`
+    /// header along with the body.
+    pub fn encode(&self) -> (String, Body) {
+        let boundary = format!("{:016x}{:016x}", fastrand::u64(..), fastrand::u64(..));
+        let mut body = Body::empty();
+
+        for part in &self.parts {
+            let mut header = format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+                boundary,
+                escape(&part.name)
+            );
+
+            if let Some(file_name) = &part.file_name {
+                header.push_str(&format!("; filename=\"{}\"", escape(file_name)));
+            }
+
+            if let Some(content_type) = &part.content_type {
+                header.push_str(&format!("\r\nContent-Type: {}", content_type));
+            }
+
+            header.push_str("\r\n\r\n");
+            body.push_bytes(header.into_bytes());
+
+            match &part.data {
+                Data::Text(text) => body.push_bytes(text.clone().into_bytes()),
+                Data::File(path, len) => body.push_file(path.clone(), *len),
+            }
+
+            body.push_bytes(b"\r\n".to_vec());
+        }
+
+        body.push_bytes(format!("--{}--\r\n", boundary).into_bytes());
+
+        (format!("multipart/form-data; boundary={}", boundary), body)
+    }
+}
+
+/// Escapes a value for use in a quoted header parameter.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let (content_type, body) = Form::new().text("mod", "my-mod").encode();
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        assert!(!body.has_files());
+        let expected = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"mod\"\r\n\r\nmy-mod\r\n--{b}--\r\n",
+            b = boundary
+        );
+
+        assert_eq!(
+            String::from_utf8(body.to_bytes().unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_encode_streams_file() {
+        let path = std::env::temp_dir().join(format!("facti-form-{}.txt", std::process::id()));
+        fs::write(&path, "file contents").unwrap();
+
+        let (_, mut body) = Form::new().file("file", &path).unwrap().encode();
+        assert!(body.has_files());
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::clone(&reported);
+        body.set_progress(Arc::new(move |sent, total| {
+            progress.lock().unwrap().push((sent, total))
+        }));
+
+        let bytes = String::from_utf8(body.to_bytes().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(body.len(), bytes.len() as u64);
+        assert!(bytes.contains("Content-Type: text/plain\r\n\r\nfile contents\r\n"));
+        assert_eq!(
+            reported.lock().unwrap().last(),
+            Some(&(body.len(), body.len()))
+        );
+    }
+}
@@ -4,31 +4,48 @@
 //! ### Default
 //!
 //! - **`async`:** Enables usage of the async client ([`ApiClient`], [`ApiClientBuilder`]).
+//! - **`reqwest`:** Enables the default [`reqwest`][reqwest-crate] based transports.
+//!   Without it, a transport must be given to the client builders, see [`transport`].
 //!
 //! ### Optional
 //!
 //! - **`blocking`:** Enables the [`blocking`] module, which provides a blocking client.
 //!
+//! [reqwest-crate]: https://docs.rs/reqwest
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 #[macro_use]
 mod client;
 
+mod api;
+
 #[cfg(feature = "async")]
 mod r#async;
 #[cfg(feature = "blocking")]
 #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 pub mod blocking;
 pub mod data;
+mod endpoint;
 pub mod error;
-mod reqwest;
+mod form;
+pub mod retry;
+mod search;
+pub mod transport;
+
+pub use api::FactorioApi;
 
 #[cfg(feature = "async")]
 #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
 pub use r#async::client::{ApiClient, ApiClientBuilder};
+#[cfg(all(feature = "async", feature = "reqwest"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "async", feature = "reqwest"))))]
+pub use r#async::reqwest::ReqwestTransport;
 use url::Url;
 
+/// The default `User-Agent` sent with requests.
+pub const DEFAULT_USER_AGENT: &str = concat!("facti-api/", env!("CARGO_PKG_VERSION"));
+
 /// The default base URL for the Factorio mod portal (non-API resources).
 pub const DEFAULT_PORTAL_BASE_URL: &str = "https://mods.factorio.com/";
 
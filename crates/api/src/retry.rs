@@ -0,0 +1,322 @@
+//! Retry handling for requests made by the API clients.
+//!
+//! By default, the clients will retry requests that fail with a transient
+//! error (rate limiting, server errors, timeouts and connection failures)
+//! a few times, waiting an exponentially increasing amount of time between
+//! attempts. Only requests that are safe to repeat are ever retried.
+//!
+//! The behaviour can be customized by giving a [`RetryPolicy`] to the
+//! `retry_policy` method on the client builders.
+
+use std::time::{Duration, SystemTime};
+
+use chrono::DateTime;
+use http::{
+    StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+
+use crate::transport::{Response, TransportError};
+
+/// Describes if and how requests should be retried.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use facti_api::retry::RetryPolicy;
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .initial_backoff(Duration::from_secs(1))
+///     .max_backoff(Duration::from_secs(60));
+///
+/// let disabled = RetryPolicy::none();
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts to make for a request, including the first one.
+    ///
+    /// A value of `1` (or `0`) disables retries.
+    pub max_attempts: u32,
+
+    /// How long to wait before the first retry.
+    pub initial_backoff: Duration,
+
+    /// Upper limit on how long to wait between attempts.
+    ///
+    /// This also caps delays requested by the server with `Retry-After`.
+    pub max_backoff: Duration,
+
+    /// Factor to multiply the delay with after each attempt.
+    pub multiplier: u32,
+
+    /// Whether to randomize delays to avoid many clients retrying in lockstep.
+    ///
+    /// When enabled, each delay will be a random value between half of
+    /// and the full computed delay.
+    pub jitter: bool,
+
+    /// Whether to honour the `Retry-After` header sent by the server.
+    pub respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// Constructs the default [`RetryPolicy`].
+    ///
+    /// It will make at most 3 attempts, starting with a delay of 500 ms and
+    /// doubling it for each subsequent attempt, up to a maximum of 30 seconds.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            jitter: true,
+            respect_retry_after: true,
+        }
+    }
+
+    /// Constructs a [`RetryPolicy`] that never retries requests.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::new()
+        }
+    }
+
+    /// Sets the [`max_attempts`][RetryPolicy::max_attempts] field.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the [`initial_backoff`][RetryPolicy::initial_backoff] field.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the [`max_backoff`][RetryPolicy::max_backoff] field.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the [`multiplier`][RetryPolicy::multiplier] field.
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the [`jitter`][RetryPolicy::jitter] field.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the [`respect_retry_after`][RetryPolicy::respect_retry_after] field.
+    pub fn respect_retry_after(mut self, respect_retry_after: bool) -> Self {
+        self.respect_retry_after = respect_retry_after;
+        self
+    }
+
+    /// Checks whether another attempt may be made after `attempt` attempts
+    /// have already been made.
+    pub(crate) fn can_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Computes how long to wait after the given (1-based) attempt failed.
+    ///
+    /// If the server asked us to wait a specific amount of time and the policy
+    /// respects that, it takes precedence over the computed backoff.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if self.respect_retry_after
+            && let Some(retry_after) = retry_after
+        {
+            return retry_after.min(self.max_backoff);
+        }
+
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let delay = self
+            .initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    /// Decides whether to retry after the given (1-based) attempt finished
+    /// with `result`, returning how long to wait before the next attempt.
+    ///
+    /// Returns [`None`] if the attempt succeeded, failed in a way that is not
+    /// worth retrying, or if there are no attempts left.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        result: &Result<Response, TransportError>,
+    ) -> Option<Duration> {
+        if !self.can_retry(attempt) {
+            return None;
+        }
+
+        match result {
+            Ok(response) if is_retryable_status(response.status()) => {
+                Some(self.delay(attempt, retry_after(response.headers())))
+            }
+            Ok(_) => None,
+            Err(e) if is_retryable_error(e) => Some(self.delay(attempt, None)),
+            Err(_) => None,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks whether a response with the given status is worth retrying.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Checks whether a failure to get a response at all is worth retrying.
+pub(crate) fn is_retryable_error(error: &TransportError) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Parses the `Retry-After` header, if present.
+///
+/// The header can either contain a number of seconds to wait,
+/// or an HTTP date after which the request may be retried.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = SystemTime::from(date)
+        .duration_since(SystemTime::now())
+        .unwrap_or(Duration::ZERO);
+
+    Some(delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::HeaderValue;
+
+    use crate::transport::TransportErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially() {
+        let policy = RetryPolicy::new().jitter(false);
+
+        assert_eq!(policy.delay(1, None), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, None), Duration::from_secs(1));
+        assert_eq!(policy.delay(3, None), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let policy = RetryPolicy::new()
+            .jitter(false)
+            .max_backoff(Duration::from_secs(5));
+
+        assert_eq!(policy.delay(40, None), Duration::from_secs(5));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(120))),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let policy = RetryPolicy::new();
+
+        for _ in 0..100 {
+            let delay = policy.delay(2, None);
+            assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn test_retry_after_past_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::new().jitter(false);
+        let unavailable = http::Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(RETRY_AFTER, "2")
+            .body(Vec::new())
+            .unwrap();
+        let not_found = http::Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new())
+            .unwrap();
+
+        assert_eq!(
+            policy.retry_delay(1, &Ok(unavailable)),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(policy.retry_delay(1, &Ok(not_found)), None);
+        assert_eq!(
+            policy.retry_delay(2, &Err(TransportErrorKind::Timeout.into())),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            policy.retry_delay(3, &Err(TransportErrorKind::Timeout.into())),
+            None
+        );
+        assert_eq!(
+            policy.retry_delay(1, &Err(TransportErrorKind::Other.into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_none_never_retries() {
+        assert!(!RetryPolicy::none().can_retry(1));
+        assert!(RetryPolicy::new().can_retry(2));
+        assert!(!RetryPolicy::new().can_retry(3));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+use crate::data::portal::{ModSummary, SearchQuery, SearchResponse};
+
+/// Maximum amount of names to send in a single `namelist` query.
+///
+/// Long name lists make for very long URLs, which the portal (or proxies
+/// in between) may reject, so they are split up into several requests.
+pub(crate) const MAX_NAMELIST_LEN: usize = 100;
+
+/// Keeps track of which pages remain to be fetched when automatically
+/// paginating through search results.
+///
+/// This contains no I/O so that it can be shared between the blocking
+/// iterator and the async stream.
+pub(crate) struct SearchPager {
+    pending: VecDeque<SearchQuery>,
+    current: Option<SearchQuery>,
+    page_count: u32,
+    buffer: VecDeque<ModSummary>,
+}
+
+impl SearchPager {
+    pub fn new(query: SearchQuery) -> Self {
+        Self {
+            pending: split_namelist(query).into(),
+            current: None,
+            page_count: 0,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Takes the next buffered result, if any.
+    pub fn pop(&mut self) -> Option<ModSummary> {
+        self.buffer.pop_front()
+    }
+
+    /// Returns the query to use for fetching the next page,
+    /// or [`None`] if all pages have been fetched.
+    pub fn next_query(&mut self) -> Option<SearchQuery> {
+        if let Some(current) = &mut self.current
+            && current.page < self.page_count
+        {
+            current.page += 1;
+            return Some(current.clone());
+        }
+
+        let next = self.pending.pop_front()?;
+        self.current = Some(next.clone());
+        self.page_count = next.page;
+
+        Some(next)
+    }
+
+    /// Adds the results of a fetched page to the buffer.
+    pub fn push(&mut self, response: SearchResponse) {
+        self.page_count = response.pagination.page_count;
+        self.buffer.extend(response.results);
+    }
+}
+
+/// Splits a query with a long `namelist` into several queries with at most
+/// [`MAX_NAMELIST_LEN`] names each.
+fn split_namelist(query: SearchQuery) -> Vec<SearchQuery> {
+    match &query.namelist {
+        Some(names) if names.len() > MAX_NAMELIST_LEN => names
+            .chunks(MAX_NAMELIST_LEN)
+            .map(|chunk| SearchQuery {
+                namelist: Some(chunk.to_vec()),
+                ..query.clone()
+            })
+            .collect(),
+        _ => vec![query],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_namelist_is_not_split() {
+        let query = SearchQuery {
+            namelist: Some(vec!["foo".to_owned(), "bar".to_owned()]),
+            ..Default::default()
+        };
+
+        assert_eq!(split_namelist(query).len(), 1);
+    }
+
+    #[test]
+    fn test_long_namelist_is_split() {
+        let names = (0..250).map(|i| format!("mod-{}", i)).collect::<Vec<_>>();
+        let query = SearchQuery {
+            namelist: Some(names),
+            ..Default::default()
+        };
+        let queries = split_namelist(query);
+
+        assert_eq!(queries.len(), 3);
+        assert_eq!(queries[2].namelist.as_ref().unwrap().len(), 50);
+        assert_eq!(queries[2].namelist.as_ref().unwrap()[0], "mod-200");
+    }
+}
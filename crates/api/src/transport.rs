@@ -0,0 +1,376 @@
+//! Pluggable HTTP transports for the API clients.
+//!
+//! The clients do not talk to the network themselves. Instead, they build
+//! plain [`Request`]s (with fully encoded bodies) and hand them to a transport,
+//! which is responsible for sending them and returning the [`Response`].
+//!
+//! With the `reqwest` feature (enabled by default), the clients use a
+//! transport based on [`reqwest`][reqwest-crate] unless told otherwise.
+//! To use a different HTTP stack, or to return canned responses in tests,
+//! implement [`Transport`] (for the blocking client) or [`AsyncTransport`]
+//! (for the async client) and give it to the `transport` method on the
+//! client builder.
+//!
+//! # Examples
+//!
+//! A blocking transport that answers every request with the same response:
+//!
+//! ```
+//! # #[cfg(feature = "blocking")]
+//! # {
+//! use facti_api::{
+//!     blocking::ApiClient,
+//!     transport::{Request, Response, Transport, TransportError},
+//! };
+//!
+//! struct Canned(&'static str);
+//!
+//! impl Transport for Canned {
+//!     fn execute(&self, _request: Request) -> Result<Response, TransportError> {
+//!         Ok(Response::new(self.0.as_bytes().to_vec()))
+//!     }
+//! }
+//!
+//! let mut builder = ApiClient::builder();
+//! builder.transport(Canned(r#"{ "stable": { "alpha": "1.1.110", "demo": "1.1.110", "headless": "1.1.110" }, "experimental": { "alpha": "1.1.110", "demo": "1.1.110", "headless": "1.1.110" } }"#));
+//! let client = builder.build().unwrap();
+//!
+//! assert_eq!(client.latest_releases().unwrap().stable.alpha.to_string(), "1.1.110");
+//! # }
+//! ```
+//!
+//! [reqwest-crate]: https://docs.rs/reqwest
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use strum::Display;
+use thiserror::Error;
+use url::Url;
+
+/// Re-export of the [`http`][http-crate] crate, whose types are used for
+/// requests and responses.
+///
+/// [http-crate]: https://docs.rs/http
+pub use http;
+
+/// A request to send, with its body already encoded.
+pub type Request = http::Request<Body>;
+
+/// A response received from the server.
+pub type Response = http::Response<Vec<u8>>;
+
+/// Callback reporting the progress of an upload.
+///
+/// It is called with the amount of bytes sent so far and the total size
+/// of the request body, as the body is read by the transport.
+pub type Progress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// The body of a [`Request`].
+///
+/// Files in the body (such as mods and images being uploaded) are not read
+/// into memory up front, but streamed from disk when the body is read
+/// through [`reader`][Body::reader].
+#[derive(Clone, Default)]
+pub struct Body {
+    segments: Vec<Segment>,
+    len: u64,
+    progress: Option<Progress>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Bytes(Vec<u8>),
+    File(PathBuf, u64),
+}
+
+impl Body {
+    /// Constructs an empty body.
+    pub fn empty() -> Self {
+        Default::default()
+    }
+
+    /// Total size of the body in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the body streams files from disk, i.e. uploads a mod or
+    /// an image.
+    pub fn has_files(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::File(..)))
+    }
+
+    /// Returns a reader for the contents of the body,
+    /// which reports progress to the upload callback (if any) as it is read.
+    pub fn reader(&self) -> BodyReader {
+        BodyReader {
+            segments: self.segments.clone().into(),
+            current: None,
+            sent: 0,
+            len: self.len,
+            progress: self.progress.clone(),
+        }
+    }
+
+    /// Reads the entire body into memory.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.len as usize);
+        self.reader().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub(crate) fn push_bytes(&mut self, bytes: Vec<u8>) {
+        self.len += bytes.len() as u64;
+
+        if let Some(Segment::Bytes(last)) = self.segments.last_mut() {
+            last.extend(bytes);
+        } else {
+            self.segments.push(Segment::Bytes(bytes));
+        }
+    }
+
+    pub(crate) fn push_file(&mut self, path: PathBuf, len: u64) {
+        self.len += len;
+        self.segments.push(Segment::File(path, len));
+    }
+
+    pub(crate) fn set_progress(&mut self, progress: Progress) {
+        self.progress = Some(progress);
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        let mut body = Self::empty();
+        body.push_bytes(bytes);
+        body
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Body")
+            .field("segments", &self.segments)
+            .field("len", &self.len)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Reader for the contents of a [`Body`], created by [`Body::reader`].
+pub struct BodyReader {
+    segments: VecDeque<Segment>,
+    current: Option<Box<dyn Read + Send + Sync>>,
+    sent: u64,
+    len: u64,
+    progress: Option<Progress>,
+}
+
+impl Read for BodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(current) = &mut self.current {
+                let read = current.read(buf)?;
+
+                if read > 0 {
+                    self.sent += read as u64;
+                    if let Some(progress) = &self.progress {
+                        progress(self.sent, self.len);
+                    }
+                    return Ok(read);
+                }
+
+                self.current = None;
+            }
+
+            let current: Box<dyn Read + Send + Sync> = match self.segments.pop_front() {
+                Some(Segment::Bytes(bytes)) => Box::new(io::Cursor::new(bytes)),
+                // Guard against the file growing after the length was taken
+                Some(Segment::File(path, len)) => Box::new(File::open(path)?.take(len)),
+                None => return Ok(0),
+            };
+            self.current = Some(current);
+        }
+    }
+}
+
+/// Settings for the HTTP connections made by the default transports.
+///
+/// These are configured through the client builders and are only used
+/// when no custom transport is given.
+#[derive(Clone, Debug, Default)]
+pub struct HttpOptions {
+    /// Proxy to send all HTTP and HTTPS requests through.
+    ///
+    /// If not set, proxies are taken from the usual environment variables
+    /// (`HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`).
+    pub proxy: Option<Url>,
+
+    /// Extra PEM encoded root certificates to trust, in addition to the
+    /// system ones.
+    ///
+    /// Each entry may contain several certificates.
+    pub root_certificates: Vec<Vec<u8>>,
+
+    /// Timeout for establishing a connection to the server.
+    pub connect_timeout: Option<Duration>,
+
+    /// Timeout for the whole of a request, from sending it to reading the
+    /// response, 30 seconds if not set.
+    ///
+    /// Requests uploading files (see [`Body::has_files`]) are not limited,
+    /// as sending a large mod can take much longer than that.
+    pub read_timeout: Option<Duration>,
+}
+
+/// Timeout for requests that do not upload files, if no read timeout is set.
+#[cfg(feature = "reqwest")]
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A blocking HTTP transport.
+///
+/// Implementations should only return an error if no response could be
+/// obtained at all; responses with error statuses must be returned as-is.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub trait Transport: Send + Sync {
+    /// Sends a request and waits for its response.
+    fn execute(&self, request: Request) -> Result<Response, TransportError>;
+}
+
+/// An async HTTP transport.
+///
+/// Implementations should only return an error if no response could be
+/// obtained at all; responses with error statuses must be returned as-is.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[async_trait::async_trait]
+pub trait AsyncTransport: Send + Sync {
+    /// Sends a request and waits for its response.
+    async fn execute(&self, request: Request) -> Result<Response, TransportError>;
+
+    /// Waits for the given duration before a request is retried.
+    ///
+    /// This is part of the transport so that the async client does not
+    /// depend on any particular runtime. Implementations should use the
+    /// timer of the runtime they are built for.
+    async fn sleep(&self, duration: Duration);
+}
+
+/// An error that prevented a transport from getting a response.
+#[derive(Error, Debug)]
+#[error("{kind}")]
+pub struct TransportError {
+    kind: TransportErrorKind,
+
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+/// Kinds of [`TransportError`].
+///
+/// Timeouts and connection failures are considered transient,
+/// and requests failing with them may be retried.
+#[derive(Display, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TransportErrorKind {
+    #[strum(to_string = "The request timed out")]
+    Timeout,
+
+    #[strum(to_string = "Failed to connect to the server")]
+    Connect,
+
+    #[strum(to_string = "Failed to send request")]
+    Other,
+}
+
+impl TransportError {
+    pub fn new<E>(kind: TransportErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: Some(source.into()),
+        }
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.kind == TransportErrorKind::Timeout
+    }
+
+    pub fn is_connect(&self) -> bool {
+        self.kind == TransportErrorKind::Connect
+    }
+}
+
+impl From<TransportErrorKind> for TransportError {
+    fn from(kind: TransportErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// Stand-in used when no transport has been configured and the `reqwest`
+/// feature is disabled, failing every request with an explanation.
+#[cfg(not(feature = "reqwest"))]
+pub(crate) struct MissingTransport;
+
+#[cfg(not(feature = "reqwest"))]
+impl MissingTransport {
+    fn error() -> TransportError {
+        TransportError::new(
+            TransportErrorKind::Other,
+            "No transport configured, enable the `reqwest` feature or set one on the client builder",
+        )
+    }
+}
+
+#[cfg(all(feature = "blocking", not(feature = "reqwest")))]
+impl Transport for MissingTransport {
+    fn execute(&self, _request: Request) -> Result<Response, TransportError> {
+        Err(Self::error())
+    }
+}
+
+#[cfg(all(feature = "async", not(feature = "reqwest")))]
+#[async_trait::async_trait]
+impl AsyncTransport for MissingTransport {
+    async fn execute(&self, _request: Request) -> Result<Response, TransportError> {
+        Err(Self::error())
+    }
+
+    async fn sleep(&self, _duration: Duration) {}
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TransportError {
+    fn from(error: reqwest::Error) -> Self {
+        let kind = if error.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if error.is_connect() {
+            TransportErrorKind::Connect
+        } else {
+            TransportErrorKind::Other
+        };
+
+        Self::new(kind, error)
+    }
+}
@@ -0,0 +1,87 @@
+#![cfg(all(feature = "async", feature = "reqwest"))]
+
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use facti_api::{ApiClient, data::portal::SearchQuery, error::ApiErrorKind};
+use facti_lib::{ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+use futures_util::{StreamExt, pin_mut};
+
+fn client(portal: &MockPortal) -> ApiClient {
+    let mut builder = ApiClient::builder();
+    builder
+        .portal_base_url(portal.portal_base_url())
+        .portal_api_base_url(portal.portal_api_base_url())
+        .game_base_url(portal.game_base_url())
+        .api_key("secret");
+
+    builder.build().unwrap()
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn test_search_and_info() {
+    let portal = MockPortal::start().unwrap();
+    for name in ["alpha", "beta"] {
+        let info = ModInfo::builder(name, Version::new(1, 0, 0), name, "someone").build();
+        portal.store().publish(&mod_zip(&info)).unwrap();
+    }
+    let client = client(&portal);
+
+    block_on(async {
+        let results = client.search_all(SearchQuery::default());
+        pin_mut!(results);
+        let mut names = Vec::new();
+        while let Some(result) = results.next().await {
+            names.push(result.unwrap().name);
+        }
+        assert_eq!(names, vec!["alpha", "beta"]);
+
+        let full = client.info_full("beta").await.unwrap();
+        assert_eq!(full.owner, facti_mock_portal::DEFAULT_OWNER);
+
+        let error = client.info_short("gamma").await.unwrap_err();
+        assert_eq!(error.kind(), &ApiErrorKind::UnknownMod);
+    });
+}
+
+#[test]
+fn test_upload_progress() {
+    let portal = MockPortal::start().unwrap();
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone").build();
+    portal.store().publish(&mod_zip(&info)).unwrap();
+    let client = client(&portal);
+
+    let update = ModInfo::builder("my-mod", Version::new(1, 1, 0), "My Mod", "someone").build();
+    let path = std::env::temp_dir().join(format!(
+        "facti-async-{}-my-mod_1.1.0.zip",
+        std::process::id()
+    ));
+    fs::write(&path, mod_zip(&update)).unwrap();
+    let reported = Arc::new(Mutex::new(None));
+    let progress = Arc::clone(&reported);
+
+    block_on(async {
+        client
+            .upload_with_progress("my-mod", &path, move |sent, total| {
+                *progress.lock().unwrap() = Some((sent, total));
+            })
+            .await
+            .unwrap();
+    });
+    fs::remove_file(&path).unwrap();
+
+    let (sent, total) = reported.lock().unwrap().unwrap();
+    assert_eq!(sent, total);
+    assert_eq!(portal.store().get("my-mod").unwrap().releases.len(), 2);
+}
@@ -0,0 +1,276 @@
+#![cfg(all(feature = "blocking", feature = "reqwest"))]
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use facti_api::{
+    blocking::ApiClient,
+    data::{
+        detail::{ModDetailsRequest, Tag},
+        image::ImageEditRequest,
+        pagination::PageSize,
+        portal::SearchQuery,
+        publish::PublishRequest,
+    },
+    error::ApiErrorKind,
+    retry::RetryPolicy,
+};
+use facti_lib::{ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+fn client(portal: &MockPortal, api_key: Option<&str>) -> ApiClient {
+    let mut builder = ApiClient::builder();
+    builder
+        .portal_base_url(portal.portal_base_url())
+        .portal_api_base_url(portal.portal_api_base_url())
+        .game_base_url(portal.game_base_url())
+        .retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)));
+
+    if let Some(api_key) = api_key {
+        builder.api_key(api_key);
+    }
+
+    builder.build().unwrap()
+}
+
+fn info(name: &str, version: Version) -> ModInfo {
+    ModInfo::builder(name, version, name.to_uppercase(), "someone").build()
+}
+
+fn seed(portal: &MockPortal, names: &[&str]) {
+    let mut store = portal.store();
+    for name in names {
+        store
+            .publish(&mod_zip(&info(name, Version::new(1, 0, 0))))
+            .unwrap();
+    }
+}
+
+/// Writes `contents` to a file unique to the calling test.
+fn temp_file(test: &str, name: &str, contents: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("facti-api-{}-{}", std::process::id(), test));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_search_and_info() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha", "beta", "gamma"]);
+    let client = client(&portal, None);
+
+    let query = SearchQuery {
+        namelist: Some(vec!["alpha".to_owned(), "gamma".to_owned()]),
+        ..Default::default()
+    };
+    let response = client.search(&query).unwrap();
+    let names = response
+        .results
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["alpha", "gamma"]);
+    assert!(response.results[0].latest_release.is_some());
+
+    let short = client.info_short("beta").unwrap();
+    assert_eq!(short.releases.len(), 1);
+
+    let full = client.info_full("beta").unwrap();
+    assert_eq!(full.releases.len(), 1);
+
+    let error = client.info_full("delta").unwrap_err();
+    assert_eq!(error.kind(), &ApiErrorKind::UnknownMod);
+}
+
+#[test]
+fn test_download_release() {
+    let portal = MockPortal::start().unwrap();
+    let zip = mod_zip(&info("alpha", Version::new(1, 0, 0)));
+    portal.store().publish(&zip).unwrap();
+
+    let release = client(&portal, None).info_short("alpha").unwrap().releases[0].clone();
+
+    let error = client(&portal, None)
+        .download_release(&release)
+        .unwrap_err();
+    assert_eq!(error.kind(), &ApiErrorKind::MissingCredentials);
+
+    let mut builder = ApiClient::builder();
+    builder
+        .portal_base_url(portal.portal_base_url())
+        .portal_api_base_url(portal.portal_api_base_url())
+        .credentials("someone", "token");
+    let client = builder.build().unwrap();
+    assert_eq!(client.download_release(&release).unwrap(), zip);
+}
+
+#[test]
+fn test_search_all_paginates() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["a", "b", "c", "d", "e"]);
+    let client = client(&portal, None);
+
+    let query = SearchQuery {
+        page_size: Some(PageSize::Custom(2)),
+        ..Default::default()
+    };
+    let results = client
+        .search_all(query)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(results.len(), 5);
+    assert_eq!(portal.requests().len(), 3);
+}
+
+#[test]
+fn test_retries_transient_failures() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    portal.fail_next(503);
+    portal.fail_next(502);
+    let client = client(&portal, None);
+
+    assert_eq!(client.info_short("alpha").unwrap().name, "alpha");
+    assert_eq!(portal.requests().len(), 3);
+}
+
+#[test]
+fn test_rejects_invalid_api_key() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    portal.store().set_api_key(Some("secret"));
+    let client = client(&portal, Some("wrong"));
+
+    let error = client
+        .edit_details(ModDetailsRequest::builder("alpha").title("Nope").build())
+        .unwrap_err();
+
+    assert_eq!(error.kind(), &ApiErrorKind::InvalidApiKey);
+}
+
+#[test]
+fn test_publish_upload_and_edit() {
+    let portal = MockPortal::start().unwrap();
+    let client = client(&portal, Some("secret"));
+
+    let first = temp_file(
+        "publish",
+        "my-mod_1.0.0.zip",
+        &mod_zip(&info("my-mod", Version::new(1, 0, 0))),
+    );
+    let published = client
+        .publish(
+            "my-mod",
+            PublishRequest::new("my-mod").description("Long description"),
+            &first,
+        )
+        .unwrap();
+    assert!(published.success);
+
+    let second = temp_file(
+        "publish",
+        "my-mod_1.1.0.zip",
+        &mod_zip(&info("my-mod", Version::new(1, 1, 0))),
+    );
+    assert!(client.upload("my-mod", &second).unwrap().success);
+
+    client
+        .edit_details(
+            ModDetailsRequest::builder("my-mod")
+                .title("My Mod")
+                .tag(Tag::Trains)
+                .build(),
+        )
+        .unwrap();
+
+    let full = client.info_full("my-mod").unwrap();
+    assert_eq!(full.title, "My Mod");
+    assert_eq!(full.description, "Long description");
+    assert_eq!(full.releases.len(), 2);
+    assert_eq!(full.tags.len(), 1);
+}
+
+#[test]
+fn test_upload_progress() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["my-mod"]);
+    let client = client(&portal, Some("secret"));
+
+    let zip = mod_zip(&info("my-mod", Version::new(2, 0, 0)));
+    let path = temp_file("progress", "my-mod_2.0.0.zip", &zip);
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let progress = Arc::clone(&reported);
+
+    client
+        .upload_with_progress("my-mod", &path, move |sent, total| {
+            progress.lock().unwrap().push((sent, total));
+        })
+        .unwrap();
+
+    let reported = reported.lock().unwrap();
+    let (sent, total) = *reported.last().unwrap();
+    assert_eq!(sent, total);
+    assert!(total > zip.len() as u64);
+    assert!(reported.windows(2).all(|w| w[0].0 <= w[1].0));
+}
+
+#[test]
+fn test_images() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["my-mod"]);
+    let client = client(&portal, Some("secret"));
+
+    let first = client
+        .upload_image("my-mod", temp_file("images", "a.png", b"first"))
+        .unwrap();
+    let second = client
+        .upload_image("my-mod", temp_file("images", "b.png", b"second"))
+        .unwrap();
+
+    let ids = |client: &ApiClient| {
+        client
+            .images("my-mod")
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&client), vec![first.id.clone(), second.id.clone()]);
+
+    let images = client.images("my-mod").unwrap();
+    assert_eq!(client.download_image(&images[0]).unwrap(), b"first");
+
+    let edited = client
+        .edit_images(ImageEditRequest {
+            name: "my-mod".to_owned(),
+            images: vec![second.id.clone()],
+        })
+        .unwrap();
+    assert_eq!(edited.images.len(), 1);
+    assert_eq!(ids(&client), vec![second.id]);
+}
+
+#[test]
+fn test_latest_releases() {
+    let portal = MockPortal::start().unwrap();
+    let expected = portal.store().latest_releases();
+    let client = client(&portal, None);
+
+    assert_eq!(client.latest_releases().unwrap(), expected);
+}
+
+#[test]
+fn test_invalid_root_certificate() {
+    let mut builder = ApiClient::builder();
+    builder.root_certificate("-----BEGIN CERTIFICATE-----\nnope\n-----END CERTIFICATE-----\n");
+
+    let error = builder.build().err().unwrap();
+    assert_eq!(error.kind(), &ApiErrorKind::ClientBuildFailed);
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "blocking")]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use facti_api::{
+    FactorioApi,
+    blocking::ApiClient,
+    data::detail::ModDetailsRequest,
+    error::ApiErrorKind,
+    retry::RetryPolicy,
+    transport::{
+        Request, Response, Transport, TransportError, TransportErrorKind,
+        http::{self, StatusCode, header},
+    },
+};
+
+const LATEST_RELEASES: &str = r#"{
+    "stable": { "alpha": "1.1.110", "demo": "1.1.110", "headless": "1.1.110" },
+    "experimental": { "alpha": "2.0.7", "demo": "2.0.7", "headless": "2.0.7" }
+}"#;
+
+/// Transport returning queued responses and recording the requests it got.
+#[derive(Clone, Default)]
+struct Canned {
+    responses: Arc<Mutex<VecDeque<Result<Response, TransportErrorKind>>>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Canned {
+    fn respond(&self, status: StatusCode, body: &str) -> &Self {
+        let response = http::Response::builder()
+            .status(status)
+            .body(body.as_bytes().to_vec())
+            .unwrap();
+        self.responses.lock().unwrap().push_back(Ok(response));
+        self
+    }
+
+    fn fail(&self, kind: TransportErrorKind) -> &Self {
+        self.responses.lock().unwrap().push_back(Err(kind));
+        self
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for Canned {
+    fn execute(&self, request: Request) -> Result<Response, TransportError> {
+        self.requests.lock().unwrap().push(request);
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("unexpected request")
+            .map_err(TransportError::from)
+    }
+}
+
+fn client(transport: &Canned, api_key: Option<&str>) -> ApiClient {
+    let mut builder = ApiClient::builder();
+    builder
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)));
+
+    if let Some(api_key) = api_key {
+        builder.api_key(api_key);
+    }
+
+    builder.build().unwrap()
+}
+
+#[test]
+fn test_get_without_auth() {
+    let transport = Canned::default();
+    transport.respond(StatusCode::OK, LATEST_RELEASES);
+
+    let latest = client(&transport, Some("secret"))
+        .latest_releases()
+        .unwrap();
+    assert_eq!(latest.experimental.alpha.to_string(), "2.0.7");
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "GET");
+    assert_eq!(
+        requests[0].uri().to_string(),
+        "https://factorio.com/api/latest-releases"
+    );
+    assert_eq!(
+        requests[0].headers()[header::USER_AGENT],
+        facti_api::DEFAULT_USER_AGENT
+    );
+    assert!(!requests[0].headers().contains_key(header::AUTHORIZATION));
+}
+
+#[test]
+fn test_custom_user_agent() {
+    let transport = Canned::default();
+    transport.respond(StatusCode::OK, LATEST_RELEASES);
+
+    let mut builder = ApiClient::builder();
+    builder
+        .transport(transport.clone())
+        .user_agent("facti/1.2.3 (+mailto:me@example.com)");
+    builder.build().unwrap().latest_releases().unwrap();
+
+    assert_eq!(
+        transport.requests()[0].headers()[header::USER_AGENT],
+        "facti/1.2.3 (+mailto:me@example.com)"
+    );
+}
+
+#[test]
+fn test_post_sends_auth_and_form() {
+    let transport = Canned::default();
+    transport.respond(
+        StatusCode::OK,
+        r#"{ "success": true, "url": "/api/mods/my-mod/full" }"#,
+    );
+
+    let request = ModDetailsRequest::builder("my-mod").title("My Mod").build();
+    client(&transport, Some("secret"))
+        .edit_details(request)
+        .unwrap();
+
+    let requests = transport.requests();
+    let headers = requests[0].headers();
+    assert_eq!(requests[0].method(), "POST");
+    assert_eq!(headers[header::AUTHORIZATION], "Bearer secret");
+
+    let content_type = headers[header::CONTENT_TYPE].to_str().unwrap();
+    assert!(content_type.starts_with("multipart/form-data; boundary="));
+
+    let body = requests[0].body().to_bytes().unwrap();
+    let body = String::from_utf8_lossy(&body);
+    assert!(body.contains("name=\"mod\"\r\n\r\nmy-mod\r\n"));
+    assert!(body.contains("name=\"title\"\r\n\r\nMy Mod\r\n"));
+}
+
+#[test]
+fn test_post_requires_api_key() {
+    let transport = Canned::default();
+    let request = ModDetailsRequest::builder("my-mod").build();
+
+    let error = client(&transport, None).edit_details(request).unwrap_err();
+    assert_eq!(error.kind(), &ApiErrorKind::MissingApiKey);
+    assert!(transport.requests().is_empty());
+}
+
+#[test]
+fn test_retries_transient_failures() {
+    let transport = Canned::default();
+    transport
+        .respond(StatusCode::SERVICE_UNAVAILABLE, "")
+        .fail(TransportErrorKind::Timeout)
+        .respond(StatusCode::OK, LATEST_RELEASES);
+
+    client(&transport, None).latest_releases().unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn test_error_response() {
+    let transport = Canned::default();
+    transport.respond(
+        StatusCode::NOT_FOUND,
+        r#"{ "error": "UnknownMod", "message": "Mod not found" }"#,
+    );
+
+    let error = client(&transport, None).info_short("nope").unwrap_err();
+    assert_eq!(error.kind(), &ApiErrorKind::UnknownMod);
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+    assert_eq!(error.method(), Some(&http::Method::GET));
+    assert_eq!(error.endpoint().unwrap().path(), "/api/mods/nope");
+    assert_eq!(error.body().unwrap().message, "Mod not found");
+    assert!(!error.is_retryable());
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[test]
+fn test_retries_exhausted() {
+    let transport = Canned::default();
+    for _ in 0..3 {
+        transport.fail(TransportErrorKind::Timeout);
+    }
+
+    let error = client(&transport, None).latest_releases().unwrap_err();
+    assert_eq!(error.kind(), &ApiErrorKind::RequestFailed);
+    assert!(error.is_retryable());
+    assert!(error.status().is_none());
+    assert!(error.endpoint().is_some());
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn test_factorio_api_trait() {
+    let transport = Canned::default();
+    transport.respond(StatusCode::OK, LATEST_RELEASES);
+
+    let client = client(&transport, None);
+    let latest = FactorioApi::latest_releases(&client).unwrap();
+    assert_eq!(latest.stable.alpha.to_string(), "1.1.110");
+}
@@ -6,23 +6,39 @@ use url::Url;
 use crate::logging::LogLevelFilter;
 
 use self::{
-    changelog::ChangelogArgs, completion::CompletionArgs, dist::DistArgs, new::NewArgs,
-    portal::PortalArgs, verbose::Verbosity,
+    blueprint::BlueprintArgs, changelog::ChangelogArgs, completion::CompletionArgs, deps::DepsArgs,
+    dist::DistArgs, game::GameArgs, mods::ModsArgs, new::NewArgs, pack::PackArgs, port::PortArgs,
+    portal::PortalArgs, save::SaveArgs, verbose::Verbosity,
 };
 
+mod blueprint;
 mod changelog;
 mod completion;
+mod deps;
 mod dist;
+mod game;
+mod mods;
 mod new;
+mod pack;
+mod port;
 mod portal;
+mod save;
 mod verbose;
 
 const ENV_CONFIG_PATH: &str = "FACTI_CONFIG";
 const ENV_LOG_LEVEL: &str = "FACTI_LOG_LEVEL";
 const ENV_API_KEY: &str = "FACTI_API_KEY";
 const ENV_API_KEY_FILE: &str = "FACTI_API_KEY_FILE";
+const ENV_USERNAME: &str = "FACTI_USERNAME";
+const ENV_TOKEN: &str = "FACTI_TOKEN";
 const ENV_PORTAL_BASE_URL: &str = "FACTI_PORTAL_BASE_URL";
+const ENV_PORTAL_API_BASE_URL: &str = "FACTI_PORTAL_API_BASE_URL";
 const ENV_GAME_BASE_URL: &str = "FACTI_GAME_BASE_URL";
+const ENV_PROXY: &str = "FACTI_PROXY";
+const ENV_CA_CERT: &str = "FACTI_CA_CERT";
+const ENV_CONNECT_TIMEOUT: &str = "FACTI_CONNECT_TIMEOUT";
+const ENV_READ_TIMEOUT: &str = "FACTI_READ_TIMEOUT";
+const ENV_USER_AGENT_CONTACT: &str = "FACTI_USER_AGENT_CONTACT";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -70,18 +86,68 @@ pub struct Cli {
     #[arg(long, env = ENV_API_KEY_FILE, value_hint = ValueHint::FilePath, conflicts_with_all = &["api_key", "api_key_stdin"])]
     pub api_key_file: Option<PathBuf>,
 
-    /// Override the base URL to the Factorio mod portal API.
+    /// Username of the Factorio account to download mods with.
+    #[arg(long, env = ENV_USERNAME, value_hint = ValueHint::Other)]
+    pub username: Option<String>,
+
+    /// Service token of the Factorio account to download mods with.
     ///
-    /// The default base URL is <https://mods.factorio.com/api/>.
+    /// This is shown on your Factorio profile page, and is not the same
+    /// as an API key.
+    #[arg(long, env = ENV_TOKEN, value_hint = ValueHint::Other)]
+    pub token: Option<String>,
+
+    /// Override the base URL to the Factorio mod portal.
+    ///
+    /// Unless the API base URL is also overridden, the portal API is assumed
+    /// to be at `api/` relative to this URL.
+    ///
+    /// The default base URL is <https://mods.factorio.com/>.
     #[arg(long, env = ENV_PORTAL_BASE_URL, value_hint = ValueHint::Url)]
     pub portal_base_url: Option<Url>,
 
+    /// Override the base URL to the Factorio mod portal API.
+    ///
+    /// The default base URL is <https://mods.factorio.com/api/>.
+    #[arg(long, env = ENV_PORTAL_API_BASE_URL, value_hint = ValueHint::Url)]
+    pub portal_api_base_url: Option<Url>,
+
     /// Override the base URL to the Factorio game API.
     ///
     /// The default base URL is <https://factorio.com/api/>.
     #[arg(long, env = ENV_GAME_BASE_URL, value_hint = ValueHint::Url)]
     pub game_base_url: Option<Url>,
 
+    /// Send all requests to the Factorio APIs through this proxy.
+    ///
+    /// If not set, the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables are respected.
+    #[arg(long, env = ENV_PROXY, value_hint = ValueHint::Url)]
+    pub proxy: Option<Url>,
+
+    /// Trust the root certificate(s) in this PEM file, in addition to the
+    /// system ones.
+    ///
+    /// Can be specified multiple times. Certificates configured in the
+    /// config file are also trusted.
+    #[arg(long = "ca-cert", env = ENV_CA_CERT, value_hint = ValueHint::FilePath)]
+    pub ca_certs: Vec<PathBuf>,
+
+    /// Timeout in seconds for connecting to the Factorio APIs.
+    #[arg(long, env = ENV_CONNECT_TIMEOUT, value_name = "SECONDS")]
+    pub connect_timeout: Option<u64>,
+
+    /// Timeout in seconds for requests to the Factorio APIs, 30 by default.
+    ///
+    /// Uploads are not limited, as they can take much longer.
+    #[arg(long, env = ENV_READ_TIMEOUT, value_name = "SECONDS")]
+    pub read_timeout: Option<u64>,
+
+    /// Contact information (e.g. an email address or URL) to include in
+    /// the User-Agent sent to the Factorio APIs.
+    #[arg(long, env = ENV_USER_AGENT_CONTACT, value_hint = ValueHint::Other)]
+    pub user_agent_contact: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -90,15 +156,28 @@ pub struct Cli {
 pub enum Commands {
     Portal(PortalArgs),
 
+    Game(GameArgs),
+
     #[command(visible_alias = "init")]
     New(Box<NewArgs>),
 
     /// Create distribution package for the mod.
-    #[command(visible_alias = "pack")]
     Dist(DistArgs),
 
     Changelog(ChangelogArgs),
 
+    Port(PortArgs),
+
+    Deps(DepsArgs),
+
+    Mods(ModsArgs),
+
+    Pack(PackArgs),
+
+    Save(SaveArgs),
+
+    Blueprint(BlueprintArgs),
+
     Completion(CompletionArgs),
 
     /// Do nothing.
@@ -0,0 +1,136 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use facti_lib::blueprint::BlueprintData;
+
+use self::{
+    decode::BlueprintDecodeArgs, encode::BlueprintEncodeArgs, validate::BlueprintValidateArgs,
+};
+
+mod decode;
+mod encode;
+mod validate;
+
+/// Work with Factorio blueprint strings.
+///
+/// Blueprint strings hold blueprints, blueprint books, deconstruction
+/// planners or upgrade planners. They can be decoded to a readable format
+/// to keep under version control or compare, and encoded back to import
+/// them into the game.
+#[derive(Args, Debug)]
+pub struct BlueprintArgs {
+    #[command(subcommand)]
+    pub command: BlueprintCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BlueprintCommands {
+    Decode(BlueprintDecodeArgs),
+
+    Encode(BlueprintEncodeArgs),
+
+    Validate(BlueprintValidateArgs),
+}
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BlueprintFormat {
+    #[default]
+    Json,
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    Yaml,
+    Toml,
+}
+
+impl BlueprintArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            BlueprintCommands::Decode(args) => args.run(),
+            BlueprintCommands::Encode(args) => args.run(),
+            BlueprintCommands::Validate(args) => args.run(),
+        }
+    }
+}
+
+impl BlueprintFormat {
+    fn read(self, mut reader: impl BufRead) -> Result<BlueprintData> {
+        match self {
+            BlueprintFormat::Json => {
+                serde_json::from_reader(reader).context("Converting from JSON failed")
+            }
+            // Blueprint data is a map with a single key naming its kind,
+            // which serde_yaml would otherwise expect as a YAML tag
+            #[cfg(feature = "yaml")]
+            BlueprintFormat::Yaml => serde_yaml::with::singleton_map::deserialize(
+                serde_yaml::Deserializer::from_reader(reader),
+            )
+            .context("Converting from YAML failed"),
+            BlueprintFormat::Toml => {
+                let mut buffer = String::new();
+                reader.read_to_string(&mut buffer)?;
+                toml::from_str(&buffer).context("Converting from TOML failed")
+            }
+        }
+    }
+
+    fn write(self, mut writer: impl Write, data: &BlueprintData) -> Result<()> {
+        match self {
+            BlueprintFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, data)?;
+                writeln!(writer)?;
+            }
+            #[cfg(feature = "yaml")]
+            BlueprintFormat::Yaml => {
+                let mut serializer = serde_yaml::Serializer::new(&mut writer);
+                serde_yaml::with::singleton_map::serialize(data, &mut serializer)?;
+            }
+            BlueprintFormat::Toml => {
+                let content = toml::to_string_pretty(data)?;
+                writer.write_all(content.as_bytes())?;
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Opens `path` for reading, or stdin if there is no path.
+fn reader(path: Option<&Path>) -> Result<Box<dyn BufRead>> {
+    match path {
+        Some(path) => {
+            let file =
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+        None => Ok(Box::new(io::stdin().lock())),
+    }
+}
+
+/// Opens `path` for writing, or stdout if there is no path.
+fn writer(path: Option<&Path>) -> Result<BufWriter<Box<dyn Write>>> {
+    match path {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            Ok(BufWriter::new(Box::new(file)))
+        }
+        None => Ok(BufWriter::new(Box::new(io::stdout().lock()))),
+    }
+}
+
+/// Reads a blueprint string from `path`, or stdin if there is no path.
+fn read_string(path: Option<&Path>) -> Result<String> {
+    let mut buffer = String::new();
+    reader(path)?
+        .read_to_string(&mut buffer)
+        .context("Failed to read the blueprint string")?;
+
+    Ok(buffer)
+}
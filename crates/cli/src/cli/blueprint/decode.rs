@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use facti_lib::blueprint::BlueprintData;
+
+use super::{BlueprintFormat, read_string, writer};
+
+/// Decode a blueprint string.
+///
+/// Fields facti does not know about are kept as they are, so that encoding
+/// the result gives back the same blueprint.
+#[derive(Args, Debug)]
+pub struct BlueprintDecodeArgs {
+    /// File with the blueprint string, defaults to stdin.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+
+    /// Format to decode to.
+    #[arg(short, long, value_enum, default_value_t = Default::default())]
+    pub to: BlueprintFormat,
+
+    /// File to write to, defaults to stdout.
+    #[arg(short, long, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+}
+
+impl BlueprintDecodeArgs {
+    pub fn run(&self) -> Result<()> {
+        let string = read_string(self.input.as_deref())?;
+        let data = BlueprintData::decode(&string).context("Failed to decode blueprint string")?;
+
+        self.to.write(writer(self.output.as_deref())?, &data)
+    }
+}
@@ -0,0 +1,34 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::{Args, ValueHint};
+
+use super::{BlueprintFormat, reader, writer};
+
+/// Encode a decoded blueprint back to a blueprint string.
+#[derive(Args, Debug)]
+pub struct BlueprintEncodeArgs {
+    /// File with the decoded blueprint, defaults to stdin.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+
+    /// Format to encode from.
+    #[arg(short, long, value_enum, default_value_t = Default::default())]
+    pub from: BlueprintFormat,
+
+    /// File to write the blueprint string to, defaults to stdout.
+    #[arg(short, long, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+}
+
+impl BlueprintEncodeArgs {
+    pub fn run(&self) -> Result<()> {
+        let data = self.from.read(reader(self.input.as_deref())?)?;
+
+        let mut writer = writer(self.output.as_deref())?;
+        writeln!(writer, "{}", data.encode()?)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
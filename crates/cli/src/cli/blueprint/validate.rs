@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use clap::{Args, ValueHint};
+use facti_lib::blueprint::{self, BlueprintData};
+use serde_json::Value;
+
+use super::read_string;
+
+/// Check that blueprint strings can be decoded.
+///
+/// Every string is decoded, and encoded again to check that nothing would
+/// be lost by `facti blueprint decode` and `facti blueprint encode`.
+/// Fails if any string is invalid.
+#[derive(Args, Debug)]
+pub struct BlueprintValidateArgs {
+    /// Files with a blueprint string each, defaults to reading one string
+    /// from stdin.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub files: Vec<PathBuf>,
+}
+
+impl BlueprintValidateArgs {
+    pub fn run(&self) -> Result<()> {
+        let paths = match self.files.is_empty() {
+            true => vec![None],
+            false => self.files.iter().map(|p| Some(p.as_path())).collect(),
+        };
+
+        let mut invalid = 0;
+        for path in &paths {
+            let name = path.map_or("<stdin>".into(), Path::to_string_lossy);
+            match validate(*path) {
+                Ok(data) => println!("{}: {}", name, describe(&data)),
+                Err(e) => {
+                    println!("{}: invalid: {:#}", name, e);
+                    invalid += 1;
+                }
+            }
+        }
+
+        if invalid > 0 {
+            bail!(
+                "{} of {} blueprint strings are invalid",
+                invalid,
+                paths.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn validate(path: Option<&Path>) -> Result<BlueprintData> {
+    let json = blueprint::decode_json(&read_string(path)?)?;
+    let data: BlueprintData = serde_json::from_str(&json)?;
+
+    let original: Value = serde_json::from_str(&json)?;
+    if serde_json::to_value(&data)? != original {
+        bail!("Encoding the blueprint again would change it");
+    }
+
+    Ok(data)
+}
+
+fn describe(data: &BlueprintData) -> String {
+    let kind = match data {
+        BlueprintData::Blueprint(_) => "blueprint",
+        BlueprintData::BlueprintBook(_) => "blueprint book",
+        BlueprintData::DeconstructionPlanner(_) => "deconstruction planner",
+        BlueprintData::UpgradePlanner(_) => "upgrade planner",
+    };
+    let [major, minor, patch, _] = blueprint::map_version(data.version());
+
+    match data.label() {
+        Some(label) => format!(
+            "{} \"{}\" (Factorio {}.{}.{})",
+            kind, label, major, minor, patch
+        ),
+        None => format!("{} (Factorio {}.{}.{})", kind, major, minor, patch),
+    }
+}
@@ -7,8 +7,13 @@ use std::{
 
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand, ValueEnum, ValueHint};
+use facti_api::blocking::ApiClient;
 use facti_lib::changelog::Changelog;
 
+use self::diff::ChangelogDiffArgs;
+
+mod diff;
+
 #[derive(Args, Debug)]
 pub struct ChangelogArgs {
     #[command(subcommand)]
@@ -19,6 +24,9 @@ pub struct ChangelogArgs {
 pub enum ChangelogCommands {
     /// Convert mod changelogs between different formats.
     Convert(ChangelogConvertArgs),
+
+    /// Compare the changelog of a mod with a released changelog.
+    Diff(ChangelogDiffArgs),
 }
 
 #[derive(Args, Debug)]
@@ -57,9 +65,10 @@ pub enum ChangelogFormat {
 }
 
 impl ChangelogArgs {
-    pub fn run(&self) -> Result<()> {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
         match &self.command {
             ChangelogCommands::Convert(args) => args.run(),
+            ChangelogCommands::Diff(args) => args.run(client),
         }
     }
 }
@@ -0,0 +1,320 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use facti_api::blocking::ApiClient;
+use facti_lib::{
+    changelog::{Changelog, Section},
+    version::Version,
+};
+use serde::Serialize;
+use tracing::info;
+
+use crate::project::Project;
+
+/// Name of the changelog file inside a mod.
+const CHANGELOG_FILENAME: &str = "changelog.txt";
+
+/// Compare the changelog of a mod project with a released changelog.
+///
+/// With `--portal`, the changelog of the latest release on the mod portal
+/// is used, and releases on the portal without a section in it are
+/// reported as well.
+/// Otherwise, the local changelog is compared with the given file.
+///
+/// Sections are matched by version, and are reported when they are missing
+/// from the local changelog, or when their entries or date differ (i.e. the
+/// local section was edited after the release).
+#[derive(Args, Debug)]
+pub struct ChangelogDiffArgs {
+    /// Path to the mod project, defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Compare with the changelog of the mod on the mod portal.
+    #[arg(long, conflicts_with = "other")]
+    pub portal: bool,
+
+    /// Changelog file to compare with.
+    #[arg(required_unless_present = "portal", value_hint = ValueHint::FilePath)]
+    pub other: Option<PathBuf>,
+
+    /// Output the differences as JSON.
+    ///
+    /// Tip: You can use jq to work with JSON!
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+/// Differences between the local changelog and a released one.
+#[derive(Debug, Default, Serialize)]
+struct ChangelogDiff {
+    /// Versions with a section in the released changelog, but not locally.
+    missing_locally: Vec<Version>,
+
+    /// Sections whose local version differs from the released one.
+    edited: Vec<EditedSection>,
+
+    /// Released versions without a section in the released changelog.
+    undocumented: Vec<Version>,
+}
+
+#[derive(Debug, Serialize)]
+struct EditedSection {
+    version: Version,
+
+    /// Released and local date, if they differ.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<(Option<String>, Option<String>)>,
+
+    /// Entries only in the local section, as `(category, entry)`.
+    added: Vec<(String, String)>,
+
+    /// Entries only in the released section, as `(category, entry)`.
+    removed: Vec<(String, String)>,
+}
+
+impl ChangelogDiffArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let project = Project::load(&path).with_context(|| {
+            format!(
+                "Failed to load Factorio mod project from {}",
+                path.display()
+            )
+        })?;
+
+        let local = read(&project.mod_path.join(CHANGELOG_FILENAME))?;
+
+        let diff = match &self.other {
+            Some(other) => diff(&local, &read(other)?, &[]),
+            None => {
+                let name = &project.mod_info.name;
+                info!("Fetching changelog of {} from the mod portal", name);
+                let full = client
+                    .info_full(name)
+                    .with_context(|| format!("Failed to get details of {}", name))?;
+
+                let released = match &full.changelog {
+                    Some(changelog) => Changelog::parse(changelog)
+                        .context("Failed to parse the changelog on the portal")?,
+                    None => Changelog { sections: vec![] },
+                };
+                let versions = full.releases.iter().map(|r| r.version).collect::<Vec<_>>();
+
+                diff(&local, &released, &versions)
+            }
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            print_diff(&diff);
+        }
+
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> Result<Changelog> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    Changelog::parse(content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Compares `local` with `released`, also reporting which of the
+/// `releases` lack a section in `released`.
+fn diff(local: &Changelog, released: &Changelog, releases: &[Version]) -> ChangelogDiff {
+    let local_sections = by_version(local);
+    let released_sections = by_version(released);
+
+    let mut diff = ChangelogDiff::default();
+
+    for (version, released) in &released_sections {
+        match local_sections.get(version) {
+            None => diff.missing_locally.push(*version),
+            Some(local) => {
+                if let Some(edited) = compare(released, local) {
+                    diff.edited.push(edited);
+                }
+            }
+        }
+    }
+
+    let mut undocumented = releases
+        .iter()
+        .filter(|v| !released_sections.contains_key(v))
+        .copied()
+        .collect::<Vec<_>>();
+    undocumented.sort();
+    undocumented.dedup();
+    diff.undocumented = undocumented;
+
+    diff
+}
+
+fn by_version(changelog: &Changelog) -> BTreeMap<Version, &Section> {
+    changelog.sections.iter().map(|s| (s.version, s)).collect()
+}
+
+/// Compares two sections of the same version, returning what changed
+/// if they differ.
+fn compare(released: &Section, local: &Section) -> Option<EditedSection> {
+    let released_entries = entries(released);
+    let local_entries = entries(local);
+
+    let added = local_entries
+        .difference(&released_entries)
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed = released_entries
+        .difference(&local_entries)
+        .cloned()
+        .collect::<Vec<_>>();
+    let date = (released.date != local.date).then(|| (released.date.clone(), local.date.clone()));
+
+    if added.is_empty() && removed.is_empty() && date.is_none() {
+        return None;
+    }
+
+    Some(EditedSection {
+        version: local.version,
+        date,
+        added,
+        removed,
+    })
+}
+
+/// Collects the entries of a section as sorted `(category, entry)` pairs.
+fn entries(section: &Section) -> BTreeSet<(String, String)> {
+    section
+        .categories
+        .iter()
+        .flat_map(|(category, entries)| {
+            entries
+                .iter()
+                .map(move |entry| (category.to_string(), entry.to_owned()))
+        })
+        .collect()
+}
+
+fn print_diff(diff: &ChangelogDiff) {
+    if diff.missing_locally.is_empty() && diff.edited.is_empty() && diff.undocumented.is_empty() {
+        println!("The local changelog matches the released one");
+        return;
+    }
+
+    if !diff.missing_locally.is_empty() {
+        println!("Versions missing from the local changelog:");
+        for version in &diff.missing_locally {
+            println!("  {}", version);
+        }
+    }
+
+    if !diff.edited.is_empty() {
+        println!("Sections edited after release:");
+        for section in &diff.edited {
+            println!("  {}", section.version);
+
+            if let Some((released, local)) = &section.date {
+                println!(
+                    "    date: {} -> {}",
+                    released.as_deref().unwrap_or("(none)"),
+                    local.as_deref().unwrap_or("(none)")
+                );
+            }
+
+            for (category, entry) in &section.removed {
+                println!("    - {}: {}", category, entry);
+            }
+
+            for (category, entry) in &section.added {
+                println!("    + {}: {}", category, entry);
+            }
+        }
+    }
+
+    if !diff.undocumented.is_empty() {
+        println!("Releases without a changelog section:");
+        for version in &diff.undocumented {
+            println!("  {}", version);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const RELEASED: &str = indoc! {"
+        ---------------------------------------------------------------------------------------------------
+        Version: 1.1.0
+        Date: 2024-01-02
+          Bugfixes:
+            - Fixed a crash
+        ---------------------------------------------------------------------------------------------------
+        Version: 1.0.0
+          Features:
+            - Initial release
+    "};
+
+    const LOCAL: &str = indoc! {"
+        ---------------------------------------------------------------------------------------------------
+        Version: 1.1.0
+        Date: 2024-01-02
+          Bugfixes:
+            - Fixed a crash when loading saves
+    "};
+
+    #[test]
+    fn test_diff() {
+        let local = Changelog::parse(LOCAL).unwrap();
+        let released = Changelog::parse(RELEASED).unwrap();
+        let releases = [
+            Version::new(1, 0, 0),
+            Version::new(1, 0, 1),
+            Version::new(1, 1, 0),
+        ];
+
+        let diff = diff(&local, &released, &releases);
+
+        assert_eq!(diff.missing_locally, vec![Version::new(1, 0, 0)]);
+        assert_eq!(diff.undocumented, vec![Version::new(1, 0, 1)]);
+        assert_eq!(diff.edited.len(), 1);
+
+        let edited = &diff.edited[0];
+        assert_eq!(edited.version, Version::new(1, 1, 0));
+        assert!(edited.date.is_none());
+        assert_eq!(
+            edited.removed,
+            vec![("Bugfixes".to_owned(), "Fixed a crash".to_owned())]
+        );
+        assert_eq!(
+            edited.added,
+            vec![(
+                "Bugfixes".to_owned(),
+                "Fixed a crash when loading saves".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let changelog = Changelog::parse(RELEASED).unwrap();
+        let diff = diff(&changelog, &changelog, &[]);
+
+        assert!(diff.missing_locally.is_empty());
+        assert!(diff.edited.is_empty());
+        assert!(diff.undocumented.is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use facti_api::blocking::ApiClient;
+
+use self::graph::DepsGraphArgs;
+
+mod graph;
+
+/// Inspect the dependencies of a mod.
+#[derive(Args, Debug)]
+pub struct DepsArgs {
+    #[command(subcommand)]
+    pub command: DepsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DepsCommands {
+    Graph(DepsGraphArgs),
+}
+
+impl DepsArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        match &self.command {
+            DepsCommands::Graph(args) => args.run(client),
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    fmt::Write,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::Release, error::ApiErrorKind};
+use facti_lib::{
+    FactorioVersion,
+    dependency::{Compatibility, Dependency, DependencyMode},
+    version::{Version, VersionReq},
+};
+use serde::Serialize;
+use tracing::{debug, info};
+
+use crate::{mods::BUILT_IN_MODS, project::Project};
+
+/// Render the transitive dependency graph of a mod.
+///
+/// Required and independent dependencies are looked up on the mod portal
+/// and followed recursively, using the latest release that satisfies the
+/// version requirement and targets the same version of Factorio.
+/// Optional dependencies and incompatibilities are shown, but only followed
+/// with `--expand-optional`.
+///
+/// Edges are styled by the kind of dependency, and labelled with the
+/// version requirement.
+#[derive(Args, Debug)]
+pub struct DepsGraphArgs {
+    /// Path to the mod project, defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Format to render the graph in.
+    #[arg(short, long, value_enum, default_value_t = Default::default())]
+    pub format: GraphFormat,
+
+    /// Also follow the dependencies of optional dependencies.
+    #[arg(long)]
+    pub expand_optional: bool,
+}
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GraphFormat {
+    /// Graphviz DOT.
+    #[default]
+    Dot,
+
+    /// Mermaid flowchart.
+    Mermaid,
+
+    /// JSON object with the `nodes` and `edges` of the graph, where edges
+    /// refer to nodes by their index.
+    Json,
+}
+
+/// A dependency graph, with the mod it was made for as the first node.
+#[derive(Debug, Serialize)]
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+#[derive(Debug, Serialize)]
+struct Node {
+    name: String,
+    kind: NodeKind,
+
+    /// Version of the mod whose dependencies were followed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<Version>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum NodeKind {
+    /// The mod the graph was made for.
+    Root,
+
+    /// A mod on the portal whose dependencies were followed.
+    Mod,
+
+    /// A mod that is part of the game.
+    BuiltIn,
+
+    /// A mod whose dependencies were not followed.
+    Unresolved,
+
+    /// A mod that does not exist on the portal, or has no release
+    /// satisfying all requirements on it.
+    Missing,
+}
+
+#[derive(Debug, Serialize)]
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: EdgeKind,
+    requirement: String,
+
+    /// The version requirement, unless this is an incompatibility.
+    #[serde(skip)]
+    version_req: Option<VersionReq>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum EdgeKind {
+    Required,
+    Optional,
+    HiddenOptional,
+    Independent,
+    Incompatible,
+}
+
+impl DepsGraphArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let project = Project::load(&path).with_context(|| {
+            format!(
+                "Failed to load Factorio mod project from {}",
+                path.display()
+            )
+        })?;
+
+        let graph = self.build(client, &project)?;
+
+        match self.format {
+            GraphFormat::Dot => print!("{}", graph.to_dot()),
+            GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+            GraphFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+        }
+
+        Ok(())
+    }
+
+    /// Builds the graph, picking for every mod that is followed the latest
+    /// release that satisfies all requirements on it, like `facti pack lock`.
+    ///
+    /// Whenever a new requirement rules out the release picked for a mod,
+    /// the mod is picked again and its dependencies are replaced by those
+    /// of the new release. Mods without a release satisfying all
+    /// requirements are marked as missing.
+    fn build(&self, client: &ApiClient, project: &Project) -> Result<Graph> {
+        let info = &project.mod_info;
+        let factorio_version = info.factorio_version;
+
+        let mut graph = Graph {
+            nodes: vec![Node {
+                name: info.name.clone(),
+                kind: NodeKind::Root,
+                version: Some(info.version),
+            }],
+            edges: Vec::new(),
+        };
+        let mut ids = HashMap::from([(info.name.clone(), 0)]);
+        let mut releases = HashMap::new();
+        // Dependencies to add, with the version of the mod they belong to
+        let mut queue = VecDeque::from([(0, Some(info.version), info.dependencies.clone())]);
+
+        while let Some((from, version, dependencies)) = queue.pop_front() {
+            // The mod was picked again since
+            if graph.nodes[from].version != version {
+                continue;
+            }
+
+            for dependency in dependencies {
+                let kind = EdgeKind::from(&dependency.compatibility);
+                let to = *ids.entry(dependency.name.clone()).or_insert_with(|| {
+                    graph.nodes.push(Node {
+                        name: dependency.name.clone(),
+                        kind: NodeKind::Unresolved,
+                        version: None,
+                    });
+                    graph.nodes.len() - 1
+                });
+
+                let version_req = match &dependency.compatibility {
+                    Compatibility::Compatible(_, req) => Some(*req),
+                    Compatibility::Incompatible => None,
+                };
+                graph.edges.push(Edge {
+                    from,
+                    to,
+                    kind,
+                    requirement: requirement(&dependency),
+                    version_req,
+                });
+
+                let node = &graph.nodes[to];
+                let needs_pick = match node.kind {
+                    NodeKind::Unresolved => self.follows(kind),
+                    NodeKind::Mod => version_req.is_some_and(|req| {
+                        node.version
+                            .is_some_and(|version| !satisfies(&req, version))
+                    }),
+                    NodeKind::Root | NodeKind::BuiltIn | NodeKind::Missing => false,
+                };
+                if !needs_pick {
+                    continue;
+                }
+
+                if BUILT_IN_MODS.contains(&node.name.as_str()) {
+                    graph.nodes[to].kind = NodeKind::BuiltIn;
+                    continue;
+                }
+
+                // Dependencies of the previously picked release no longer apply
+                let repick = node.kind == NodeKind::Mod;
+                if repick {
+                    graph.edges.retain(|e| e.from != to);
+                }
+
+                let requirements = graph
+                    .edges
+                    .iter()
+                    .filter(|e| e.to == to)
+                    .filter_map(|e| e.version_req)
+                    .collect::<Vec<_>>();
+                let name = graph.nodes[to].name.clone();
+                let release = pick(
+                    client,
+                    &mut releases,
+                    &name,
+                    &requirements,
+                    factorio_version,
+                )?;
+
+                let node = &mut graph.nodes[to];
+                match release {
+                    Some(release) => {
+                        if repick {
+                            debug!("Picked {} {} instead", name, release.version);
+                        }
+                        node.kind = NodeKind::Mod;
+                        node.version = Some(release.version);
+                        queue.push_back((
+                            to,
+                            node.version,
+                            release.info.dependencies.unwrap_or_default(),
+                        ));
+                    }
+                    None => {
+                        node.kind = NodeKind::Missing;
+                        node.version = None;
+                    }
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Whether the dependencies of a dependency should be followed.
+    fn follows(&self, kind: EdgeKind) -> bool {
+        match kind {
+            EdgeKind::Required | EdgeKind::Independent => true,
+            EdgeKind::Optional | EdgeKind::HiddenOptional => self.expand_optional,
+            EdgeKind::Incompatible => false,
+        }
+    }
+}
+
+/// Finds the latest release of a mod that satisfies all of the given
+/// requirements and targets the given version of Factorio.
+///
+/// Releases are looked up once per mod and cached in `releases`, where
+/// [`None`] means the mod does not exist on the portal.
+fn pick(
+    client: &ApiClient,
+    releases: &mut HashMap<String, Option<Vec<Release>>>,
+    name: &str,
+    requirements: &[VersionReq],
+    factorio_version: FactorioVersion,
+) -> Result<Option<Release>> {
+    if !releases.contains_key(name) {
+        info!("Fetching releases of {}", name);
+        let found = match client.info_short(name) {
+            Ok(short) => Some(short.releases),
+            Err(e) if *e.kind() == ApiErrorKind::UnknownMod => {
+                debug!("{} does not exist on the portal", name);
+                None
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to get releases of {}", name));
+            }
+        };
+        releases.insert(name.to_owned(), found);
+    }
+
+    Ok(releases[name]
+        .iter()
+        .flatten()
+        .filter(|r| {
+            r.info.factorio_version.major == factorio_version.major
+                && r.info.factorio_version.minor == factorio_version.minor
+        })
+        .filter(|r| requirements.iter().all(|req| satisfies(req, r.version)))
+        .max_by_key(|r| r.version)
+        .cloned())
+}
+
+fn satisfies(req: &VersionReq, version: Version) -> bool {
+    match req {
+        VersionReq::Latest => true,
+        VersionReq::Spec(spec) => spec.matches(version),
+    }
+}
+
+impl From<&Compatibility> for EdgeKind {
+    fn from(compatibility: &Compatibility) -> Self {
+        match compatibility {
+            Compatibility::Compatible(DependencyMode::Required, _) => EdgeKind::Required,
+            Compatibility::Compatible(DependencyMode::Optional { hidden: false }, _) => {
+                EdgeKind::Optional
+            }
+            Compatibility::Compatible(DependencyMode::Optional { hidden: true }, _) => {
+                EdgeKind::HiddenOptional
+            }
+            Compatibility::Compatible(DependencyMode::Independent, _) => EdgeKind::Independent,
+            Compatibility::Incompatible => EdgeKind::Incompatible,
+        }
+    }
+}
+
+/// Formats the version requirement of a dependency, prefixed with the
+/// symbol `info.json` uses for its mode (e.g. `? >= 1.0.0`).
+fn requirement(dependency: &Dependency) -> String {
+    let label = match &dependency.compatibility {
+        Compatibility::Compatible(mode, req) => format!("{} {}", mode, req),
+        Compatibility::Incompatible => "!".to_owned(),
+    };
+
+    label.trim().to_owned()
+}
+
+impl Graph {
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    node [shape=box];\n");
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = match node.version {
+                Some(version) => format!("{}\\n{}", node.name, version),
+                None => node.name.clone(),
+            };
+            let style = match node.kind {
+                NodeKind::Root => ", style=bold",
+                NodeKind::Mod | NodeKind::Unresolved => "",
+                NodeKind::BuiltIn => ", style=filled, fillcolor=lightgrey",
+                NodeKind::Missing => ", style=dashed, color=red",
+            };
+            writeln!(dot, "    n{} [label=\"{}\"{}];", id, label, style).unwrap();
+        }
+
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Required => "",
+                EdgeKind::Optional => ", style=dashed",
+                EdgeKind::HiddenOptional => ", style=dotted",
+                EdgeKind::Independent => ", arrowhead=empty",
+                EdgeKind::Incompatible => ", color=red, arrowhead=tee",
+            };
+            writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.from, edge.to, edge.requirement, style
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = match node.version {
+                Some(version) => format!("{}<br>{}", node.name, version),
+                None => node.name.clone(),
+            };
+            writeln!(mermaid, "    n{}[\"{}\"]", id, label).unwrap();
+        }
+
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                EdgeKind::Required => "-->",
+                EdgeKind::Optional | EdgeKind::HiddenOptional => "-.->",
+                EdgeKind::Independent => "--o",
+                EdgeKind::Incompatible => "--x",
+            };
+
+            if edge.requirement.is_empty() {
+                writeln!(mermaid, "    n{} {} n{}", edge.from, arrow, edge.to).unwrap();
+            } else {
+                writeln!(
+                    mermaid,
+                    "    n{} {}|\"{}\"| n{}",
+                    edge.from, arrow, edge.requirement, edge.to
+                )
+                .unwrap();
+            }
+        }
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            match node.kind {
+                NodeKind::BuiltIn => writeln!(mermaid, "    style n{} fill:#ddd", id).unwrap(),
+                NodeKind::Missing => {
+                    writeln!(mermaid, "    style n{} stroke:#f00,stroke-dasharray:4", id).unwrap()
+                }
+                _ => {}
+            }
+        }
+
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        let node = |name: &str, kind, version| Node {
+            name: name.to_owned(),
+            kind,
+            version,
+        };
+        let edge = |to, kind, requirement: &str| Edge {
+            from: 0,
+            to,
+            kind,
+            requirement: requirement.to_owned(),
+            version_req: None,
+        };
+
+        Graph {
+            nodes: vec![
+                node("my-mod", NodeKind::Root, Some(Version::new(1, 0, 0))),
+                node("base", NodeKind::BuiltIn, None),
+                node("flib", NodeKind::Unresolved, None),
+                node("bad-mod", NodeKind::Unresolved, None),
+            ],
+            edges: vec![
+                edge(1, EdgeKind::Required, ">= 2.0.0"),
+                edge(2, EdgeKind::HiddenOptional, "(?)"),
+                edge(3, EdgeKind::Incompatible, "!"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_requirement() {
+        let dependency = Dependency::parse("? flib >= 0.12.0").unwrap();
+        assert_eq!(requirement(&dependency), "? >= 0.12.0");
+
+        let dependency = Dependency::parse("base").unwrap();
+        assert_eq!(requirement(&dependency), "");
+
+        let dependency = Dependency::parse("! bad-mod").unwrap();
+        assert_eq!(requirement(&dependency), "!");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = graph().to_dot();
+
+        assert!(dot.starts_with("digraph dependencies {\n"));
+        assert!(dot.contains("    n0 [label=\"my-mod\\n1.0.0\", style=bold];\n"));
+        assert!(dot.contains("    n0 -> n1 [label=\">= 2.0.0\"];\n"));
+        assert!(dot.contains("    n0 -> n2 [label=\"(?)\", style=dotted];\n"));
+        assert!(dot.contains("    n0 -> n3 [label=\"!\", color=red, arrowhead=tee];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let mermaid = graph().to_mermaid();
+
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    n0[\"my-mod<br>1.0.0\"]\n"));
+        assert!(mermaid.contains("    n0 -->|\">= 2.0.0\"| n1\n"));
+        assert!(mermaid.contains("    n0 -.->|\"(?)\"| n2\n"));
+        assert!(mermaid.contains("    n0 --x|\"!\"| n3\n"));
+        assert!(mermaid.contains("    style n1 fill:#ddd\n"));
+    }
+}
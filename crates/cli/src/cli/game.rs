@@ -0,0 +1,220 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueHint};
+use facti_api::{blocking::ApiClient, data::game::LatestReleases};
+use facti_lib::{FactorioVersion, version::Version};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::project::Project;
+
+/// Get information about releases of Factorio (the game).
+#[derive(Args, Debug)]
+pub struct GameArgs {
+    /// Output results as JSON.
+    ///
+    /// Tip: You can use jq to work with JSON!
+    #[arg(short, long)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: GameCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GameCommands {
+    /// Show the latest stable and experimental versions of the game.
+    Latest,
+
+    /// Check whether a mod targets the current version of the game.
+    ///
+    /// Compares the `factorio_version` in the mod's `info.json` with the
+    /// major and minor version of the latest stable and experimental releases,
+    /// and warns if the mod targets an outdated version.
+    Check(GameCheckArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct GameCheckArgs {
+    /// Path to the mod project, defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+}
+
+/// How a mod's targeted game version relates to the latest releases.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameVersionStatus {
+    /// Targets the same major.minor as the latest stable release.
+    Current,
+
+    /// Targets a newer major.minor than stable, which is available
+    /// on the experimental channel.
+    Experimental,
+
+    /// Targets an older major.minor than the latest stable release.
+    Outdated,
+
+    /// Targets a version newer than any release of the game.
+    Unreleased,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    name: String,
+    factorio_version: String,
+    stable: String,
+    experimental: String,
+    status: GameVersionStatus,
+}
+
+impl GameArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        match &self.command {
+            GameCommands::Latest => latest(client, self.json),
+            GameCommands::Check(args) => args.run(client, self.json),
+        }
+    }
+}
+
+fn latest(client: &ApiClient, json: bool) -> Result<()> {
+    let latest = client
+        .latest_releases()
+        .context("Failed to get latest game releases")?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&latest)?);
+    } else {
+        for (channel, release) in [
+            ("stable", latest.stable),
+            ("experimental", latest.experimental),
+        ] {
+            println!(
+                "{}: {} (demo {}, headless {})",
+                channel, release.alpha, release.demo, release.headless
+            );
+        }
+    }
+
+    Ok(())
+}
+
+impl GameCheckArgs {
+    pub fn run(&self, client: &ApiClient, json: bool) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let project = Project::load(&path).with_context(|| {
+            format!(
+                "Failed to load Factorio mod project from {}",
+                path.display()
+            )
+        })?;
+
+        info!("Fetching latest game releases");
+        let latest = client
+            .latest_releases()
+            .context("Failed to get latest game releases")?;
+
+        let target = project.mod_info.factorio_version;
+        let status = status(target, &latest);
+        let stable = major_minor(latest.stable.alpha);
+        let experimental = major_minor(latest.experimental.alpha);
+
+        if status == GameVersionStatus::Outdated {
+            warn!(
+                "{} targets Factorio {}, but the latest stable release is {}",
+                project.mod_info.name, target, stable
+            );
+        }
+
+        if json {
+            let report = CheckReport {
+                name: project.mod_info.name,
+                factorio_version: target.to_string(),
+                stable: stable.to_string(),
+                experimental: experimental.to_string(),
+                status,
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            let description = match status {
+                GameVersionStatus::Current => "the latest stable release",
+                GameVersionStatus::Experimental => "the experimental release",
+                GameVersionStatus::Outdated => "an outdated release",
+                GameVersionStatus::Unreleased => "an unreleased version",
+            };
+            println!(
+                "{} targets Factorio {}, {} (stable: {}, experimental: {})",
+                project.mod_info.name, target, description, stable, experimental
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Strips the patch component, as mods only target a major.minor version.
+fn major_minor(version: Version) -> FactorioVersion {
+    FactorioVersion::new(version.major, version.minor)
+}
+
+fn status(target: FactorioVersion, latest: &LatestReleases) -> GameVersionStatus {
+    let target = major_minor(target.into());
+    let stable = major_minor(latest.stable.alpha);
+    let experimental = major_minor(latest.experimental.alpha);
+
+    if target == stable {
+        GameVersionStatus::Current
+    } else if target < stable {
+        GameVersionStatus::Outdated
+    } else if target <= experimental {
+        GameVersionStatus::Experimental
+    } else {
+        GameVersionStatus::Unreleased
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use facti_api::data::game::LatestRelease;
+
+    use super::*;
+
+    fn latest(stable: Version, experimental: Version) -> LatestReleases {
+        let release = |version| LatestRelease {
+            alpha: version,
+            demo: version,
+            headless: version,
+        };
+
+        LatestReleases {
+            stable: release(stable),
+            experimental: release(experimental),
+        }
+    }
+
+    #[test]
+    fn test_status() {
+        let latest = latest(Version::new(1, 1, 110), Version::new(2, 0, 7));
+
+        assert_eq!(
+            status(FactorioVersion::new(1, 1), &latest),
+            GameVersionStatus::Current
+        );
+        assert_eq!(
+            status(FactorioVersion::new(1, 0), &latest),
+            GameVersionStatus::Outdated
+        );
+        assert_eq!(
+            status(FactorioVersion::new(2, 0), &latest),
+            GameVersionStatus::Experimental
+        );
+        assert_eq!(
+            status(FactorioVersion::new(2, 1), &latest),
+            GameVersionStatus::Unreleased
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use self::order::ModsOrderArgs;
+
+mod order;
+
+/// Work with a folder of installed mods.
+#[derive(Args, Debug)]
+pub struct ModsArgs {
+    #[command(subcommand)]
+    pub command: ModsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ModsCommands {
+    Order(ModsOrderArgs),
+}
+
+impl ModsArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ModsCommands::Order(args) => args.run(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use facti_lib::{load_order::load_order, version::Version};
+use serde::Serialize;
+
+use crate::mods::ModsFolder;
+
+/// Print the order Factorio loads the mods in a mods folder in.
+///
+/// Mods are loaded by dependency depth, then by name, after `core` and
+/// `base`. Disabled mods (according to `mod-list.json`) are left out unless
+/// `--all` is given.
+///
+/// Fails with a report of the cycle if mods depend on each other in a cycle.
+#[derive(Args, Debug)]
+pub struct ModsOrderArgs {
+    /// Path to the mods folder, defaults to the current directory.
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Include disabled mods.
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Output the load order as JSON.
+    ///
+    /// Tip: You can use jq to work with JSON!
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct OrderedMod<'a> {
+    name: &'a str,
+    version: Version,
+}
+
+impl ModsOrderArgs {
+    pub fn run(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let folder = ModsFolder::load(&path)
+            .with_context(|| format!("Failed to load mods folder {}", path.display()))?;
+
+        let mods = folder
+            .mods
+            .values()
+            .filter(|m| self.all || folder.is_enabled(&m.info.name))
+            .map(|m| m.info.clone())
+            .collect::<Vec<_>>();
+
+        let order = load_order(&mods)?
+            .into_iter()
+            .map(|info| OrderedMod {
+                name: &info.name,
+                version: info.version,
+            })
+            .collect::<Vec<_>>();
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&order)?);
+        } else {
+            for (i, m) in order.iter().enumerate() {
+                println!("{:>4}. {} {}", i + 1, m.name, m.version);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,17 +1,21 @@
 use std::{
     env,
     fs::{self, File},
-    path::{Path, PathBuf},
+    io::{self, Cursor},
+    path::{Component, Path, PathBuf},
 };
 
 use anyhow::{Context, Result, anyhow, bail};
-use clap::Args;
+use clap::{Args, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::Release};
 use facti_lib::{FactorioVersion, ModInfo, version::Version};
 use tracing::{debug, info, warn};
 use url::Url;
+use zip::ZipArchive;
 
 use crate::{
     config::Config,
+    mods,
     vcs::{self, Vcs},
 };
 
@@ -72,11 +76,42 @@ pub struct NewArgs {
     /// and if none exists there, it will fall back to version 0.12.
     #[arg(long)]
     pub factorio_version: Option<FactorioVersion>,
+
+    /// Import an existing mod from the mod portal instead of creating
+    /// an empty one.
+    ///
+    /// Every release of the mod is downloaded and committed to the new Git
+    /// repository in order, dated with its release time and tagged with its
+    /// version (e.g. `v1.2.3`), to reconstruct a history of the mod.
+    ///
+    /// Downloading releases requires a Factorio username and token,
+    /// see `--username` and `--token`.
+    #[arg(
+        long,
+        value_name = "MOD",
+        value_hint = ValueHint::Other,
+        conflicts_with_all = [
+            "name",
+            "mod_version",
+            "title",
+            "author",
+            "contact",
+            "homepage",
+            "description",
+            "factorio_version"
+        ]
+    )]
+    pub from_portal: Option<String>,
 }
 
 impl NewArgs {
-    pub fn run(&self, config: &Config) -> Result<()> {
+    pub fn run(&self, config: &Config, client: &ApiClient) -> Result<()> {
         let path = self.path.to_owned().unwrap_or(env::current_dir()?);
+
+        if let Some(name) = &self.from_portal {
+            return import(&path, name, client);
+        }
+
         let name = path
             .file_name()
             .context("Failed to get file_name (basename) of mod path")?
@@ -104,6 +139,145 @@ impl NewArgs {
     }
 }
 
+/// Creates a new mod project from the releases of a mod on the portal,
+/// with one commit per release.
+fn import(path: &Path, name: &str, client: &ApiClient) -> Result<()> {
+    info!("Fetching releases of {} from the mod portal", name);
+    let short = client
+        .info_short(name)
+        .with_context(|| format!("Failed to get releases of {}", name))?;
+    let owner = short.owner;
+    let mut releases = short.releases;
+    releases.sort_by_key(|r| (r.released_at, r.version));
+
+    if releases.is_empty() {
+        bail!("{} has no releases to import", name);
+    }
+
+    let src_path = path.join("src");
+    for (i, release) in releases.iter().enumerate() {
+        let zip = mods::fetch(client, name, release)?;
+
+        // Only create the project once the first download succeeded, so
+        // that a missing token does not leave an empty project behind
+        if i == 0 {
+            init_dir(path)?;
+            info!("Importing {} into {}", name, path.display());
+            vcs::git::Git::init(path)?;
+        }
+
+        if src_path.exists() {
+            fs::remove_dir_all(&src_path).context("Failed to clear src dir")?;
+        }
+        unpack(&zip, &src_path)
+            .with_context(|| format!("Failed to unpack {} v{}", name, release.version))?;
+
+        commit_release(path, release, &owner)?;
+    }
+
+    println!(
+        "Imported {} releases of {} into {}",
+        releases.len(),
+        name,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Commits a release, attributing it to the owner of the mod.
+///
+/// Git requires an email address, the address in the contact from the
+/// unpacked `info.json` is used if it has one, otherwise a no-reply address
+/// for the owner.
+fn commit_release(path: &Path, release: &Release, owner: &str) -> Result<()> {
+    let contact = fs::read(path.join("src").join("info.json"))
+        .ok()
+        .and_then(|json| serde_json::from_slice::<ModInfo>(&json).ok())
+        .and_then(|info| info.contact);
+    let email = contact
+        .as_deref()
+        .and_then(email_address)
+        .map(str::to_owned)
+        .unwrap_or_else(|| format!("{}@users.noreply.mods.factorio.com", owner));
+
+    vcs::git::Git::commit_all(
+        path,
+        &format!("Release {}", release.version),
+        owner,
+        &email,
+        release.released_at,
+    )
+    .with_context(|| format!("Failed to commit release {}", release.version))?;
+    vcs::git::Git::tag(path, &format!("v{}", release.version))
+}
+
+/// Extracts the email address from a contact, which is either a bare
+/// address or in the `Name <address>` form.
+fn email_address(contact: &str) -> Option<&str> {
+    let address = match contact.split_once('<') {
+        Some((_, rest)) => rest.split_once('>')?.0,
+        None => contact,
+    }
+    .trim();
+
+    let (user, domain) = address.split_once('@')?;
+    let valid = !user.is_empty()
+        && !domain.is_empty()
+        && !domain.contains('@')
+        && !address.contains(|c: char| c.is_whitespace() || matches!(c, '<' | '>'));
+
+    valid.then_some(address)
+}
+
+/// Unpacks a mod zip into `dest`, leaving out the directory that
+/// mod zips wrap their files in.
+fn unpack(zip: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = ZipArchive::new(Cursor::new(zip)).context("Failed to read zip")?;
+
+    let paths = (0..archive.len())
+        .map(|i| {
+            archive
+                .by_index(i)?
+                .enclosed_name()
+                .ok_or_else(|| zip::result::ZipError::InvalidArchive("Unsafe file path".into()))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read zip entries")?;
+
+    // Mod zips contain a single top level directory, usually `<name>_<version>`
+    let first = paths.first().and_then(|p| p.components().next());
+    let wrapped = |first: &Component| {
+        paths.iter().all(|p| p.starts_with(first))
+            && paths.iter().any(|p| p.components().count() > 1)
+    };
+    let prefix = match first {
+        Some(first) if wrapped(&first) => PathBuf::from(first.as_os_str()),
+        _ => PathBuf::new(),
+    };
+
+    for (i, path) in paths.iter().enumerate() {
+        let mut file = archive.by_index(i)?;
+        let target = dest.join(path.strip_prefix(&prefix)?);
+
+        if file.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut out = File::create(&target)
+            .with_context(|| format!("Failed to create {}", target.display()))?;
+        io::copy(&mut file, &mut out)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+    }
+
+    Ok(())
+}
+
 fn init_dir(path: &Path) -> Result<()> {
     let exists = path.exists();
 
@@ -212,3 +386,21 @@ fn resolve_factorio_version(args: &NewArgs, config: &Config) -> FactorioVersion
         .or(config.mod_defaults.factorio_version)
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_address() {
+        assert_eq!(email_address("jane@example.com"), Some("jane@example.com"));
+        assert_eq!(
+            email_address("Jane <jane@example.com>"),
+            Some("jane@example.com")
+        );
+        assert_eq!(email_address("Discord: x / mail: a@b.c"), None);
+        assert_eq!(email_address("https://example.com"), None);
+        assert_eq!(email_address("Jane <jane@example.com"), None);
+        assert_eq!(email_address("a@b@c"), None);
+    }
+}
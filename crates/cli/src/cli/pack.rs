@@ -0,0 +1,50 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use facti_api::blocking::ApiClient;
+
+use self::{
+    export::PackExportArgs, import::PackImportArgs, install::PackInstallArgs, lock::PackLockArgs,
+};
+
+mod export;
+mod import;
+mod install;
+mod lock;
+
+/// Work with modpacks described by a `modpack.toml` manifest.
+///
+/// The manifest lists the mods of the pack with version requirements,
+/// and the version of Factorio it targets. `facti pack lock` resolves it
+/// to exact releases in `modpack.lock`, which `facti pack install` then
+/// installs into a mods folder.
+///
+/// `facti pack export` and `facti pack import` move a modpack between
+/// machines as a single archive, without needing access to the mod portal
+/// to install it.
+#[derive(Args, Debug)]
+pub struct PackArgs {
+    #[command(subcommand)]
+    pub command: PackCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PackCommands {
+    Lock(PackLockArgs),
+
+    Install(PackInstallArgs),
+
+    Export(PackExportArgs),
+
+    Import(PackImportArgs),
+}
+
+impl PackArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        match &self.command {
+            PackCommands::Lock(args) => args.run(client),
+            PackCommands::Install(args) => args.run(client),
+            PackCommands::Export(args) => args.run(client),
+            PackCommands::Import(args) => args.run(),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use facti_api::blocking::ApiClient;
+use facti_lib::ModInfo;
+use tracing::{info, warn};
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    modpack::{ARCHIVE_FORMAT, ARCHIVE_MANIFEST_FILENAME, ArchiveManifest, ArchivedMod, Lockfile},
+    mods::{self, MOD_LIST_FILENAME, MOD_SETTINGS_FILENAME, ModsFolder},
+};
+
+use super::install::mod_list;
+
+/// Bundle a modpack into a single archive.
+///
+/// The archive contains the mod zips, `mod-list.json`, `mod-settings.dat`
+/// and a manifest with a summary and checksum of every mod, so that it can
+/// be installed with `facti pack import` without access to the mod portal.
+///
+/// Mods are taken either from a mods folder (`--mods-dir`), or from
+/// `modpack.lock` (`--lock`), in which case the locked releases are
+/// downloaded from the mod portal. Mods in a mods folder that are not
+/// zipped are left out.
+#[derive(Args, Debug)]
+pub struct PackExportArgs {
+    /// Path of the archive to create.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub output: PathBuf,
+
+    /// Export the mods in this mods folder.
+    #[arg(
+        long,
+        value_hint = ValueHint::DirPath,
+        required_unless_present = "lock",
+        conflicts_with = "lock"
+    )]
+    pub mods_dir: Option<PathBuf>,
+
+    /// Export the releases in `modpack.lock`.
+    #[arg(long)]
+    pub lock: bool,
+
+    /// Path to the directory with `modpack.lock`, defaults to the current
+    /// directory.
+    #[arg(long, requires = "lock", value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// `mod-settings.dat` to include, defaults to the one in the mods folder.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub settings: Option<PathBuf>,
+}
+
+impl PackExportArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let mut archive = ArchiveWriter::create(&self.output)?;
+        if let Err(e) = self.add_contents(client, &mut archive) {
+            archive.discard();
+            return Err(e);
+        }
+
+        let count = archive.manifest.mods.len();
+        archive.finish()?;
+        println!("Exported {} mods to {}", count, self.output.display());
+
+        Ok(())
+    }
+
+    fn add_contents(&self, client: &ApiClient, archive: &mut ArchiveWriter) -> Result<()> {
+        match &self.mods_dir {
+            Some(dir) => export_mods_dir(archive, dir)?,
+            None => {
+                let path = match &self.path {
+                    Some(path) => path.to_owned(),
+                    None => env::current_dir().context("Failed to get current directory")?,
+                };
+                export_lockfile(client, archive, &Lockfile::load(&path)?)?
+            }
+        }
+
+        let settings = match (&self.settings, &self.mods_dir) {
+            (Some(path), _) => Some(path.to_owned()),
+            (None, Some(dir)) => Some(dir.join(MOD_SETTINGS_FILENAME)).filter(|p| p.is_file()),
+            (None, None) => None,
+        };
+        if let Some(path) = settings {
+            archive.add_file(MOD_SETTINGS_FILENAME, open(&path)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds the zipped mods and `mod-list.json` of a mods folder to the archive.
+fn export_mods_dir(archive: &mut ArchiveWriter, dir: &Path) -> Result<()> {
+    let folder = ModsFolder::load(dir)
+        .with_context(|| format!("Failed to load mods folder {}", dir.display()))?;
+
+    for installed in folder.mods.values() {
+        let file_name = match installed.path.file_name().and_then(|n| n.to_str()) {
+            Some(file_name) if installed.path.is_file() => file_name.to_owned(),
+            _ => {
+                warn!(
+                    "Leaving out {}, only zipped mods can be exported",
+                    installed.path.display()
+                );
+                continue;
+            }
+        };
+
+        archive.add_mod(&installed.info, file_name, open(&installed.path)?)?;
+    }
+
+    let mod_list_path = dir.join(MOD_LIST_FILENAME);
+    if mod_list_path.is_file() {
+        archive.add_file(MOD_LIST_FILENAME, open(&mod_list_path)?)?;
+    }
+
+    Ok(())
+}
+
+/// Downloads the releases of a lockfile into the archive, with a
+/// `mod-list.json` enabling exactly them.
+///
+/// Releases are written to the archive as they are downloaded, so only one
+/// of them is held in memory at a time.
+fn export_lockfile(
+    client: &ApiClient,
+    archive: &mut ArchiveWriter,
+    lockfile: &Lockfile,
+) -> Result<()> {
+    archive.manifest.factorio_version = Some(lockfile.factorio_version);
+
+    for locked in &lockfile.mods {
+        let release = locked.release(lockfile.factorio_version);
+        let data = mods::fetch(client, &locked.name, &release)?;
+        let info = mods::read_zip_info(Cursor::new(&data))
+            .with_context(|| format!("Failed to read info.json of {}", locked.file_name))?;
+
+        archive.add_mod(&info, locked.file_name.clone(), Cursor::new(data))?;
+    }
+
+    let mod_list = serde_json::to_string_pretty(&mod_list(lockfile, []))?;
+    archive.add_file(
+        MOD_LIST_FILENAME,
+        Cursor::new(format!("{}\n", mod_list).into_bytes()),
+    )?;
+
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+/// Writes a modpack archive, one file at a time.
+///
+/// The archive is written to a temporary file next to it, which replaces
+/// it once everything has been written.
+struct ArchiveWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    zip: ZipWriter<BufWriter<File>>,
+    manifest: ArchiveManifest,
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        info!("Writing archive to {}", path.display());
+        let file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            temp_path,
+            zip: ZipWriter::new(BufWriter::new(file)),
+            manifest: ArchiveManifest {
+                format: ARCHIVE_FORMAT,
+                factorio_version: None,
+                mods: Vec::new(),
+                files: BTreeMap::new(),
+            },
+        })
+    }
+
+    fn add_mod<R: Read>(&mut self, info: &ModInfo, file_name: String, reader: R) -> Result<()> {
+        let mut archived = ArchivedMod::new(info, file_name, String::new());
+        // Mod zips are already compressed
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        self.zip.start_file(archived.archive_path(), options)?;
+        archived.sha1 = self.copy(reader, &archived.file_name)?;
+        self.manifest.mods.push(archived);
+
+        Ok(())
+    }
+
+    fn add_file<R: Read>(&mut self, name: &str, reader: R) -> Result<()> {
+        self.zip.start_file(name, SimpleFileOptions::default())?;
+        let sha1 = self.copy(reader, name)?;
+        self.manifest.files.insert(name.to_owned(), sha1);
+
+        Ok(())
+    }
+
+    /// Copies `reader` into the current entry, returning its checksum.
+    fn copy<R: Read>(&mut self, reader: R, name: &str) -> Result<String> {
+        let tee = Tee {
+            reader,
+            writer: &mut self.zip,
+        };
+        mods::sha1_hex_reader(tee).with_context(|| format!("Failed to add {}", name))
+    }
+
+    /// Removes the partially written archive.
+    fn discard(self) {
+        drop(self.zip);
+        let _ = fs::remove_file(&self.temp_path);
+    }
+
+    /// Writes the manifest and moves the archive into place.
+    fn finish(mut self) -> Result<()> {
+        let options = SimpleFileOptions::default();
+        self.zip.start_file(ARCHIVE_MANIFEST_FILENAME, options)?;
+        self.zip
+            .write_all(serde_json::to_string_pretty(&self.manifest)?.as_bytes())?;
+
+        self.zip
+            .finish()
+            .with_context(|| format!("Failed to write {}", self.temp_path.display()))?
+            .flush()?;
+
+        fs::rename(&self.temp_path, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// A reader that writes everything read from it to a writer.
+struct Tee<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Read for Tee<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.writer.write_all(&buf[..read])?;
+        Ok(read)
+    }
+}
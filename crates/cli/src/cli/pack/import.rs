@@ -0,0 +1,171 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use tracing::{debug, info};
+use zip::ZipArchive;
+
+use crate::{
+    modpack::{ARCHIVE_FORMAT, ARCHIVE_MANIFEST_FILENAME, ArchiveManifest},
+    mods::{self, MOD_LIST_FILENAME, MOD_SETTINGS_FILENAME},
+};
+
+/// Install a modpack archive created by `facti pack export`.
+///
+/// The checksum of every file in the archive is verified before anything
+/// is written to the mods folder. Other versions of the mods in the archive
+/// are removed from the folder, and `mod-list.json` and `mod-settings.dat`
+/// are replaced with the ones in the archive.
+#[derive(Args, Debug)]
+pub struct PackImportArgs {
+    /// Path to the archive.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub archive: PathBuf,
+
+    /// Path to the mods folder to install into, created if it does not exist.
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub mods_dir: PathBuf,
+}
+
+impl PackImportArgs {
+    pub fn run(&self) -> Result<()> {
+        let file = File::open(&self.archive)
+            .with_context(|| format!("Failed to open {}", self.archive.display()))?;
+        let mut archive = ZipArchive::new(BufReader::new(file))
+            .with_context(|| format!("{} is not a zip file", self.archive.display()))?;
+
+        let manifest = read_manifest(&mut archive)?;
+        let files = files(&manifest)?;
+
+        for (entry, sha1) in &files {
+            verify(&mut archive, entry, sha1)?;
+        }
+
+        let dir = &self.mods_dir;
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        for archived in &manifest.mods {
+            mods::remove_other_versions(dir, &archived.name, archived.version)?;
+        }
+
+        for (entry, _) in &files {
+            let file_name = entry.rsplit('/').next().unwrap_or(entry);
+            extract(&mut archive, entry, &dir.join(file_name))?;
+        }
+
+        println!(
+            "Imported {} mods into {}",
+            manifest.mods.len(),
+            dir.display()
+        );
+
+        Ok(())
+    }
+}
+
+fn read_manifest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<ArchiveManifest> {
+    let entry = archive
+        .by_name(ARCHIVE_MANIFEST_FILENAME)
+        .with_context(|| {
+            format!(
+                "Not a modpack archive, {} is missing",
+                ARCHIVE_MANIFEST_FILENAME
+            )
+        })?;
+    let manifest: ArchiveManifest =
+        serde_json::from_reader(entry).context("Failed to parse the archive manifest")?;
+
+    if manifest.format > ARCHIVE_FORMAT {
+        bail!(
+            "The archive uses format {}, but this version of facti only supports up to {}",
+            manifest.format,
+            ARCHIVE_FORMAT
+        );
+    }
+
+    Ok(manifest)
+}
+
+/// Lists the entries of the archive to extract, with their checksums.
+///
+/// Only files with a plain name are accepted, so that extracting them
+/// cannot write outside of the mods folder.
+fn files(manifest: &ArchiveManifest) -> Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+
+    for archived in &manifest.mods {
+        if !is_plain_file_name(&archived.file_name) || !archived.file_name.ends_with(".zip") {
+            bail!(
+                "Invalid file name for {}: {}",
+                archived.name,
+                archived.file_name
+            );
+        }
+        files.push((archived.archive_path(), archived.sha1.clone()));
+    }
+
+    for (name, sha1) in &manifest.files {
+        if name != MOD_LIST_FILENAME && name != MOD_SETTINGS_FILENAME {
+            bail!("Unexpected file in the archive manifest: {}", name);
+        }
+        files.push((name.clone(), sha1.clone()));
+    }
+
+    Ok(files)
+}
+
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\', ':'])
+}
+
+fn verify<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str, sha1: &str) -> Result<()> {
+    debug!("Verifying {}", entry);
+    let file = archive
+        .by_name(entry)
+        .with_context(|| format!("{} is missing from the archive", entry))?;
+    let actual =
+        mods::sha1_hex_reader(file).with_context(|| format!("Failed to read {}", entry))?;
+
+    if !actual.eq_ignore_ascii_case(sha1) {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            entry,
+            sha1,
+            actual
+        );
+    }
+
+    Ok(())
+}
+
+fn extract<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str, path: &Path) -> Result<()> {
+    info!("Writing {}", path.display());
+    let mut file = archive.by_name(entry)?;
+    let mut writer = BufWriter::new(
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+    );
+
+    io::copy(&mut file, &mut writer)
+        .and_then(|_| writer.flush())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_plain_file_name() {
+        assert!(is_plain_file_name("my-mod_1.0.0.zip"));
+        assert!(!is_plain_file_name("../my-mod_1.0.0.zip"));
+        assert!(!is_plain_file_name("sub/my-mod_1.0.0.zip"));
+        assert!(!is_plain_file_name("..\\my-mod_1.0.0.zip"));
+        assert!(!is_plain_file_name(""));
+    }
+}
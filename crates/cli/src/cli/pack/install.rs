@@ -0,0 +1,95 @@
+use std::{env, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::blocking::ApiClient;
+use tracing::debug;
+
+use crate::{
+    modpack::{Lockfile, Manifest},
+    mods::{self, ModList, ModsFolder},
+};
+
+/// Install the mods of a modpack into a mods folder.
+///
+/// The releases in `modpack.lock` are downloaded from the mod portal, which
+/// requires the username and token of a Factorio account (see `--username`
+/// and `--token`). Zips already in the folder with the right checksum are
+/// kept, and other versions of the locked mods are removed.
+///
+/// `mod-list.json` is written to enable exactly the mods of the modpack,
+/// disabling any other mods in the folder.
+#[derive(Args, Debug)]
+pub struct PackInstallArgs {
+    /// Path to the directory with `modpack.toml` and `modpack.lock`,
+    /// defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Path to the mods folder to install into, created if it does not exist.
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub mods_dir: PathBuf,
+}
+
+impl PackInstallArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let manifest = Manifest::load(&path)?;
+        let lockfile = Lockfile::load(&path)?;
+        if !lockfile.is_up_to_date(&manifest) {
+            bail!("The lockfile does not match modpack.toml, update it with `facti pack lock`");
+        }
+
+        let dir = &self.mods_dir;
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let (mut downloaded, mut kept) = (0, 0);
+        for locked in &lockfile.mods {
+            mods::remove_other_versions(dir, &locked.name, locked.version)?;
+
+            if mods::has_checksum(&dir.join(&locked.file_name), &locked.sha1)? {
+                debug!("{} {} is already installed", locked.name, locked.version);
+                kept += 1;
+                continue;
+            }
+
+            let release = locked.release(lockfile.factorio_version);
+            mods::download(client, &locked.name, &release, dir)?;
+            downloaded += 1;
+        }
+
+        let folder = ModsFolder::load(dir)?;
+        mod_list(&lockfile, folder.mods.keys()).save(dir)?;
+
+        println!(
+            "Installed {} mods into {} ({} downloaded, {} already installed)",
+            lockfile.mods.len(),
+            dir.display(),
+            downloaded,
+            kept
+        );
+
+        Ok(())
+    }
+}
+
+/// Builds a mod list enabling `base` and the mods of the modpack, and
+/// disabling the `other` mods.
+pub(super) fn mod_list<'a, I>(lockfile: &Lockfile, other: I) -> ModList
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let names = other
+        .into_iter()
+        .cloned()
+        .chain(lockfile.mods.iter().map(|m| m.name.clone()));
+
+    ModList::new(names, |name| {
+        name == "base"
+            || lockfile.built_in.iter().any(|n| n == name)
+            || lockfile.mods.iter().any(|m| m.name == name)
+    })
+}
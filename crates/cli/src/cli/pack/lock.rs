@@ -0,0 +1,296 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    env,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::Release, error::ApiErrorKind};
+use facti_lib::{
+    FactorioVersion,
+    dependency::{Compatibility, DependencyMode},
+    version::VersionReq,
+};
+use tracing::{debug, info};
+
+use crate::{
+    modpack::{LOCK_FILENAME, LockedMod, Lockfile, Manifest},
+    mods::{BUILT_IN_MODS, built_in_dependencies},
+};
+
+/// Resolve the mods of a modpack to exact releases.
+///
+/// Every mod in `modpack.toml`, and every mod they require, is resolved to
+/// the latest release on the mod portal that targets the Factorio version
+/// of the modpack and satisfies all requirements on it. The result is
+/// written to `modpack.lock` next to the manifest.
+///
+/// Version requirements of optional dependencies are respected for mods
+/// that are part of the modpack. Fails if mods in the modpack are
+/// incompatible with each other.
+#[derive(Args, Debug)]
+pub struct PackLockArgs {
+    /// Path to the directory with `modpack.toml`, defaults to the current
+    /// directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+}
+
+impl PackLockArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let manifest = Manifest::load(&path)?;
+
+        let lockfile = Resolver::new(client, manifest.factorio_version).resolve(&manifest)?;
+        lockfile.save(&path)?;
+
+        for name in &lockfile.built_in {
+            println!("  {} (built-in)", name);
+        }
+        for locked in &lockfile.mods {
+            println!("  {} {}", locked.name, locked.version);
+        }
+        println!(
+            "Locked {} mods to {}",
+            lockfile.built_in.len() + lockfile.mods.len(),
+            path.join(LOCK_FILENAME).display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Who placed a requirement on a mod.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    Manifest,
+    Mod(String),
+}
+
+struct Resolver<'a> {
+    client: &'a ApiClient,
+    factorio_version: FactorioVersion,
+
+    /// Releases of every mod looked up so far that target the Factorio
+    /// version of the modpack.
+    releases: HashMap<String, Vec<Release>>,
+
+    /// Version requirements on each mod, by where they come from.
+    requirements: BTreeMap<String, BTreeMap<Source, VersionReq>>,
+
+    /// The release currently picked for each mod.
+    picked: BTreeMap<String, Release>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(client: &'a ApiClient, factorio_version: FactorioVersion) -> Self {
+        Self {
+            client,
+            factorio_version,
+            releases: HashMap::new(),
+            requirements: BTreeMap::new(),
+            picked: BTreeMap::new(),
+        }
+    }
+
+    /// Resolves the mods of a manifest.
+    ///
+    /// Mods are picked one at a time, re-picking a mod whenever a newly
+    /// picked release adds a requirement on it, until every requirement
+    /// is satisfied.
+    fn resolve(mut self, manifest: &Manifest) -> Result<Lockfile> {
+        let mut queue = VecDeque::new();
+        for (name, req) in &manifest.mods {
+            self.require(name, Source::Manifest, *req);
+            queue.push_back(name.clone());
+        }
+
+        let mut built_in = BTreeSet::new();
+        while let Some(name) = queue.pop_front() {
+            if BUILT_IN_MODS.contains(&name.as_str()) {
+                queue.extend(built_in_dependencies(&name).iter().map(|n| n.to_string()));
+                built_in.insert(name);
+                continue;
+            }
+
+            let release = self.pick(&name)?;
+            if self
+                .picked
+                .get(&name)
+                .is_some_and(|p| p.version == release.version)
+            {
+                continue;
+            }
+
+            debug!("Picked {} {}", name, release.version);
+            let source = Source::Mod(name.clone());
+            for requirements in self.requirements.values_mut() {
+                requirements.remove(&source);
+            }
+
+            for dependency in release.info.dependencies.iter().flatten() {
+                let Compatibility::Compatible(mode, req) = &dependency.compatibility else {
+                    continue;
+                };
+
+                self.require(&dependency.name, source.clone(), *req);
+                match mode {
+                    DependencyMode::Required | DependencyMode::Independent => {
+                        queue.push_back(dependency.name.clone());
+                    }
+                    // Only matters if the mod is part of the pack
+                    DependencyMode::Optional { .. } => {
+                        if self.picked.contains_key(&dependency.name) {
+                            queue.push_back(dependency.name.clone());
+                        }
+                    }
+                }
+            }
+
+            self.picked.insert(name, release);
+        }
+
+        self.prune(manifest);
+        self.check_incompatible(&built_in)?;
+
+        Ok(Lockfile {
+            factorio_version: self.factorio_version,
+            built_in: built_in.into_iter().collect(),
+            mods: self
+                .picked
+                .iter()
+                .map(|(name, release)| LockedMod::new(name, release))
+                .collect(),
+        })
+    }
+
+    fn require(&mut self, name: &str, source: Source, req: VersionReq) {
+        self.requirements
+            .entry(name.to_owned())
+            .or_default()
+            .insert(source, req);
+    }
+
+    /// Picks the latest release of a mod that satisfies all requirements
+    /// on it.
+    fn pick(&mut self, name: &str) -> Result<Release> {
+        let factorio_version = self.factorio_version;
+        let requirements = self.requirements.get(name).cloned().unwrap_or_default();
+        let releases = self.releases(name)?;
+
+        let release = releases
+            .iter()
+            .filter(|r| {
+                requirements.values().all(|req| match req {
+                    VersionReq::Latest => true,
+                    VersionReq::Spec(spec) => spec.matches(r.version),
+                })
+            })
+            .max_by_key(|r| r.version);
+
+        match release {
+            Some(release) => Ok(release.clone()),
+            None => {
+                let reasons = requirements
+                    .iter()
+                    .filter(|(_, req)| **req != VersionReq::Latest)
+                    .map(|(source, req)| match source {
+                        Source::Manifest => format!("\n  modpack requires {}", req),
+                        Source::Mod(from) => format!("\n  {} requires {}", from, req),
+                    })
+                    .collect::<String>();
+
+                bail!(
+                    "No release of {} for Factorio {} satisfies all requirements{}",
+                    name,
+                    factorio_version,
+                    reasons
+                )
+            }
+        }
+    }
+
+    /// Looks up the releases of a mod for the Factorio version of the
+    /// modpack, caching the result.
+    fn releases(&mut self, name: &str) -> Result<&[Release]> {
+        if !self.releases.contains_key(name) {
+            info!("Fetching releases of {}", name);
+            let full = match self.client.info_full(name) {
+                Ok(full) => full,
+                Err(e) if *e.kind() == ApiErrorKind::UnknownMod => {
+                    bail!("{} does not exist on the mod portal", name)
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to get releases of {}", name));
+                }
+            };
+
+            let releases = full
+                .releases
+                .into_iter()
+                .filter(|r| {
+                    r.info.factorio_version.major == self.factorio_version.major
+                        && r.info.factorio_version.minor == self.factorio_version.minor
+                })
+                .collect();
+            self.releases.insert(name.to_owned(), releases);
+        }
+
+        Ok(&self.releases[name])
+    }
+
+    /// Removes mods that are no longer required by anything, as a release
+    /// that required them may have been replaced since they were picked.
+    fn prune(&mut self, manifest: &Manifest) {
+        let mut keep = BTreeSet::new();
+        let mut queue = manifest.mods.keys().cloned().collect::<VecDeque<_>>();
+
+        while let Some(name) = queue.pop_front() {
+            let Some(release) = self.picked.get(&name) else {
+                continue;
+            };
+            if !keep.insert(name) {
+                continue;
+            }
+
+            for dependency in release.info.dependencies.iter().flatten() {
+                if let Compatibility::Compatible(
+                    DependencyMode::Required | DependencyMode::Independent,
+                    _,
+                ) = dependency.compatibility
+                {
+                    queue.push_back(dependency.name.clone());
+                }
+            }
+        }
+
+        self.picked.retain(|name, _| keep.contains(name));
+    }
+
+    fn check_incompatible(&self, built_in: &BTreeSet<String>) -> Result<()> {
+        let conflicts = self
+            .picked
+            .iter()
+            .flat_map(|(name, release)| {
+                release
+                    .info
+                    .dependencies
+                    .iter()
+                    .flatten()
+                    .filter(|d| d.compatibility == Compatibility::Incompatible)
+                    .filter(|d| self.picked.contains_key(&d.name) || built_in.contains(&d.name))
+                    .map(move |d| format!("\n  {} is incompatible with {}", name, d.name))
+            })
+            .collect::<String>();
+
+        if !conflicts.is_empty() {
+            bail!("The modpack contains incompatible mods:{}", conflicts);
+        }
+
+        Ok(())
+    }
+}
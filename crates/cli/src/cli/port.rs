@@ -0,0 +1,344 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::Release, error::ApiErrorKind};
+use facti_lib::{
+    FactorioVersion,
+    dependency::{Compatibility, Dependency, DependencyMode},
+    version::{Op, Version, VersionReq, VersionSpec},
+};
+use serde::Serialize;
+use tracing::info;
+
+use crate::{mods::BUILT_IN_MODS, project::Project};
+
+/// Port a mod to a new version of Factorio.
+///
+/// Updates `factorio_version` in the mod's `info.json`, and checks every
+/// dependency on the mod portal to see if it has a release for the new
+/// version of the game. A readiness report is printed, with suggested
+/// updates to the version requirements of the dependencies.
+#[derive(Args, Debug)]
+pub struct PortArgs {
+    /// The version of Factorio to port the mod to, e.g. `2.0`.
+    #[arg(long, value_name = "FACTORIO_VERSION")]
+    pub to: FactorioVersion,
+
+    /// Path to the mod project, defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Only print the readiness report, without changing `info.json`.
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Output the report as JSON.
+    ///
+    /// Tip: You can use jq to work with JSON!
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+/// Whether a dependency is available for the targeted game version.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Readiness {
+    /// A release for the new game version exists.
+    Ready,
+
+    /// The dependency is part of the game itself.
+    BuiltIn,
+
+    /// No release for the new game version exists.
+    Missing,
+
+    /// The dependency could not be found on the mod portal.
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+struct DependencyReport {
+    name: String,
+    optional: bool,
+    readiness: Readiness,
+
+    /// Latest release of the dependency for the new game version.
+    latest: Option<Version>,
+
+    requirement: String,
+
+    /// Suggested replacement for the version requirement, if it should change.
+    suggested_requirement: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PortReport {
+    name: String,
+    from: String,
+    to: String,
+    dependencies: Vec<DependencyReport>,
+}
+
+impl PortArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let project = Project::load(&path).with_context(|| {
+            format!(
+                "Failed to load Factorio mod project from {}",
+                path.display()
+            )
+        })?;
+
+        if self.to.patch.is_some() {
+            bail!(
+                "Mods can only target a major.minor version of Factorio, without a patch version"
+            );
+        }
+
+        let mut dependencies = Vec::new();
+        for dependency in &project.mod_info.dependencies {
+            if let Some(report) = self.check(client, dependency)? {
+                dependencies.push(report);
+            }
+        }
+
+        let report = PortReport {
+            name: project.mod_info.name.clone(),
+            from: project.mod_info.factorio_version.to_string(),
+            to: self.to.to_string(),
+            dependencies,
+        };
+
+        if !self.dry_run {
+            set_factorio_version(&project, self.to)?;
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_report(&report, self.dry_run);
+        }
+
+        Ok(())
+    }
+
+    /// Checks a single dependency, returning [`None`] for incompatibilities
+    /// as they do not need to be available.
+    fn check(
+        &self,
+        client: &ApiClient,
+        dependency: &Dependency,
+    ) -> Result<Option<DependencyReport>> {
+        let Compatibility::Compatible(mode, req) = &dependency.compatibility else {
+            return Ok(None);
+        };
+
+        let name = dependency.name.clone();
+        let optional = matches!(mode, DependencyMode::Optional { .. });
+
+        if BUILT_IN_MODS.contains(&name.as_str()) {
+            let version = Version::from(self.to);
+            return Ok(Some(DependencyReport {
+                name,
+                optional,
+                readiness: Readiness::BuiltIn,
+                latest: None,
+                requirement: req.to_string(),
+                suggested_requirement: suggest(req, version, version).map(|s| s.to_string()),
+            }));
+        }
+
+        info!("Checking releases of {}", name);
+        let releases = match client.info_full(&name) {
+            Ok(result) => result.releases,
+            Err(e) if *e.kind() == ApiErrorKind::UnknownMod => {
+                return Ok(Some(DependencyReport {
+                    name,
+                    optional,
+                    readiness: Readiness::Unknown,
+                    latest: None,
+                    requirement: req.to_string(),
+                    suggested_requirement: None,
+                }));
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to get releases of {}", name));
+            }
+        };
+
+        let compatible = compatible_versions(&releases, self.to);
+        let report = match (compatible.first(), compatible.last()) {
+            (Some(&earliest), Some(&latest)) => DependencyReport {
+                name,
+                optional,
+                readiness: Readiness::Ready,
+                latest: Some(latest),
+                requirement: req.to_string(),
+                suggested_requirement: suggest(req, earliest, latest).map(|s| s.to_string()),
+            },
+            _ => DependencyReport {
+                name,
+                optional,
+                readiness: Readiness::Missing,
+                latest: None,
+                requirement: req.to_string(),
+                suggested_requirement: None,
+            },
+        };
+
+        Ok(Some(report))
+    }
+}
+
+/// Versions of the releases made for the given game version, oldest first.
+fn compatible_versions(releases: &[Release], to: FactorioVersion) -> Vec<Version> {
+    let mut versions: Vec<_> = releases
+        .iter()
+        .filter(|r| {
+            r.info.factorio_version.major == to.major && r.info.factorio_version.minor == to.minor
+        })
+        .map(|r| r.version)
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// Suggests a new version requirement for a dependency whose releases
+/// for the new game version range from `earliest` to `latest`.
+///
+/// Requirements that allow any version are left alone. Others are raised
+/// to at least the earliest compatible release, as older releases cannot be
+/// used with the new version of the game anyway.
+fn suggest(req: &VersionReq, earliest: Version, latest: Version) -> Option<VersionReq> {
+    let VersionReq::Spec(spec) = req else {
+        return None;
+    };
+
+    let raise = VersionReq::Spec(VersionSpec::new(Op::GreaterEq, earliest));
+    match spec.op {
+        _ if !spec.matches(latest) => Some(raise),
+        Op::Greater | Op::GreaterEq if spec.version < earliest => Some(raise),
+        _ => None,
+    }
+}
+
+/// Updates `factorio_version` in `info.json`, leaving all other fields as-is.
+fn set_factorio_version(project: &Project, to: FactorioVersion) -> Result<()> {
+    project.update_info(|info| {
+        info.insert("factorio_version".to_owned(), to.to_string().into());
+        Ok(())
+    })?;
+
+    info!(
+        "Updated {} to target Factorio {}",
+        project.mod_info_path.display(),
+        to
+    );
+
+    Ok(())
+}
+
+fn print_report(report: &PortReport, dry_run: bool) {
+    let verb = if dry_run { "Would port" } else { "Ported" };
+    println!(
+        "{} {} from Factorio {} to {}",
+        verb, report.name, report.from, report.to
+    );
+
+    if report.dependencies.is_empty() {
+        println!("No dependencies to check");
+        return;
+    }
+
+    println!();
+    for dependency in &report.dependencies {
+        let status = match dependency.readiness {
+            Readiness::Ready => "ready",
+            Readiness::BuiltIn => "built-in",
+            Readiness::Missing => "missing",
+            Readiness::Unknown => "unknown",
+        };
+        let mut line = format!("  {:<9} {}", status, dependency.name);
+
+        if let Some(latest) = dependency.latest {
+            line.push_str(&format!(" {}", latest));
+        }
+
+        match dependency.readiness {
+            Readiness::Missing => {
+                line.push_str(&format!(" (no release for Factorio {})", report.to))
+            }
+            Readiness::Unknown => line.push_str(" (not found on the mod portal)"),
+            _ => {}
+        }
+
+        if let Some(suggested) = &dependency.suggested_requirement {
+            line.push_str(&format!(
+                " (requirement {} -> {})",
+                dependency.requirement, suggested
+            ));
+        }
+
+        if dependency.optional {
+            line.push_str(" [optional]");
+        }
+
+        println!("{}", line);
+    }
+
+    let blocking = report
+        .dependencies
+        .iter()
+        .filter(|d| !d.optional && matches!(d.readiness, Readiness::Missing | Readiness::Unknown))
+        .count();
+
+    println!();
+    if blocking == 0 {
+        println!(
+            "All required dependencies are available for Factorio {}",
+            report.to
+        );
+    } else {
+        println!(
+            "{} required {} not available for Factorio {} yet",
+            blocking,
+            if blocking == 1 {
+                "dependency is"
+            } else {
+                "dependencies are"
+            },
+            report.to
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_suggest() {
+        let earliest = Version::new(0, 15, 0);
+        let latest = Version::new(0, 16, 2);
+
+        assert_eq!(suggest(&VersionReq::Latest, earliest, latest), None);
+        assert_eq!(suggest(&req(">= 0.15.1"), earliest, latest), None);
+        assert_eq!(
+            suggest(&req(">= 0.12.0"), earliest, latest),
+            Some(req(">= 0.15.0"))
+        );
+        assert_eq!(
+            suggest(&req("= 0.12.0"), earliest, latest),
+            Some(req(">= 0.15.0"))
+        );
+        assert_eq!(suggest(&req("< 1.0.0"), earliest, latest), None);
+    }
+}
@@ -1,10 +1,28 @@
+use std::{fmt::Display, path::PathBuf};
+
+use anyhow::Context;
 use clap::{Args, Subcommand, ValueHint};
-use facti_api::blocking::ApiClient;
+use facti_api::{blocking::ApiClient, data::pagination::PageSize};
 use facti_lib::FactorioVersion;
+use serde::Serialize;
+use tracing::info;
+
+use crate::{index::PortalIndex, progress::ProgressBar};
 
-use self::images::ImagesArgs;
+use self::{
+    bulk::BulkArgs, edit::EditArgs, images::ImagesArgs, index::IndexArgs, pull::PullArgs,
+    rdeps::RdepsArgs,
+};
 
+mod bulk;
+mod edit;
 mod images;
+mod index;
+mod pull;
+mod rdeps;
+
+/// Number of results to show from local searches if no page size is given.
+const DEFAULT_LOCAL_RESULTS: usize = 25;
 
 /// Interact with the Factorio mod portal.
 ///
@@ -35,9 +53,23 @@ pub enum PortalCommands {
     )]
     Show(PortalShowArgs),
 
+    /// Upload a new release of an existing mod.
+    Upload(PortalUploadArgs),
+
     /// Interact with mod images.
     #[command(alias = "img", alias = "image")]
     Images(ImagesArgs),
+
+    Index(IndexArgs),
+
+    Pull(PullArgs),
+
+    Edit(Box<EditArgs>),
+
+    Bulk(BulkArgs),
+
+    #[command(alias = "dependents")]
+    Rdeps(RdepsArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -52,6 +84,37 @@ pub struct PortalSearchArgs {
     #[arg(short, long, default_value_t = 1)]
     pub page: u32,
 
+    /// Fetch and show results from every page instead of a single one.
+    ///
+    /// Unless a page size is given, pages will be fetched with the maximum
+    /// possible size to minimize the number of requests made.
+    #[arg(short, long, conflicts_with = "page")]
+    pub all: bool,
+
+    /// Search the local portal index for mods matching the given text.
+    ///
+    /// Matches against names, titles, summaries, owners, categories and tags,
+    /// allowing for minor typos. Results are ordered by relevance.
+    ///
+    /// The index must first be created with `facti portal index update`.
+    /// Of the other options, only `--size` applies to local searches, to
+    /// limit the number of results.
+    #[arg(
+        short = 'Q',
+        long,
+        value_hint = ValueHint::Other,
+        conflicts_with_all = [
+            "all",
+            "page",
+            "sort_mode",
+            "sort_order",
+            "deprecated",
+            "factorio_version",
+            "names",
+        ]
+    )]
+    pub query: Option<String>,
+
     /// The amount of results to show, specify 'max' for maximum possible.
     #[arg(short = 'n', long = "size", value_name = "SIZE")]
     pub page_size: Option<facti_api::data::pagination::PageSize>,
@@ -87,6 +150,20 @@ pub struct PortalSearchArgs {
     pub names: Vec<String>,
 }
 
+#[derive(clap::Args, Debug)]
+pub struct PortalUploadArgs {
+    /// Name of the mod to upload a release for.
+    ///
+    /// If not given, it is taken from the file name, which is expected to be
+    /// in the `<name>_<version>.zip` format used by `facti dist`.
+    #[arg(short, long, value_hint = ValueHint::Other)]
+    pub name: Option<String>,
+
+    /// Path to the zip file of the release.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub path: PathBuf,
+}
+
 #[derive(clap::Args, Debug)]
 pub struct PortalShowArgs {
     /// If given, will show more details about the mod.
@@ -97,11 +174,19 @@ pub struct PortalShowArgs {
 }
 
 impl PortalArgs {
-    pub fn run(&self, client: &ApiClient) -> anyhow::Result<()> {
+    /// Runs the portal command, showing progress bars for uploads
+    /// if `progress` is set.
+    pub fn run(&self, client: &ApiClient, progress: bool) -> anyhow::Result<()> {
         match &self.command {
             PortalCommands::Search(args) => args.run(client, self.json),
             PortalCommands::Show(args) => args.run(client, self.json),
-            PortalCommands::Images(args) => args.run(client),
+            PortalCommands::Upload(args) => args.run(client, progress),
+            PortalCommands::Images(args) => args.run(client, progress),
+            PortalCommands::Index(args) => args.run(client, self.json),
+            PortalCommands::Pull(args) => args.run(client),
+            PortalCommands::Edit(args) => args.run(client),
+            PortalCommands::Bulk(args) => args.run(client, self.json),
+            PortalCommands::Rdeps(args) => args.run(self.json),
         }
     }
 }
@@ -121,6 +206,14 @@ impl PortalSearchArgs {
             },
         };
 
+        if let Some(text) = &self.query {
+            return self.run_local(text, json);
+        }
+
+        if self.all {
+            return self.run_all(client, query, json);
+        }
+
         let response = client.search(&query)?;
 
         if json {
@@ -133,22 +226,120 @@ impl PortalSearchArgs {
 
         Ok(())
     }
-}
 
-impl PortalShowArgs {
-    pub fn run(&self, client: &ApiClient, json: bool) -> anyhow::Result<()> {
-        let response = if self.full {
-            client.info_full(&self.name)?
-        } else {
-            client.info_short(&self.name)?
+    fn run_local(&self, text: &str, json: bool) -> anyhow::Result<()> {
+        let index = PortalIndex::load()?
+            .context("No portal index exists, create one with `facti portal index update`")?;
+
+        let limit = match self.page_size {
+            Some(PageSize::Max) => usize::MAX,
+            Some(PageSize::Custom(size)) => size as usize,
+            None => DEFAULT_LOCAL_RESULTS,
         };
 
+        let results = index
+            .search(text)
+            .into_iter()
+            .map(|hit| hit.result)
+            .filter(|r| self.names.is_empty() || self.names.contains(&r.summary.name))
+            .filter(|r| match self.factorio_version {
+                Some(version) => r
+                    .summary
+                    .latest()
+                    .is_some_and(|l| l.info.factorio_version == version),
+                None => true,
+            })
+            .take(limit)
+            .collect::<Vec<_>>();
+
         if json {
-            println!("{}", serde_json::to_string_pretty(&response)?);
+            println!("{}", serde_json::to_string_pretty(&results)?);
         } else {
-            println!("{}", response);
+            for item in results {
+                println!("{}", item.summary);
+            }
         }
 
         Ok(())
     }
+
+    fn run_all(
+        &self,
+        client: &ApiClient,
+        mut query: facti_api::data::portal::SearchQuery,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        query.page_size = query.page_size.or(Some(PageSize::Max));
+        let results = client.search_all(query);
+
+        if json {
+            let results = results.collect::<Result<Vec<_>, _>>()?;
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        } else {
+            for item in results {
+                println!("{}", item?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PortalUploadArgs {
+    pub fn run(&self, client: &ApiClient, progress: bool) -> anyhow::Result<()> {
+        let name = match &self.name {
+            Some(name) => name.to_owned(),
+            None => mod_name_from_path(&self.path).with_context(|| {
+                format!(
+                    "Could not determine mod name from {}, specify it with --name",
+                    self.path.display()
+                )
+            })?,
+        };
+
+        info!(
+            "Uploading {} as a new release of {}",
+            self.path.display(),
+            name
+        );
+        let bar = ProgressBar::new(format!("Uploading {}", name), progress);
+        let result = client.upload_with_progress(&name, &self.path, bar.callback());
+        bar.finish();
+        result?;
+
+        println!("Uploaded new release of {}", name);
+
+        Ok(())
+    }
+}
+
+/// Gets the mod name from a file name like `my-mod_1.2.3.zip`.
+fn mod_name_from_path(path: &std::path::Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let (name, version) = stem.rsplit_once('_')?;
+
+    version
+        .parse::<facti_lib::version::Version>()
+        .ok()
+        .map(|_| name.to_owned())
+}
+
+impl PortalShowArgs {
+    pub fn run(&self, client: &ApiClient, json: bool) -> anyhow::Result<()> {
+        if self.full {
+            print(&client.info_full(&self.name)?, json)
+        } else {
+            print(&client.info_short(&self.name)?, json)
+        }
+    }
+}
+
+fn print<T: Display + Serialize>(value: &T, json: bool) -> anyhow::Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", value);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,364 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{
+    blocking::ApiClient,
+    data::{
+        detail::{License, ModDetailsRequest, Tag},
+        pagination::PageSize,
+        portal::{ModFull, SearchQuery},
+    },
+};
+use serde::Serialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::index::PortalIndex;
+
+use super::edit::{Change, changes};
+
+/// Placeholder in URL templates that is replaced with the name of each mod.
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Apply the same edit to every mod owned by an account.
+///
+/// The mods are looked up through the portal's mod list (or the local index
+/// with `--index`), then the current details of each mod are fetched and
+/// compared against the edit, so that mods that are already up to date are
+/// left alone.
+///
+/// Use `--dry-run` to see the planned changes without submitting them.
+#[derive(Args, Debug)]
+pub struct BulkArgs {
+    /// Name of the account whose mods to edit.
+    #[arg(long, value_hint = ValueHint::Username)]
+    pub owner: String,
+
+    /// Look up the mods of the owner in the local portal index instead of
+    /// fetching the mod list from the portal.
+    ///
+    /// The index must first be created with `facti portal index update`.
+    #[arg(long)]
+    pub index: bool,
+
+    /// Mods to leave out, can be repeated.
+    #[arg(long, value_name = "NAME", value_hint = ValueHint::Other)]
+    pub exclude: Vec<String>,
+
+    /// License to give every mod, e.g. `default_mit` or `custom_<ID>`.
+    #[arg(long)]
+    pub license: Option<License>,
+
+    /// Template for the source URL of every mod.
+    ///
+    /// `{name}` is replaced with the name of the mod,
+    /// e.g. `https://github.com/someone/{name}`.
+    #[arg(long, value_name = "TEMPLATE", value_hint = ValueHint::Url)]
+    pub source_url: Option<String>,
+
+    /// Whether every mod should be deprecated.
+    #[arg(long, value_name = "BOOL")]
+    pub deprecated: Option<bool>,
+
+    /// Tag to add to every mod, can be repeated.
+    #[arg(long, value_name = "TAG")]
+    pub add_tag: Vec<Tag>,
+
+    /// Tag to remove from every mod, can be repeated.
+    #[arg(long, value_name = "TAG")]
+    pub remove_tag: Vec<Tag>,
+
+    /// Maximum number of mods to process at the same time.
+    #[arg(long, default_value = "4")]
+    pub jobs: NonZeroUsize,
+
+    /// Only show the planned changes, without submitting them.
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+/// What happened to a single mod.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+enum Outcome {
+    /// The mod already matches the edit.
+    Unchanged,
+
+    /// The mod would be changed, but this is a dry run.
+    Planned {
+        changes: Vec<Change>,
+    },
+
+    Updated {
+        changes: Vec<Change>,
+    },
+
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ModOutcome {
+    name: String,
+
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+impl BulkArgs {
+    pub fn run(&self, client: &ApiClient, json: bool) -> Result<()> {
+        if self.license.is_none()
+            && self.source_url.is_none()
+            && self.deprecated.is_none()
+            && self.add_tag.is_empty()
+            && self.remove_tag.is_empty()
+        {
+            bail!("No edits given, see `facti portal bulk --help` for the available edits");
+        }
+
+        if let Some(template) = &self.source_url {
+            render(template, "mod").context("Invalid source URL template")?;
+        }
+
+        let names = self.mods(client)?;
+        if names.is_empty() {
+            bail!("No mods owned by {} were found", self.owner);
+        }
+
+        info!(
+            "Processing {} mods with {} jobs",
+            names.len(),
+            self.jobs.get()
+        );
+        let outcomes = self.process(client, names);
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&outcomes)?);
+        } else {
+            print_outcomes(&outcomes);
+        }
+
+        let failed = outcomes
+            .iter()
+            .filter(|o| matches!(o.outcome, Outcome::Failed { .. }))
+            .count();
+        if failed > 0 {
+            bail!("Failed to edit {} of {} mods", failed, outcomes.len());
+        }
+
+        Ok(())
+    }
+
+    /// Finds the names of the mods to edit, in alphabetical order.
+    fn mods(&self, client: &ApiClient) -> Result<Vec<String>> {
+        let mut names = if self.index {
+            let index = PortalIndex::load()?
+                .context("No portal index exists, create one with `facti portal index update`")?;
+
+            index
+                .mods
+                .into_values()
+                .filter(|m| m.summary.owner == self.owner)
+                .map(|m| m.summary.name)
+                .collect::<Vec<_>>()
+        } else {
+            let query = SearchQuery {
+                hide_deprecated: false,
+                page_size: Some(PageSize::Max),
+                ..Default::default()
+            };
+
+            info!("Fetching mods owned by {} from the portal", self.owner);
+            let mut names = Vec::new();
+            for result in client.search_all(query) {
+                let result = result.context("Failed to fetch mod list from the portal")?;
+                if result.owner == self.owner {
+                    names.push(result.name);
+                }
+            }
+            names
+        };
+
+        names.retain(|name| !self.exclude.contains(name));
+        names.sort();
+        debug!("Found mods {:?}", names);
+
+        Ok(names)
+    }
+
+    /// Processes every mod, with at most `jobs` mods at a time, returning
+    /// the outcomes in the same order as `names`.
+    fn process(&self, client: &ApiClient, names: Vec<String>) -> Vec<ModOutcome> {
+        let queue = Mutex::new(names.into_iter().enumerate());
+        let outcomes = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..self.jobs.get() {
+                scope.spawn(|| {
+                    loop {
+                        let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+                        let Some((i, name)) = next else {
+                            break;
+                        };
+
+                        let outcome = match self.process_mod(client, &name) {
+                            Ok(outcome) => outcome,
+                            Err(e) => Outcome::Failed {
+                                error: format!("{:#}", e),
+                            },
+                        };
+
+                        outcomes
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .push((i, ModOutcome { name, outcome }));
+                    }
+                });
+            }
+        });
+
+        let mut outcomes = outcomes
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        outcomes.sort_by_key(|(i, _)| *i);
+        outcomes.into_iter().map(|(_, o)| o).collect()
+    }
+
+    fn process_mod(&self, client: &ApiClient, name: &str) -> Result<Outcome> {
+        let full = client
+            .info_full(name)
+            .context("Failed to get current details")?;
+        let request = self.request(&full)?;
+
+        let changes = changes(&request, &full);
+        if changes.is_empty() {
+            return Ok(Outcome::Unchanged);
+        }
+
+        if self.dry_run {
+            return Ok(Outcome::Planned { changes });
+        }
+
+        info!("Editing details of {}", name);
+        client
+            .edit_details(request)
+            .context("Failed to edit details")?;
+
+        Ok(Outcome::Updated { changes })
+    }
+
+    /// Builds the edit for a single mod from its current details.
+    fn request(&self, full: &ModFull) -> Result<ModDetailsRequest> {
+        let mut builder = ModDetailsRequest::builder(&full.name);
+
+        if let Some(license) = &self.license {
+            builder.license(license.clone());
+        }
+
+        if let Some(template) = &self.source_url {
+            builder.source_url(render(template, &full.name)?);
+        }
+
+        if let Some(deprecated) = self.deprecated {
+            builder.deprecated(deprecated);
+        }
+
+        let mut request = builder.build();
+
+        if !self.add_tag.is_empty() || !self.remove_tag.is_empty() {
+            request.tags = Some(edit_tags(&full.tags, &self.add_tag, &self.remove_tag));
+        }
+
+        Ok(request)
+    }
+}
+
+/// Fills in a URL template for a mod.
+fn render(template: &str, name: &str) -> Result<Url> {
+    let url = template.replace(NAME_PLACEHOLDER, name);
+
+    Url::parse(&url).with_context(|| format!("{} is not a valid URL", url))
+}
+
+/// Adds and removes tags, keeping the order of the existing ones.
+fn edit_tags(current: &[Tag], add: &[Tag], remove: &[Tag]) -> Vec<Tag> {
+    let mut tags = current
+        .iter()
+        .filter(|t| !remove.contains(t))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for tag in add {
+        if !tags.contains(tag) && !remove.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    tags
+}
+
+fn print_outcomes(outcomes: &[ModOutcome]) {
+    let (mut unchanged, mut changed, mut failed) = (0, 0, 0);
+
+    for ModOutcome { name, outcome } in outcomes {
+        match outcome {
+            Outcome::Unchanged => {
+                unchanged += 1;
+                println!("  {}: unchanged", name);
+            }
+            Outcome::Planned { changes } | Outcome::Updated { changes } => {
+                changed += 1;
+                let verb = match outcome {
+                    Outcome::Planned { .. } => "would update",
+                    _ => "updated",
+                };
+                println!("  {}: {}", name, verb);
+                for change in changes {
+                    println!("      {}", change);
+                }
+            }
+            Outcome::Failed { error } => {
+                failed += 1;
+                println!("  {}: failed: {}", name, error);
+            }
+        }
+    }
+
+    println!(
+        "{} changed, {} unchanged, {} failed",
+        changed, unchanged, failed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_tags() {
+        let current = [Tag::Trains, Tag::Logistics];
+
+        assert_eq!(
+            edit_tags(&current, &[Tag::Combat, Tag::Trains], &[Tag::Logistics]),
+            vec![Tag::Trains, Tag::Combat]
+        );
+        assert_eq!(edit_tags(&current, &[], &[]), current.to_vec());
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("https://github.com/someone/{name}", "my-mod")
+                .unwrap()
+                .as_str(),
+            "https://github.com/someone/my-mod"
+        );
+        assert!(render("not a url/{name}", "my-mod").is_err());
+    }
+}
@@ -0,0 +1,436 @@
+use std::{fmt::Display, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{
+    blocking::ApiClient,
+    data::{
+        detail::{Category, License, ModDetailsRequest, Tag},
+        portal::ModFull,
+    },
+};
+use serde::Serialize;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::known_tags::KnownTags;
+
+/// Edit the details of a mod on the mod portal.
+///
+/// Changes can be given with a flag per field, or read from a TOML, JSON or
+/// YAML file with `--from`, using the field names of the mod details API
+/// (`title`, `summary`, `description`, `category`, `tags`, `license`,
+/// `homepage`, `deprecated`, `source_url` and `faq`).
+/// Flags take precedence over values from the file.
+///
+/// The changes are shown as a diff against the current details of the mod
+/// before they are submitted.
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// Name of the mod to edit.
+    #[arg(value_hint = ValueHint::Other)]
+    pub name: String,
+
+    /// Read the changes from a file.
+    ///
+    /// The format is determined by the file extension,
+    /// `.toml`, `.json` and (if enabled) `.yaml` or `.yml` are supported.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub from: Option<PathBuf>,
+
+    /// Display name of the mod.
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Short description of the mod.
+    #[arg(long)]
+    pub summary: Option<String>,
+
+    /// Long description of the mod in markdown format.
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// Category of the mod, e.g. `content` or `tweaks`.
+    #[arg(long)]
+    pub category: Option<Category>,
+
+    /// Tag to give the mod, can be repeated.
+    ///
+    /// The given tags replace all existing tags of the mod.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<Tag>,
+
+    /// Remove all tags from the mod.
+    #[arg(long, conflicts_with = "tags")]
+    pub no_tags: bool,
+
+    /// License of the mod, e.g. `default_mit` or `custom_<ID>`.
+    #[arg(long)]
+    pub license: Option<License>,
+
+    /// Homepage of the mod.
+    #[arg(long, value_hint = ValueHint::Url)]
+    pub homepage: Option<Url>,
+
+    /// Whether the mod is deprecated.
+    ///
+    /// Deprecated mods do not show up in public listings.
+    #[arg(long, value_name = "BOOL")]
+    pub deprecated: Option<bool>,
+
+    /// URL of the mod's source code repository.
+    #[arg(long, value_hint = ValueHint::Url)]
+    pub source_url: Option<Url>,
+
+    /// FAQ for the mod in markdown format.
+    #[arg(long)]
+    pub faq: Option<String>,
+
+    /// Only show the changes, without submitting them.
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+/// A change to a single field of a mod's details.
+#[derive(Debug, Serialize)]
+pub(super) struct Change {
+    pub field: &'static str,
+
+    /// Current value, if known.
+    ///
+    /// Not every field can be read back through the API.
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl EditArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let request = self.request()?;
+
+        info!("Fetching current details of {}", self.name);
+        let full = client
+            .info_full(&self.name)
+            .with_context(|| format!("Failed to get details of {}", self.name))?;
+
+        let changes = changes(&request, &full);
+        if changes.is_empty() {
+            println!("No changes to the details of {}", self.name);
+            return Ok(());
+        }
+
+        for change in &changes {
+            print_change(change);
+        }
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        client
+            .edit_details(request)
+            .with_context(|| format!("Failed to edit details of {}", self.name))?;
+        println!("Updated details of {}", self.name);
+
+        Ok(())
+    }
+
+    /// Builds the request from the file given with `--from` (if any),
+    /// overridden by the values of the flags.
+    fn request(&self) -> Result<ModDetailsRequest> {
+        let mut request = match &self.from {
+            Some(path) => read_request(path)?,
+            None => ModDetailsRequest::builder(&self.name).build(),
+        };
+
+        if request.name.is_empty() {
+            request.name = self.name.clone();
+        } else if request.name != self.name {
+            bail!(
+                "Changes are for mod {}, but {} was given",
+                request.name,
+                self.name
+            );
+        }
+
+        let flags = [
+            (&mut request.title, &self.title),
+            (&mut request.summary, &self.summary),
+            (&mut request.description, &self.description),
+            (&mut request.faq, &self.faq),
+        ];
+        for (field, flag) in flags {
+            if flag.is_some() {
+                field.clone_from(flag);
+            }
+        }
+
+        if self.category.is_some() {
+            request.category.clone_from(&self.category);
+        }
+
+        if !self.tags.is_empty() {
+            request.tags = Some(self.tags.clone());
+        } else if self.no_tags {
+            request.tags = Some(Vec::new());
+        }
+
+        if self.license.is_some() {
+            request.license.clone_from(&self.license);
+        }
+
+        if self.homepage.is_some() {
+            request.homepage.clone_from(&self.homepage);
+        }
+
+        if self.deprecated.is_some() {
+            request.deprecated = self.deprecated;
+        }
+
+        if self.source_url.is_some() {
+            request.source_url.clone_from(&self.source_url);
+        }
+
+        warn_unknown(&request)?;
+
+        Ok(request)
+    }
+}
+
+fn read_request(path: &PathBuf) -> Result<ModDetailsRequest> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+
+    match extension.as_deref() {
+        Some("toml") => toml::from_str(&content).context("Failed to parse TOML"),
+        Some("json") => serde_json::from_str(&content).context("Failed to parse JSON"),
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).context("Failed to parse YAML"),
+        _ => bail!(
+            "Unsupported file format for {}, expected a .toml, .json or .yaml file",
+            path.display()
+        ),
+    }
+}
+
+/// Warns about categories and tags neither this version of facti nor the
+/// local portal index knows, which are likely typos and will be rejected
+/// by the portal.
+fn warn_unknown(request: &ModDetailsRequest) -> Result<()> {
+    let known_tags = KnownTags::load()?;
+
+    if let Some(category) = request
+        .category
+        .as_ref()
+        .filter(|c| !known_tags.is_known_category(c))
+    {
+        warn!("Unknown category {}", category);
+    }
+
+    for tag in request
+        .tags
+        .iter()
+        .flatten()
+        .filter(|t| !known_tags.is_known_tag(t))
+    {
+        warn!("Unknown tag {}", tag);
+    }
+
+    Ok(())
+}
+
+/// Collects the fields the request changes, compared to the current details.
+///
+/// Fields that already have the requested value are left out.
+pub(super) fn changes(request: &ModDetailsRequest, full: &ModFull) -> Vec<Change> {
+    let join = |tags: &[Tag]| {
+        tags.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let fields = [
+        ("title", Some(full.title.clone()), request.title.clone()),
+        (
+            "summary",
+            Some(full.summary.clone()),
+            request.summary.clone(),
+        ),
+        (
+            "description",
+            Some(full.description.clone()),
+            request.description.clone(),
+        ),
+        (
+            "category",
+            Some(full.category.to_string()),
+            request.category.as_ref().map(ToString::to_string),
+        ),
+        (
+            "tags",
+            Some(join(&full.tags)),
+            request.tags.as_deref().map(join),
+        ),
+        (
+            "license",
+            Some(
+                full.license
+                    .as_ref()
+                    .map(|l| l.id.to_string())
+                    .unwrap_or_default(),
+            ),
+            request.license.as_ref().map(ToString::to_string),
+        ),
+        (
+            "homepage",
+            // Compare parsed URLs, as parsing normalises them
+            Some(
+                full.homepage
+                    .as_deref()
+                    .map(|h| Url::parse(h).map_or_else(|_| h.to_owned(), String::from))
+                    .unwrap_or_default(),
+            ),
+            request.homepage.as_ref().map(ToString::to_string),
+        ),
+        (
+            "deprecated",
+            Some(full.deprecated.to_string()),
+            request.deprecated.map(|d| d.to_string()),
+        ),
+        (
+            "source_url",
+            Some(
+                full.source_url
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            ),
+            request.source_url.as_ref().map(ToString::to_string),
+        ),
+        ("faq", None, request.faq.clone()),
+    ];
+
+    fields
+        .into_iter()
+        .filter_map(|(field, old, new)| {
+            let new = new?;
+            (old.as_ref() != Some(&new)).then_some(Change { field, old, new })
+        })
+        .collect()
+}
+
+/// Shows a change on a single line, with multi-line values shortened.
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let brief = |value: &str| match value.lines().count() {
+            0 => "(empty)".to_owned(),
+            1 => value.to_owned(),
+            n => format!("({} lines)", n),
+        };
+
+        let old = self.old.as_deref().map(brief);
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            old.as_deref().unwrap_or("?"),
+            brief(&self.new)
+        )
+    }
+}
+
+fn print_change(change: &Change) {
+    println!("{}:", change.field);
+
+    match &change.old {
+        Some(old) => {
+            for (tag, line) in diff_lines(old, &change.new) {
+                println!("  {} {}", tag, line);
+            }
+        }
+        None => {
+            println!("  ? (current value not available through the API)");
+            for line in change.new.lines() {
+                println!("  + {}", line);
+            }
+        }
+    }
+}
+
+/// Diffs two texts line by line, returning every line tagged with
+/// `' '` if it is in both texts, `'-'` if it was removed or `'+'` if it
+/// was added.
+///
+/// Uses the longest common subsequence of lines, which is plenty fast for
+/// the size of mod descriptions.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lengths[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            diff.push(('-', old[i]));
+            i += 1;
+        } else {
+            diff.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| ('-', *line)));
+    diff.extend(new[j..].iter().map(|line| ('+', *line)));
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines(
+            "# Title\n\nOld line\nSame",
+            "# Title\n\nNew line\nSame\nAdded",
+        );
+
+        assert_eq!(
+            diff,
+            vec![
+                (' ', "# Title"),
+                (' ', ""),
+                ('-', "Old line"),
+                ('+', "New line"),
+                (' ', "Same"),
+                ('+', "Added"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_empty() {
+        assert_eq!(diff_lines("", "New"), vec![('+', "New")]);
+        assert_eq!(diff_lines("Old", ""), vec![('-', "Old")]);
+    }
+}
@@ -1,7 +1,18 @@
-use anyhow::Result;
-use clap::{Args, Subcommand};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueHint};
 use facti_api::blocking::ApiClient;
-use tracing::info;
+use tracing::{debug, info};
+
+use crate::{
+    image,
+    progress::{ProgressBar, format_bytes},
+};
 
 #[derive(Args, Debug)]
 pub struct ImagesArgs {
@@ -12,6 +23,17 @@ pub struct ImagesArgs {
 #[derive(Subcommand, Debug)]
 pub enum ImagesCommands {
     List(ListImagesArgs),
+
+    /// Upload images to a mod.
+    ///
+    /// The images are added to the mod's gallery,
+    /// after any existing images.
+    ///
+    /// Before uploading, images are converted to a format the portal accepts
+    /// (BMP images become PNG), downsized to fit within 2048x2048 pixels,
+    /// and stripped of metadata such as EXIF data.
+    #[command(visible_alias = "add")]
+    Upload(UploadImagesArgs),
 }
 
 #[derive(Args, Debug)]
@@ -19,10 +41,26 @@ pub struct ListImagesArgs {
     pub name: String,
 }
 
+#[derive(Args, Debug)]
+pub struct UploadImagesArgs {
+    /// Name of the mod to add the images to.
+    pub name: String,
+
+    /// Paths to the images to upload.
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub paths: Vec<PathBuf>,
+
+    /// Upload the images exactly as they are, without converting, resizing
+    /// or stripping metadata from them.
+    #[arg(long)]
+    pub no_process: bool,
+}
+
 impl ImagesArgs {
-    pub fn run(&self, client: &ApiClient) -> Result<()> {
+    pub fn run(&self, client: &ApiClient, progress: bool) -> Result<()> {
         match &self.command {
             ImagesCommands::List(args) => args.run(client),
+            ImagesCommands::Upload(args) => args.run(client, progress),
         }
     }
 }
@@ -39,3 +77,98 @@ impl ListImagesArgs {
         Ok(())
     }
 }
+
+impl UploadImagesArgs {
+    pub fn run(&self, client: &ApiClient, progress: bool) -> Result<()> {
+        for path in &self.paths {
+            let processed = if self.no_process {
+                None
+            } else {
+                Some(process(path, progress)?)
+            };
+            let upload_path = processed.as_ref().map_or(path.as_path(), |p| p.path());
+
+            info!("Uploading image {} to {}", path.display(), self.name);
+            let file_name = path.file_name().unwrap_or(path.as_os_str());
+            let bar = ProgressBar::new(
+                format!("Uploading {}", file_name.to_string_lossy()),
+                progress,
+            );
+            let result = client.upload_image_with_progress(&self.name, upload_path, bar.callback());
+            bar.finish();
+            drop(processed);
+
+            let image =
+                result.with_context(|| format!("Failed to upload image {}", path.display()))?;
+            println!("{}: {}", image.id, image.url);
+        }
+
+        Ok(())
+    }
+}
+
+/// A processed image written to a temporary file for uploading,
+/// which is removed again when dropped.
+struct ProcessedFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl ProcessedFile {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProcessedFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            debug!("Failed to remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+/// Processes an image for uploading, reporting what changed unless `verbose` is false.
+fn process(path: &Path, verbose: bool) -> Result<ProcessedFile> {
+    let data =
+        fs::read(path).with_context(|| format!("Failed to read image {}", path.display()))?;
+    let processed = image::process(&data)
+        .with_context(|| format!("Failed to process image {}", path.display()))?;
+
+    if verbose && processed.is_modified() {
+        eprintln!(
+            "{}: {} {} ({}) -> {} {} ({})",
+            path.display(),
+            processed.original_format,
+            processed.original_dimensions,
+            format_bytes(processed.original_size as u64),
+            processed.format,
+            processed.dimensions,
+            format_bytes(processed.data.len() as u64)
+        );
+    }
+
+    // Keep the original file name, as the portal may show it,
+    // but make the extension match the format after conversion
+    let mut file_name = PathBuf::from(path.file_name().unwrap_or("image".as_ref()));
+    if processed.format != processed.original_format {
+        file_name.set_extension(processed.format.extension());
+    }
+
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "facti-image-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create temporary directory {}", dir.display()))?;
+    let file = ProcessedFile {
+        path: dir.join(file_name),
+        dir,
+    };
+    fs::write(&file.path, &processed.data)
+        .with_context(|| format!("Failed to write {}", file.path.display()))?;
+
+    Ok(file)
+}
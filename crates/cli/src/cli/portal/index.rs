@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Result, bail};
+use clap::{Args, Subcommand};
+use facti_api::{
+    blocking::ApiClient,
+    data::detail::{Category, Tag},
+};
+use serde::Serialize;
+use tracing::info;
+
+use crate::{index::PortalIndex, known_tags::KnownTags};
+
+/// Manage the local index of the mod portal.
+///
+/// The index is used for offline text search with `facti portal search --query`.
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: IndexCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommands {
+    /// Create or update the local index.
+    ///
+    /// The first update downloads the complete mod list from the portal,
+    /// subsequent updates only fetch mods that have been updated since.
+    /// Every week, the complete mod list is downloaded again to remove mods
+    /// that have since been deleted.
+    ///
+    /// Categories and tags used by the indexed mods that this version of
+    /// facti does not know about are remembered, see `used-tags`.
+    Update(IndexUpdateArgs),
+
+    /// Show information about the local index.
+    #[command(alias = "info")]
+    Status,
+
+    /// List the categories and tags used by the mods in the local index.
+    ///
+    /// This does not contact the portal: the portal has no list of its
+    /// categories and tags, so they are counted from the mods in the index,
+    /// and are only as current as the index is. Tags are only known for mods
+    /// whose details were fetched with `facti portal index update --details`.
+    ///
+    /// Categories and tags that were added to the portal after this version
+    /// of facti was released are marked as new. Updating the index refreshes
+    /// the list of these, so that `facti portal edit` accepts them without
+    /// warning.
+    #[command(visible_alias = "tags", alias = "categories")]
+    UsedTags,
+}
+
+#[derive(Args, Debug)]
+pub struct IndexUpdateArgs {
+    /// Download the complete mod list even if an index already exists.
+    ///
+    /// This also removes mods that have since been deleted or deprecated.
+    #[arg(short, long)]
+    pub full: bool,
+
+    /// Also fetch full details (such as tags) for every added or updated mod.
+    ///
+    /// The portal's mod list does not include tags, so this is required for
+    /// tags to be searchable. Note that this makes one request per mod,
+    /// which can take a long time for a full update.
+    #[arg(short, long)]
+    pub details: bool,
+}
+
+impl IndexArgs {
+    pub fn run(&self, client: &ApiClient, json: bool) -> Result<()> {
+        match &self.command {
+            IndexCommands::Update(args) => args.run(client),
+            IndexCommands::Status => status(),
+            IndexCommands::UsedTags => used_tags(json),
+        }
+    }
+}
+
+impl IndexUpdateArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let mut index = PortalIndex::load()?.unwrap_or_default();
+
+        let summary = if self.full {
+            index.update_full(client)?
+        } else {
+            index.update_incremental(client)?
+        };
+
+        if self.details {
+            // Updated mods that had details already got them again
+            let names = summary
+                .changed
+                .iter()
+                .filter(|name| index.mods.get(*name).is_some_and(|m| m.tags.is_none()))
+                .cloned()
+                .collect::<Vec<_>>();
+            info!("Fetching details for {} mods", names.len());
+            index.fetch_details(client, names.iter().map(|n| n.as_str()))?;
+        }
+
+        index.save()?;
+
+        let mut known_tags = KnownTags::load()?;
+        if known_tags.learn(&index) {
+            known_tags.save()?;
+        }
+
+        println!(
+            "Index updated: {} added, {} updated, {} removed, {} mods in total",
+            summary.added,
+            summary.updated,
+            summary.removed,
+            index.mods.len()
+        );
+
+        Ok(())
+    }
+}
+
+fn status() -> Result<()> {
+    let Some(index) = PortalIndex::load()? else {
+        bail!("No portal index exists, create one with `facti portal index update`");
+    };
+
+    println!("Path: {}", PortalIndex::default_path()?.display());
+    println!("Mods: {}", index.mods.len());
+
+    if let Some(updated_at) = index.updated_at {
+        println!("Last updated: {}", updated_at);
+    }
+    if let Some(full_updated_at) = index.full_updated_at {
+        println!("Last full update: {}", full_updated_at);
+    }
+
+    Ok(())
+}
+
+/// How often a category or tag is used in the index.
+#[derive(Debug, Serialize)]
+struct Usage {
+    name: String,
+    mods: usize,
+
+    /// Whether this version of facti was released with it.
+    known: bool,
+
+    /// Whether it was learned from the index, as it is not known.
+    learned: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Taxonomy {
+    categories: Vec<Usage>,
+    tags: Vec<Usage>,
+}
+
+/// Counts the categories and tags used by the mods in the index,
+/// along with every one facti knows about.
+fn used_tags(json: bool) -> Result<()> {
+    let Some(index) = PortalIndex::load()? else {
+        bail!("No portal index exists, create one with `facti portal index update`");
+    };
+
+    // Include everything facti knows about, even if no mod uses it
+    let mut categories: BTreeMap<String, (usize, bool)> = Category::KNOWN
+        .iter()
+        .map(|c| (c.to_string(), (0, true)))
+        .collect();
+    let mut tags: BTreeMap<String, (usize, bool)> = Tag::KNOWN
+        .iter()
+        .map(|t| (t.to_string(), (0, true)))
+        .collect();
+
+    for result in index.mods.values() {
+        let category = &result.summary.category;
+        let entry = categories
+            .entry(category.to_string())
+            .or_insert((0, category.is_known()));
+        entry.0 += 1;
+
+        for tag in result.tags.iter().flatten() {
+            let entry = tags.entry(tag.to_string()).or_insert((0, tag.is_known()));
+            entry.0 += 1;
+        }
+    }
+
+    let known_tags = KnownTags::load()?;
+    let usages = |map: BTreeMap<String, (usize, bool)>, learned: &BTreeSet<String>| {
+        map.into_iter()
+            .map(|(name, (mods, known))| Usage {
+                learned: learned.contains(&name),
+                name,
+                mods,
+                known,
+            })
+            .collect::<Vec<_>>()
+    };
+    let taxonomy = Taxonomy {
+        categories: usages(categories, &known_tags.categories),
+        tags: usages(tags, &known_tags.tags),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&taxonomy)?);
+        return Ok(());
+    }
+
+    for (heading, usages) in [
+        ("Categories", &taxonomy.categories),
+        ("Tags", &taxonomy.tags),
+    ] {
+        println!("{}:", heading);
+        for usage in usages {
+            let new = if usage.known { "" } else { " (new)" };
+            println!("  {} ({} mods){}", usage.name, usage.mods, new);
+        }
+    }
+
+    Ok(())
+}
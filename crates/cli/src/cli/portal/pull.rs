@@ -0,0 +1,264 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::ModFull};
+use facti_lib::modinfo::ModPackageInfo;
+use tracing::{info, warn};
+
+use crate::project::Project;
+
+/// File the description is written to, unless `info.json` already names one.
+const INFORMATION_FILENAME: &str = "information.md";
+
+/// Directory gallery images are downloaded to, inside the output directory.
+const GALLERY_DIRNAME: &str = "gallery";
+
+/// Pull the details of a mod from the mod portal into the project.
+///
+/// Fetches the mod's portal page and writes the description to a markdown
+/// file, downloads the gallery images, and stores the summary, category,
+/// tags, license and source URL in the `package` section of `info.json`,
+/// so that they can be kept under version control.
+///
+/// Files already referenced by `info.json` are overwritten in place,
+/// new files are created in the output directory.
+///
+/// The FAQ is not available through the portal API, so it is left as-is.
+#[derive(Args, Debug)]
+pub struct PullArgs {
+    /// Path to the mod project, defaults to the current directory.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+
+    /// Directory to write new files to, relative to the project root.
+    #[arg(short, long, default_value = "portal", value_hint = ValueHint::DirPath)]
+    pub dir: PathBuf,
+
+    /// Do not download the gallery images.
+    #[arg(long)]
+    pub no_images: bool,
+}
+
+impl PullArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => env::current_dir().context("Failed to get current directory")?,
+        };
+        let project = Project::load(&path).with_context(|| {
+            format!(
+                "Failed to load Factorio mod project from {}",
+                path.display()
+            )
+        })?;
+        let name = &project.mod_info.name;
+
+        info!("Fetching details of {} from the mod portal", name);
+        let full = client
+            .info_full(name)
+            .with_context(|| format!("Failed to get details of {} from the portal", name))?;
+
+        let mut package = project.mod_info.package.clone().unwrap_or_default();
+        let dir = project.path.join(&self.dir);
+
+        let information = match &package.readme_path {
+            Some(readme) => project.mod_path.join(readme),
+            None => dir.join(INFORMATION_FILENAME),
+        };
+        write(&information, full.description.as_bytes())?;
+        package.readme_path = Some(relative_to(&information, &project.mod_path)?);
+
+        if !self.no_images {
+            package.gallery_paths = pull_gallery(client, name, &dir.join(GALLERY_DIRNAME))?
+                .iter()
+                .map(|image| relative_to(image, &project.mod_path))
+                .collect::<Result<_>>()?;
+        }
+
+        apply_details(&mut package, &full);
+
+        if package.description_as_summary
+            && project.mod_info.description.as_deref() != Some(full.summary.as_str())
+        {
+            warn!(
+                "The summary on the portal differs from the description in info.json, \
+                 but is ignored as description_as_summary is set"
+            );
+        }
+
+        set_package(&project, &package)?;
+        println!("Pulled details of {} from the mod portal", name);
+
+        Ok(())
+    }
+}
+
+/// Copies the details stored in `info.json` from the portal page.
+///
+/// The summary is left alone if the description in `info.json` is used
+/// as the summary instead.
+fn apply_details(package: &mut ModPackageInfo, full: &ModFull) {
+    if !package.description_as_summary {
+        package.summary = Some(full.summary.clone());
+    }
+    package.category = Some(full.category.to_string());
+    package.tags = full.tags.iter().map(ToString::to_string).collect();
+    package.license = full.license.as_ref().map(|l| l.id.to_string());
+    package.source_url = full.source_url.clone();
+}
+
+/// Downloads the gallery of a mod into `dir`, returning the paths of the
+/// images in display order.
+fn pull_gallery(client: &ApiClient, name: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+    info!("Fetching images of {}", name);
+    let images = client
+        .images(name)
+        .with_context(|| format!("Failed to get images of {}", name))?;
+
+    let mut paths = Vec::with_capacity(images.len());
+    for image in images {
+        info!("Downloading image {}", image.url);
+        let data = client
+            .download_image(&image)
+            .with_context(|| format!("Failed to download image {}", image.id))?;
+
+        let extension = Path::new(image.url.path())
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("png");
+        let path = dir.join(format!("{}.{}", image.id, extension));
+        write(&path, &data)?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("Wrote {}", path.display());
+
+    Ok(())
+}
+
+/// Makes `path` relative to `base`, as paths in `info.json` are relative to
+/// the directory containing it.
+///
+/// Both paths must be inside the project, `base` is allowed to be below
+/// the directory `path` is in (e.g. when the mod is in a `src` directory).
+fn relative_to(path: &Path, base: &Path) -> Result<PathBuf> {
+    let path = normalize(path);
+    let base = normalize(base);
+
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if common == 0 {
+        bail!(
+            "Cannot make {} relative to {}",
+            path.display(),
+            base.display()
+        );
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in base.components().skip(common) {
+        relative.push("..");
+    }
+    relative.extend(path.components().skip(common));
+
+    Ok(relative)
+}
+
+/// Removes `.` components and resolves `..` components lexically.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    normalized
+}
+
+/// Replaces the `package` section of `info.json`, leaving all other fields as-is.
+fn set_package(project: &Project, package: &ModPackageInfo) -> Result<()> {
+    project.update_info(|info| {
+        info.insert("package".to_owned(), serde_json::to_value(package)?);
+        Ok(())
+    })?;
+
+    info!(
+        "Updated package details in {}",
+        project.mod_info_path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to() {
+        let root = Path::new("/project");
+
+        assert_eq!(
+            relative_to(&root.join("portal/information.md"), root).unwrap(),
+            PathBuf::from("portal/information.md")
+        );
+        assert_eq!(
+            relative_to(&root.join("portal/gallery/a.png"), &root.join("src")).unwrap(),
+            PathBuf::from("../portal/gallery/a.png")
+        );
+        assert_eq!(
+            relative_to(&root.join("src/../README.md"), &root.join("src")).unwrap(),
+            PathBuf::from("../README.md")
+        );
+    }
+
+    #[test]
+    fn test_apply_details_keeps_summary_from_description() {
+        let full: ModFull = serde_json::from_value(serde_json::json!({
+            "downloads_count": 0,
+            "name": "my-mod",
+            "owner": "someone",
+            "releases": [],
+            "summary": "From the portal",
+            "category": "content",
+            "created_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap();
+
+        let mut package = ModPackageInfo::default();
+        apply_details(&mut package, &full);
+        assert_eq!(package.summary.as_deref(), Some("From the portal"));
+
+        let mut package = ModPackageInfo {
+            description_as_summary: true,
+            ..Default::default()
+        };
+        apply_details(&mut package, &full);
+        assert_eq!(package.summary, None);
+        assert_eq!(package.category.as_deref(), Some("content"));
+    }
+}
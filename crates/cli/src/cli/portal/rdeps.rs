@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use facti_api::data::portal::Release;
+use facti_lib::{
+    dependency::{Compatibility, DependencyMode},
+    version::{Version, VersionReq},
+};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::index::{IndexedMod, PortalIndex};
+
+/// List mods on the portal that depend on a mod.
+///
+/// Dependents are looked up in the local portal index, which must first be
+/// created with `facti portal index update --details`, as the portal's mod
+/// list does not include the dependencies of releases.
+///
+/// Use `--version` to check which dependents would not accept a new version
+/// of the mod (e.g. before releasing a new major version).
+#[derive(Args, Debug)]
+pub struct RdepsArgs {
+    /// Name of the mod to find dependents of.
+    #[arg(value_hint = ValueHint::Other)]
+    pub name: String,
+
+    /// Check every release of the dependents, not just their latest one.
+    #[arg(short, long)]
+    pub all_releases: bool,
+
+    /// Check whether the requirements of the dependents accept this version.
+    #[arg(long)]
+    pub version: Option<Version>,
+}
+
+/// How a dependent refers to the mod.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Kind {
+    Required,
+    Optional,
+    HiddenOptional,
+    Independent,
+    Incompatible,
+}
+
+/// A release of a mod that depends on the looked up mod.
+#[derive(Debug, Serialize)]
+struct Dependent {
+    name: String,
+    owner: String,
+    version: Version,
+    kind: Kind,
+
+    /// Version requirement of the dependency, empty if any version will do.
+    requirement: String,
+
+    /// Whether the requirement accepts the version given with `--version`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    accepts: Option<bool>,
+}
+
+impl RdepsArgs {
+    pub fn run(&self, json: bool) -> Result<()> {
+        let index = PortalIndex::load()?
+            .context("No portal index exists, create one with `facti portal index update`")?;
+
+        if !index.mods.values().any(has_dependencies) {
+            warn!(
+                "The portal index has no dependency information, \
+                 update it with `facti portal index update --details`"
+            );
+        }
+
+        let dependents = dependents(
+            index.mods.values(),
+            &self.name,
+            self.all_releases,
+            self.version,
+        );
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&dependents)?);
+        } else {
+            print_dependents(&self.name, &dependents);
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether any release of the mod in the index lists its dependencies.
+fn has_dependencies(indexed: &IndexedMod) -> bool {
+    releases(indexed, true).any(|r| r.info.dependencies.is_some())
+}
+
+/// Releases of a mod to check, either all known ones or only the latest.
+fn releases(indexed: &IndexedMod, all: bool) -> Box<dyn Iterator<Item = &Release> + '_> {
+    match (&indexed.summary.releases, all) {
+        (Some(releases), true) => Box::new(releases.iter()),
+        _ => Box::new(indexed.summary.latest().into_iter()),
+    }
+}
+
+/// Finds the releases that depend on `name`, sorted by kind, name and
+/// version.
+fn dependents<'a, I>(mods: I, name: &str, all: bool, version: Option<Version>) -> Vec<Dependent>
+where
+    I: IntoIterator<Item = &'a IndexedMod>,
+{
+    let mut dependents = Vec::new();
+
+    for indexed in mods {
+        for release in releases(indexed, all) {
+            let Some(dependencies) = &release.info.dependencies else {
+                continue;
+            };
+
+            for dependency in dependencies.iter().filter(|d| d.name == name) {
+                let (kind, requirement) = match &dependency.compatibility {
+                    Compatibility::Compatible(mode, req) => (Kind::from(*mode), Some(*req)),
+                    Compatibility::Incompatible => (Kind::Incompatible, None),
+                };
+
+                dependents.push(Dependent {
+                    name: indexed.summary.name.clone(),
+                    owner: indexed.summary.owner.clone(),
+                    version: release.version,
+                    kind,
+                    requirement: requirement.map(|r| r.to_string()).unwrap_or_default(),
+                    accepts: version.zip(requirement).map(|(v, r)| accepts(r, v)),
+                });
+            }
+        }
+    }
+
+    dependents.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.version.cmp(&b.version))
+    });
+
+    dependents
+}
+
+fn accepts(requirement: VersionReq, version: Version) -> bool {
+    match requirement {
+        VersionReq::Latest => true,
+        VersionReq::Spec(spec) => spec.matches(version),
+    }
+}
+
+impl From<DependencyMode> for Kind {
+    fn from(mode: DependencyMode) -> Self {
+        match mode {
+            DependencyMode::Required => Kind::Required,
+            DependencyMode::Optional { hidden: false } => Kind::Optional,
+            DependencyMode::Optional { hidden: true } => Kind::HiddenOptional,
+            DependencyMode::Independent => Kind::Independent,
+        }
+    }
+}
+
+impl Kind {
+    fn heading(self) -> &'static str {
+        match self {
+            Kind::Required => "Required by",
+            Kind::Optional => "Optional dependency of",
+            Kind::HiddenOptional => "Hidden optional dependency of",
+            Kind::Independent => "Independent dependency of",
+            Kind::Incompatible => "Incompatible with",
+        }
+    }
+}
+
+fn print_dependents(name: &str, dependents: &[Dependent]) {
+    if dependents.is_empty() {
+        println!("No mods in the index depend on {}", name);
+        return;
+    }
+
+    let mut kind = None;
+    for dependent in dependents {
+        if kind != Some(dependent.kind) {
+            kind = Some(dependent.kind);
+            println!("{}:", dependent.kind.heading());
+        }
+
+        print!(
+            "  {} v{} by {}",
+            dependent.name, dependent.version, dependent.owner
+        );
+        if !dependent.requirement.is_empty() {
+            print!(" ({})", dependent.requirement);
+        }
+        if dependent.accepts == Some(false) {
+            print!(" [does not accept new version]");
+        }
+        println!();
+    }
+
+    let rejecting = dependents
+        .iter()
+        .filter(|d| d.accepts == Some(false))
+        .count();
+    if rejecting > 0 {
+        println!(
+            "{} of {} dependencies do not accept the new version",
+            rejecting,
+            dependents.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use facti_api::data::portal::{ModSummary, ReleaseInfo};
+    use facti_lib::{FactorioVersion, dependency::Dependency};
+
+    use super::*;
+
+    fn release(version: Version, dependencies: Vec<Dependency>) -> Release {
+        Release {
+            download_path: String::new(),
+            filename: String::new(),
+            info: ReleaseInfo {
+                factorio_version: FactorioVersion::new(2, 0),
+                dependencies: Some(dependencies),
+            },
+            released_at: Default::default(),
+            version,
+            sha1: String::new(),
+        }
+    }
+
+    fn indexed(name: &str, releases: Vec<Release>) -> IndexedMod {
+        IndexedMod {
+            summary: ModSummary {
+                download_count: 0,
+                latest_release: None,
+                name: name.to_owned(),
+                owner: "someone".to_owned(),
+                releases: Some(releases),
+                summary: String::new(),
+                title: name.to_owned(),
+                category: Default::default(),
+                thumbnail_path: None,
+            },
+            tags: None,
+        }
+    }
+
+    #[test]
+    fn test_dependents() {
+        let v1 = Version::new(1, 0, 0);
+        let v2 = Version::new(2, 0, 0);
+        let before_2 = VersionReq::parse("< 2.0.0").unwrap();
+        let mods = [
+            indexed(
+                "addon",
+                vec![
+                    release(v1, vec![Dependency::required("lib", before_2)]),
+                    release(v2, vec![Dependency::required("lib", VersionReq::Latest)]),
+                ],
+            ),
+            indexed(
+                "rival",
+                vec![release(v1, vec![Dependency::incompatible("lib")])],
+            ),
+            indexed(
+                "extra",
+                vec![release(
+                    v1,
+                    vec![Dependency::optional("lib", before_2, true)],
+                )],
+            ),
+            indexed("other", vec![release(v1, vec![])]),
+        ];
+
+        let latest = dependents(&mods, "lib", false, Some(v2));
+        let summary = latest
+            .iter()
+            .map(|d| (d.name.as_str(), d.version, d.kind, d.accepts))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("addon", v2, Kind::Required, Some(true)),
+                ("extra", v1, Kind::HiddenOptional, Some(false)),
+                ("rival", v1, Kind::Incompatible, None),
+            ]
+        );
+        assert_eq!(latest[1].requirement, "< 2.0.0");
+
+        let all = dependents(&mods, "lib", true, None);
+        assert_eq!(all.len(), 4);
+        assert_eq!((all[0].name.as_str(), all[0].version), ("addon", v1));
+        assert!(all.iter().all(|d| d.accepts.is_none()));
+    }
+}
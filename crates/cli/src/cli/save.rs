@@ -0,0 +1,46 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use facti_api::blocking::ApiClient;
+use facti_lib::save::SaveInfo;
+
+use self::{info::SaveInfoArgs, sync_mods::SaveSyncModsArgs};
+
+mod info;
+mod sync_mods;
+
+/// Work with Factorio save files.
+///
+/// Saves record the version of Factorio they were made with and the
+/// versions of the mods that were active, which these commands read
+/// without needing the game.
+#[derive(Args, Debug)]
+pub struct SaveArgs {
+    #[command(subcommand)]
+    pub command: SaveCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SaveCommands {
+    Info(SaveInfoArgs),
+
+    SyncMods(SaveSyncModsArgs),
+}
+
+impl SaveArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        match &self.command {
+            SaveCommands::Info(args) => args.run(),
+            SaveCommands::SyncMods(args) => args.run(client),
+        }
+    }
+}
+
+/// Reads the metadata of the save at `path`.
+fn read_save(path: &Path) -> Result<SaveInfo> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+
+    SaveInfo::read(BufReader::new(file))
+        .with_context(|| format!("Failed to read save {}", path.display()))
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueHint};
+
+use super::read_save;
+
+/// Show the Factorio version and mods of a save.
+#[derive(Args, Debug)]
+pub struct SaveInfoArgs {
+    /// Path to the save file.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub file: PathBuf,
+
+    /// Output the save info as JSON.
+    ///
+    /// Tip: You can use jq to work with JSON!
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+impl SaveInfoArgs {
+    pub fn run(&self) -> Result<()> {
+        let info = read_save(&self.file)?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+            return Ok(());
+        }
+
+        println!(
+            "Factorio version: {} (build {})",
+            info.factorio_version, info.build
+        );
+        if info.campaign.is_empty() {
+            println!("Scenario: {}/{}", info.base_mod, info.level_name);
+        } else {
+            println!(
+                "Campaign: {}/{} ({})",
+                info.base_mod, info.campaign, info.level_name
+            );
+        }
+
+        println!("Mods ({}):", info.mods.len());
+        for m in &info.mods {
+            println!("  {} {}", m.name, m.version);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueHint};
+use facti_api::{blocking::ApiClient, data::portal::Release};
+use facti_lib::save::SaveMod;
+use tracing::{debug, info, warn};
+
+use crate::mods::{self, BUILT_IN_MODS, ModList, ModsFolder};
+
+use super::read_save;
+
+/// Set up a mods folder to load a save.
+///
+/// `mod-list.json` is written to enable exactly the mods the save was made
+/// with, disabling any other mods in the folder. Versions of the mods that
+/// are missing from the folder are downloaded from the mod portal, which
+/// requires the username and token of a Factorio account (see `--username`
+/// and `--token`), and other versions of them are removed, as Factorio
+/// loads the latest version of a mod it finds.
+///
+/// Built-in mods like `base` ship with the game, so they are only enabled
+/// or disabled.
+#[derive(Args, Debug)]
+pub struct SaveSyncModsArgs {
+    /// Path to the save file.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub file: PathBuf,
+
+    /// Path to the mods folder to sync, created if it does not exist.
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub mods_dir: PathBuf,
+
+    /// Print what would be done, without changing the mods folder.
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+impl SaveSyncModsArgs {
+    pub fn run(&self, client: &ApiClient) -> Result<()> {
+        let save = read_save(&self.file)?;
+        let dir = &self.mods_dir;
+
+        let installed = if dir.is_dir() {
+            ModsFolder::load(dir)?.mods
+        } else {
+            BTreeMap::new()
+        };
+
+        let needed = save
+            .mods
+            .iter()
+            .filter(|m| !BUILT_IN_MODS.contains(&m.name.as_str()))
+            .collect::<Vec<_>>();
+
+        // Look up every missing release before changing anything, so that
+        // a mod missing from the portal leaves the folder as it was
+        let mut missing = Vec::new();
+        for m in &needed {
+            let file_name = format!("{}_{}.zip", m.name, m.version);
+            let is_installed = dir.join(file_name).is_file()
+                || installed
+                    .get(&m.name)
+                    .is_some_and(|i| i.info.version == m.version);
+            if is_installed {
+                debug!("{} {} is already installed", m.name, m.version);
+            } else {
+                missing.push((*m, find_release(client, m)?));
+            }
+        }
+
+        let names = installed
+            .keys()
+            .cloned()
+            .chain(save.mods.iter().map(|m| m.name.clone()));
+        let mod_list = ModList::new(names, |name| save.mods.iter().any(|m| m.name == name));
+
+        if self.dry_run {
+            for (m, _) in &missing {
+                println!("Would download {} {}", m.name, m.version);
+            }
+            for entry in &mod_list.mods {
+                let action = if entry.enabled { "enable" } else { "disable" };
+                println!("Would {} {}", action, entry.name);
+            }
+            return Ok(());
+        }
+
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        for m in &needed {
+            mods::remove_other_versions(dir, &m.name, m.version)?;
+        }
+        for (m, release) in &missing {
+            mods::download(client, &m.name, release, dir)?;
+        }
+
+        mod_list.save(dir)?;
+
+        // Mods installed as directories are left alone, and Factorio would
+        // load them instead if they are newer
+        let folder = ModsFolder::load(dir)?;
+        for m in &needed {
+            if let Some(i) = folder
+                .mods
+                .get(&m.name)
+                .filter(|i| i.info.version != m.version)
+            {
+                warn!(
+                    "The save needs {} {}, but {} {} at {} would be loaded",
+                    m.name,
+                    m.version,
+                    m.name,
+                    i.info.version,
+                    i.path.display()
+                );
+            }
+        }
+
+        println!(
+            "Synced {} mods into {} ({} downloaded, {} already installed)",
+            needed.len(),
+            dir.display(),
+            missing.len(),
+            needed.len() - missing.len()
+        );
+
+        Ok(())
+    }
+}
+
+/// Finds the release of a mod used by a save on the mod portal.
+fn find_release(client: &ApiClient, m: &SaveMod) -> Result<Release> {
+    info!("Fetching releases of {}", m.name);
+    let short = client
+        .info_short(&m.name)
+        .with_context(|| format!("Failed to get {} from the mod portal", m.name))?;
+
+    match short.releases.into_iter().find(|r| r.version == m.version) {
+        Some(release) => Ok(release),
+        None => bail!(
+            "The save needs {} {}, which is not on the mod portal",
+            m.name,
+            m.version
+        ),
+    }
+}
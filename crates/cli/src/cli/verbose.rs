@@ -54,6 +54,11 @@ impl<L: LogLevel> Verbosity<L> {
             .unwrap_or(tracing::level_filters::LevelFilter::OFF)
     }
 
+    /// Whether quiet output was explicitly asked for.
+    pub fn is_quiet(&self) -> bool {
+        self.quiet > 0
+    }
+
     pub fn is_given(&self) -> bool {
         self.verbose > 0 || self.quiet > 0
     }
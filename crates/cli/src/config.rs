@@ -33,6 +33,9 @@ pub struct FactorioApiConfig {
     #[serde(alias = "portal_base_url", skip_serializing_if = "Option::is_none")]
     pub portal_base_url: Option<Url>,
 
+    #[serde(alias = "portal_api_base_url", skip_serializing_if = "Option::is_none")]
+    pub portal_api_base_url: Option<Url>,
+
     #[serde(alias = "game_base_url", skip_serializing_if = "Option::is_none")]
     pub game_base_url: Option<Url>,
 
@@ -41,6 +44,37 @@ pub struct FactorioApiConfig {
 
     #[serde(alias = "api_key_file", skip_serializing_if = "Option::is_none")]
     pub api_key_file: Option<PathBuf>,
+
+    /// Username of the Factorio account to download mods with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Service token of the Factorio account to download mods with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Url>,
+
+    /// PEM files with extra root certificates to trust.
+    #[serde(
+        default,
+        alias = "ca_certificates",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub ca_certificates: Vec<PathBuf>,
+
+    /// Connect timeout, in seconds.
+    #[serde(alias = "connect_timeout", skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+
+    /// Read timeout, in seconds.
+    #[serde(alias = "read_timeout", skip_serializing_if = "Option::is_none")]
+    pub read_timeout: Option<u64>,
+
+    /// Contact information to include in the User-Agent.
+    #[serde(alias = "user_agent_contact", skip_serializing_if = "Option::is_none")]
+    pub user_agent_contact: Option<String>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
@@ -183,6 +217,34 @@ mod tests {
         assert_eq!(config.factorio_api.api_key.unwrap(), "foobar");
     }
 
+    #[test]
+    fn test_config_http_parse() {
+        let config: Config = toml::from_str(
+            r#"
+            [factorio-api]
+            proxy = "http://proxy.example.com:3128/"
+            ca-certificates = ["/etc/ssl/corp.pem"]
+            connect-timeout = 5
+            read-timeout = 60
+            user-agent-contact = "mailto:ci@example.com"
+            "#,
+        )
+        .unwrap();
+        let api = config.factorio_api;
+
+        assert_eq!(
+            api.proxy.unwrap().as_str(),
+            "http://proxy.example.com:3128/"
+        );
+        assert_eq!(
+            api.ca_certificates,
+            vec![PathBuf::from("/etc/ssl/corp.pem")]
+        );
+        assert_eq!(api.connect_timeout, Some(5));
+        assert_eq!(api.read_timeout, Some(60));
+        assert_eq!(api.user_agent_contact.unwrap(), "mailto:ci@example.com");
+    }
+
     #[test]
     fn test_config_defaults_to_empty() {
         let config: Config = toml::from_str("").unwrap();
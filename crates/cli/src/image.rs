@@ -0,0 +1,484 @@
+//! Preprocessing of gallery images before they are uploaded to the portal.
+//!
+//! Images are checked and, where needed, converted so that the portal
+//! accepts them: formats the portal does not take are converted to PNG,
+//! images larger than [`MAX_DIMENSION`] are downsized, and metadata such as
+//! EXIF (which can contain camera details and GPS coordinates) is removed.
+
+use std::{
+    fmt::{self, Display},
+    io::Cursor,
+};
+
+use anyhow::{Context, Result, bail};
+use image::{
+    AnimationDecoder, DynamicImage, Frame, ImageDecoder, ImageReader, Limits, RgbaImage,
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        jpeg::JpegEncoder,
+    },
+    imageops::{self, FilterType},
+};
+
+mod jpeg;
+mod png;
+
+/// Largest width or height facti lets through for gallery images.
+///
+/// Larger images are downsized to fit, keeping their aspect ratio.
+pub const MAX_DIMENSION: u32 = 2048;
+
+/// Largest width or height of an image facti will decode.
+///
+/// Checked against the image header before decoding, so that a crafted
+/// header cannot make facti allocate gigabytes for the pixels.
+const MAX_INPUT_DIMENSION: u32 = 16384;
+
+/// Quality used when re-encoding downsized JPEG images.
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Detects the format of an image from its contents.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(png::SIGNATURE) {
+            Some(Self::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if data.starts_with(b"BM") {
+            Some(Self::Bmp)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Gif => "gif",
+            Self::Bmp => "bmp",
+        }
+    }
+}
+
+impl From<ImageFormat> for image::ImageFormat {
+    fn from(format: ImageFormat) -> Self {
+        match format {
+            ImageFormat::Png => Self::Png,
+            ImageFormat::Jpeg => Self::Jpeg,
+            ImageFormat::Gif => Self::Gif,
+            ImageFormat::Bmp => Self::Bmp,
+        }
+    }
+}
+
+impl Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Png => write!(f, "PNG"),
+            Self::Jpeg => write!(f, "JPEG"),
+            Self::Gif => write!(f, "GIF"),
+            Self::Bmp => write!(f, "BMP"),
+        }
+    }
+}
+
+/// Width and height of an image, in pixels.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Dimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Dimensions {
+    fn fits(&self) -> bool {
+        self.width <= MAX_DIMENSION && self.height <= MAX_DIMENSION
+    }
+
+    /// Scales the dimensions down to fit within [`MAX_DIMENSION`],
+    /// keeping the aspect ratio.
+    fn fitted(&self) -> Self {
+        if self.fits() {
+            return *self;
+        }
+
+        let scale = MAX_DIMENSION as f64 / self.width.max(self.height) as f64;
+        Self {
+            width: ((self.width as f64 * scale).round() as u32).clamp(1, MAX_DIMENSION),
+            height: ((self.height as f64 * scale).round() as u32).clamp(1, MAX_DIMENSION),
+        }
+    }
+}
+
+impl Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// The result of preprocessing an image.
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub dimensions: Dimensions,
+    pub original_format: ImageFormat,
+    pub original_dimensions: Dimensions,
+    pub original_size: usize,
+}
+
+impl ProcessedImage {
+    /// Whether processing changed anything at all.
+    pub fn is_modified(&self) -> bool {
+        self.data.len() != self.original_size
+            || self.format != self.original_format
+            || self.dimensions != self.original_dimensions
+    }
+}
+
+/// Prepares an image for uploading to the portal.
+///
+/// PNG and JPEG images that fit are only stripped of metadata, and GIF
+/// images that fit are left as they are. Larger images are downsized, and
+/// BMP images are converted to PNG. JPEG images with an EXIF orientation
+/// are re-encoded with the orientation applied, as it is stripped with the
+/// rest of the metadata.
+///
+/// # Errors
+///
+/// Fails if the image is in an unsupported format, is larger than facti
+/// is willing to decode, or cannot be decoded.
+pub fn process(data: &[u8]) -> Result<ProcessedImage> {
+    let Some(format) = ImageFormat::detect(data) else {
+        bail!("Unsupported image format, only PNG, JPEG, GIF and BMP images are supported");
+    };
+
+    let original = dimensions(data, format)?;
+
+    let (processed, output_format) = match format {
+        ImageFormat::Png if original.fits() => (png::strip(data)?, format),
+        ImageFormat::Jpeg if original.fits() && !jpeg::is_oriented(data)? => {
+            (jpeg::strip(data)?, format)
+        }
+        ImageFormat::Gif if original.fits() => (data.to_vec(), format),
+        ImageFormat::Png | ImageFormat::Bmp => {
+            let image = resize(&decode(data, format)?);
+            (encode(image, image::ImageFormat::Png)?, ImageFormat::Png)
+        }
+        ImageFormat::Jpeg => {
+            let image = DynamicImage::ImageRgba8(resize(&decode(data, format)?));
+            let mut output = Vec::new();
+            JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY)
+                .encode_image(&image.to_rgb8())
+                .context("Failed to encode JPEG image")?;
+            (output, format)
+        }
+        ImageFormat::Gif => (resize_gif(data, original.fitted())?, format),
+    };
+
+    // Read back, as applying the EXIF orientation of a JPEG image can
+    // swap its width and height
+    let dimensions = if processed == data {
+        original
+    } else {
+        self::dimensions(&processed, output_format)?
+    };
+
+    Ok(ProcessedImage {
+        data: processed,
+        format: output_format,
+        dimensions,
+        original_format: format,
+        original_dimensions: original,
+        original_size: data.len(),
+    })
+}
+
+fn limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_INPUT_DIMENSION);
+    limits.max_image_height = Some(MAX_INPUT_DIMENSION);
+    limits
+}
+
+/// Reads the dimensions of an image from its header, without decoding it.
+fn dimensions(data: &[u8], format: ImageFormat) -> Result<Dimensions> {
+    let (width, height) = ImageReader::with_format(Cursor::new(data), format.into())
+        .into_dimensions()
+        .with_context(|| format!("Failed to read {} image", format))?;
+    let dimensions = Dimensions { width, height };
+
+    if width > MAX_INPUT_DIMENSION || height > MAX_INPUT_DIMENSION {
+        bail!(
+            "{} image is {}, larger than the {}x{} facti can process; resize it manually or use --no-process",
+            format,
+            dimensions,
+            MAX_INPUT_DIMENSION,
+            MAX_INPUT_DIMENSION
+        );
+    }
+
+    Ok(dimensions)
+}
+
+/// Decodes an image, applying its EXIF orientation if it has one.
+fn decode(data: &[u8], format: ImageFormat) -> Result<DynamicImage> {
+    let mut reader = ImageReader::with_format(Cursor::new(data), format.into());
+    reader.limits(limits());
+    let mut decoder = reader
+        .into_decoder()
+        .with_context(|| format!("Failed to decode {} image", format))?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)
+        .with_context(|| format!("Failed to decode {} image", format))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Downsizes an image to fit within [`MAX_DIMENSION`], keeping the aspect ratio.
+fn resize(image: &DynamicImage) -> RgbaImage {
+    let dimensions = Dimensions {
+        width: image.width(),
+        height: image.height(),
+    };
+    if dimensions.fits() {
+        return image.to_rgba8();
+    }
+
+    let dimensions = dimensions.fitted();
+    imageops::resize(
+        image,
+        dimensions.width,
+        dimensions.height,
+        FilterType::Lanczos3,
+    )
+}
+
+fn encode(image: RgbaImage, format: image::ImageFormat) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut output), format)
+        .with_context(|| format!("Failed to encode {:?} image", format))?;
+    Ok(output)
+}
+
+/// Downsizes every frame of a (possibly animated) GIF image.
+///
+/// The re-encoded image loops forever, as most animated GIFs do.
+fn resize_gif(data: &[u8], dimensions: Dimensions) -> Result<Vec<u8>> {
+    let mut decoder = GifDecoder::new(Cursor::new(data)).context("Failed to decode GIF image")?;
+    decoder.set_limits(limits())?;
+    let frames = decoder
+        .into_frames()
+        .collect_frames()
+        .context("Failed to decode GIF image")?;
+
+    let mut output = Vec::new();
+    {
+        // The default speed of 1 takes minutes to quantize large frames
+        let mut encoder = GifEncoder::new_with_speed(&mut output, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder
+            .encode_frames(frames.into_iter().map(|frame| {
+                let delay = frame.delay();
+                let buffer = imageops::resize(
+                    frame.buffer(),
+                    dimensions.width,
+                    dimensions.height,
+                    FilterType::Lanczos3,
+                );
+                Frame::from_parts(buffer, 0, 0, delay)
+            }))
+            .context("Failed to encode GIF image")?;
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ExtendedColorType, Rgba, codecs::bmp::BmpEncoder};
+
+    use super::*;
+
+    fn checkerboard(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let v = if (x + y) % 2 == 0 { 255 } else { 0 };
+            Rgba([v, v, v, 255])
+        })
+    }
+
+    fn large() -> Dimensions {
+        Dimensions {
+            width: MAX_DIMENSION,
+            height: 1,
+        }
+    }
+
+    #[test]
+    fn test_fitted_keeps_aspect_ratio() {
+        let dimensions = Dimensions {
+            width: 4096,
+            height: 1024,
+        };
+
+        assert_eq!(
+            dimensions.fitted(),
+            Dimensions {
+                width: 2048,
+                height: 512
+            }
+        );
+    }
+
+    #[test]
+    fn test_small_png_is_only_stripped() {
+        let data = encode(checkerboard(4, 4), image::ImageFormat::Png).unwrap();
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Png);
+        assert_eq!(processed.dimensions, processed.original_dimensions);
+        assert!(!processed.is_modified());
+    }
+
+    #[test]
+    fn test_large_png_is_downsized() {
+        let data = encode(checkerboard(MAX_DIMENSION * 2, 2), image::ImageFormat::Png).unwrap();
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Png);
+        assert_eq!(processed.dimensions, large());
+        assert_eq!(
+            dimensions(&processed.data, ImageFormat::Png).unwrap(),
+            large()
+        );
+    }
+
+    #[test]
+    fn test_large_jpeg_is_downsized() {
+        let image = DynamicImage::ImageRgba8(checkerboard(MAX_DIMENSION * 2, 2));
+        let mut data = Vec::new();
+        JpegEncoder::new(&mut data)
+            .encode_image(&image.to_rgb8())
+            .unwrap();
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Jpeg);
+        assert_eq!(processed.dimensions, large());
+        assert_eq!(
+            dimensions(&processed.data, ImageFormat::Jpeg).unwrap(),
+            large()
+        );
+    }
+
+    #[test]
+    fn test_oriented_jpeg_is_rotated() {
+        let image = DynamicImage::ImageRgba8(checkerboard(4, 2));
+        let mut encoded = Vec::new();
+        JpegEncoder::new(&mut encoded)
+            .encode_image(&image.to_rgb8())
+            .unwrap();
+
+        // EXIF segment with orientation 6 (rotate 90 degrees clockwise)
+        let exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0";
+        let mut data = vec![0xff, 0xd8, 0xff, 0xe1];
+        data.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(exif);
+        data.extend_from_slice(&encoded[2..]);
+
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Jpeg);
+        assert_eq!(
+            processed.original_dimensions,
+            Dimensions {
+                width: 4,
+                height: 2
+            }
+        );
+        assert_eq!(
+            processed.dimensions,
+            Dimensions {
+                width: 2,
+                height: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_large_gif_is_downsized() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new_with_speed(&mut data, 30);
+            encoder
+                .encode_frames((0..2).map(|_| Frame::new(checkerboard(MAX_DIMENSION * 2, 2))))
+                .unwrap();
+        }
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Gif);
+        assert_eq!(processed.dimensions, large());
+
+        let frames = GifDecoder::new(Cursor::new(&processed.data))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn test_bitfields_bmp_is_converted() {
+        // 32-bit BMP images with alpha are stored with BITFIELDS compression
+        let image = checkerboard(3, 2);
+        let mut data = Vec::new();
+        BmpEncoder::new(&mut data)
+            .encode(image.as_raw(), 3, 2, ExtendedColorType::Rgba8)
+            .unwrap();
+        assert_eq!(u32::from_le_bytes(data[30..34].try_into().unwrap()), 3);
+
+        let processed = process(&data).unwrap();
+
+        assert_eq!(processed.format, ImageFormat::Png);
+        let decoded = image::load_from_memory(&processed.data).unwrap();
+        assert_eq!(decoded.to_rgba8(), image);
+    }
+
+    #[test]
+    fn test_huge_header_is_rejected_before_decoding() {
+        fn chunk(data: &mut Vec<u8>, kind: &[u8; 4], content: &[u8]) {
+            data.extend_from_slice(&(content.len() as u32).to_be_bytes());
+            data.extend_from_slice(kind);
+            data.extend_from_slice(content);
+            let crc = crc32fast::hash(&[kind.as_slice(), content].concat());
+            data.extend_from_slice(&crc.to_be_bytes());
+        }
+
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&100_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&100_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut data = png::SIGNATURE.to_vec();
+        chunk(&mut data, b"IHDR", &ihdr);
+        chunk(&mut data, b"IDAT", &[]);
+        chunk(&mut data, b"IEND", &[]);
+
+        let error = process(&data).err().unwrap();
+        assert!(error.to_string().contains("100000x100000"), "{:#}", error);
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        assert!(process(b"RIFF\0\0\0\0WEBPVP8 ").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Metadata stripping for JPEG images.
+//!
+//! Only the segments before the image data are touched; the compressed image
+//! itself is copied verbatim, so stripping is lossless.
+
+use anyhow::{Result, bail};
+
+const APP1: u8 = 0xe1;
+const APP13: u8 = 0xed;
+const COM: u8 = 0xfe;
+const SOS: u8 = 0xda;
+const EOI: u8 = 0xd9;
+
+/// EXIF tag holding the orientation of the image.
+const ORIENTATION_TAG: u16 = 0x0112;
+
+struct Segment<'a> {
+    marker: u8,
+    /// The segment data, after the marker and length.
+    data: &'a [u8],
+    /// The entire segment, including its marker and length.
+    raw: &'a [u8],
+}
+
+/// Splits a JPEG image into the segments before the image data,
+/// and everything from the start of the image data onwards.
+fn segments(data: &[u8]) -> Result<(Vec<Segment<'_>>, &[u8])> {
+    if !data.starts_with(&[0xff, 0xd8]) {
+        bail!("Not a JPEG image");
+    }
+
+    let mut segments = Vec::new();
+    let mut offset = 2;
+
+    loop {
+        // Markers may be preceded by any number of fill bytes
+        let start = offset;
+        while data.get(offset) == Some(&0xff) && data.get(offset + 1) == Some(&0xff) {
+            offset += 1;
+        }
+
+        let (Some(0xff), Some(&marker)) = (data.get(offset), data.get(offset + 1)) else {
+            bail!("Invalid or truncated JPEG image");
+        };
+
+        if marker == SOS || marker == EOI {
+            return Ok((segments, &data[start..]));
+        }
+
+        let Some(len) = data.get(offset + 2..offset + 4) else {
+            bail!("Truncated JPEG image");
+        };
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        let end = offset + 2 + len;
+        if len < 2 || end > data.len() {
+            bail!("Truncated JPEG image");
+        }
+
+        segments.push(Segment {
+            marker,
+            data: &data[offset + 4..end],
+            raw: &data[start..end],
+        });
+        offset = end;
+    }
+}
+
+/// Removes EXIF and XMP (APP1), Photoshop/IPTC (APP13) and comment segments.
+///
+/// Color information (such as ICC profiles) is kept.
+pub fn strip(data: &[u8]) -> Result<Vec<u8>> {
+    let (segments, image) = segments(data)?;
+    let mut output = vec![0xff, 0xd8];
+
+    for segment in segments {
+        if !matches!(segment.marker, APP1 | APP13 | COM) {
+            output.extend_from_slice(segment.raw);
+        }
+    }
+
+    output.extend_from_slice(image);
+
+    Ok(output)
+}
+
+/// Whether the EXIF data of a JPEG image asks for it to be rotated or
+/// flipped, which [`strip`] would lose.
+pub fn is_oriented(data: &[u8]) -> Result<bool> {
+    let (segments, _) = segments(data)?;
+
+    Ok(segments
+        .iter()
+        .filter(|segment| segment.marker == APP1)
+        .filter_map(|segment| orientation(segment.data))
+        .any(|orientation| orientation != 1))
+}
+
+/// Reads the orientation from an EXIF segment, if present.
+fn orientation(data: &[u8]) -> Option<u16> {
+    let tiff = data.strip_prefix(b"Exif\0\0")?;
+    let big_endian = match tiff.get(0..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+
+    let u16_at = |offset: usize| {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let ifd = u32_at(4)? as usize;
+    let count = u16_at(ifd)? as usize;
+
+    (0..count)
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(ORIENTATION_TAG))
+        .and_then(|entry| u16_at(entry + 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xff, marker];
+        segment.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(data);
+        segment
+    }
+
+    fn exif(orientation: u16) -> Vec<u8> {
+        let mut data = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+        data.extend_from_slice(&ORIENTATION_TAG.to_be_bytes());
+        data.extend_from_slice(&[0, 3, 0, 0, 0, 1]);
+        data.extend_from_slice(&orientation.to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        data
+    }
+
+    fn jpeg(extra: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![0xff, 0xd8];
+        data.extend(segment(0xe0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        for segment in extra {
+            data.extend_from_slice(segment);
+        }
+        data.extend(segment(0xc0, &[8, 0, 48, 0, 64, 1, 1, 0x11, 0]));
+        data.extend(segment(SOS, &[1, 1, 0, 0, 0x3f, 0]));
+        data.extend_from_slice(&[0x12, 0x34, 0xff, 0x00, 0xff, EOI]);
+        data
+    }
+
+    #[test]
+    fn test_strip_removes_metadata() {
+        let with_metadata = jpeg(&[
+            segment(APP1, &exif(1)),
+            segment(COM, b"made with a camera"),
+            segment(APP13, b"Photoshop 3.0\0"),
+        ]);
+
+        assert_eq!(strip(&with_metadata).unwrap(), jpeg(&[]));
+    }
+
+    #[test]
+    fn test_is_oriented() {
+        assert!(is_oriented(&jpeg(&[segment(APP1, &exif(6))])).unwrap());
+        assert!(!is_oriented(&jpeg(&[segment(APP1, &exif(1))])).unwrap());
+        assert!(!is_oriented(&jpeg(&[])).unwrap());
+    }
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(orientation(&exif(6)), Some(6));
+        assert_eq!(orientation(b"http://ns.adobe.com/xap/1.0/\0"), None);
+    }
+}
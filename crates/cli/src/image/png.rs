@@ -0,0 +1,103 @@
+//! Metadata stripping for PNG images.
+//!
+//! Only ancillary metadata chunks are removed; every other chunk is copied
+//! verbatim, so stripping is lossless.
+
+use anyhow::{Result, bail};
+
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Ancillary chunks that only carry metadata, and are removed when stripping.
+const METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+struct Chunk<'a> {
+    kind: &'a [u8; 4],
+    /// The entire chunk, including its length, type and CRC.
+    raw: &'a [u8],
+}
+
+fn chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>> {
+    if !data.starts_with(SIGNATURE) {
+        bail!("Not a PNG image");
+    }
+
+    let mut chunks = Vec::new();
+    let mut rest = &data[SIGNATURE.len()..];
+
+    while !rest.is_empty() {
+        if rest.len() < 12 {
+            bail!("Truncated PNG image");
+        }
+
+        let len = u32::from_be_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let Some(end) = len.checked_add(12).filter(|&end| end <= rest.len()) else {
+            bail!("Truncated PNG image");
+        };
+
+        let chunk = Chunk {
+            kind: rest[4..8].try_into().unwrap(),
+            raw: &rest[..end],
+        };
+        let is_end = chunk.kind == b"IEND";
+        chunks.push(chunk);
+        rest = &rest[end..];
+
+        if is_end {
+            break;
+        }
+    }
+
+    match chunks.first() {
+        Some(chunk) if chunk.kind == b"IHDR" => Ok(chunks),
+        _ => bail!("PNG image is missing its header"),
+    }
+}
+
+/// Removes metadata chunks from a PNG image, leaving the pixel data untouched.
+pub fn strip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut output = SIGNATURE.to_vec();
+
+    for chunk in chunks(data)? {
+        if !METADATA_CHUNKS.contains(&chunk.kind) {
+            output.extend_from_slice(chunk.raw);
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, RgbaImage};
+
+    use super::*;
+
+    fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(kind);
+        crc.update(data);
+
+        output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        output.extend_from_slice(kind);
+        output.extend_from_slice(data);
+        output.extend_from_slice(&crc.finalize().to_be_bytes());
+    }
+
+    #[test]
+    fn test_strip_removes_metadata() {
+        let mut encoded = Vec::new();
+        RgbaImage::new(3, 2)
+            .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+            .unwrap();
+
+        let iend = encoded.len() - 12;
+        let mut with_text = encoded[..iend].to_vec();
+        write_chunk(&mut with_text, b"tEXt", b"Author\0someone");
+        write_chunk(&mut with_text, b"eXIf", b"MM\0*");
+        with_text.extend_from_slice(&encoded[iend..]);
+
+        assert_eq!(strip(&with_text).unwrap(), encoded);
+    }
+}
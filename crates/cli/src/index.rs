@@ -0,0 +1,531 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use facti_api::{
+    blocking::ApiClient,
+    data::{
+        detail::Tag,
+        pagination::PageSize,
+        portal::{ModFull, ModSummary, SearchQuery},
+        sorting::{SortMode, SortOrder},
+    },
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::dirs;
+
+const INDEX_FILENAME: &str = "portal-index.json";
+
+/// Page size to use when fetching recently updated mods.
+///
+/// Incremental updates usually only concern a handful of mods, so there is
+/// no point in fetching the entire portal in one go.
+const INCREMENTAL_PAGE_SIZE: u32 = 100;
+
+/// How long incremental updates are used before falling back to a full
+/// update, which is the only way to notice mods that were deleted.
+const FULL_UPDATE_INTERVAL: TimeDelta = TimeDelta::days(7);
+
+/// Minimum similarity for a word to be considered a fuzzy match of a search term.
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+/// A local copy of the mod listing on the mod portal.
+///
+/// The portal API has no free-text search, so this is used to search
+/// the portal offline.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct PortalIndex {
+    /// When the index was last updated.
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// When the complete mod list was last downloaded.
+    #[serde(default)]
+    pub full_updated_at: Option<DateTime<Utc>>,
+
+    /// Indexed mods, keyed by their internal name.
+    pub mods: BTreeMap<String, IndexedMod>,
+}
+
+/// A mod in the index, as listed on the portal.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexedMod {
+    #[serde(flatten)]
+    pub summary: ModSummary,
+
+    /// Tags of the mod, which are not included in the portal's mod listing
+    /// and are only known once its full details have been fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Tag>>,
+}
+
+/// Summary of what changed in an index update.
+#[derive(Default, Debug)]
+pub struct UpdateSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+
+    /// Names of the mods that were added or updated.
+    pub changed: Vec<String>,
+}
+
+/// How merging a mod from the portal's mod list changed the index.
+enum Change {
+    Added,
+    Updated { had_details: bool },
+    Unchanged,
+}
+
+/// A search hit from the local index, with its relevance score.
+#[derive(Debug)]
+pub struct SearchHit<'a> {
+    pub score: f64,
+    pub result: &'a IndexedMod,
+}
+
+impl PortalIndex {
+    pub fn default_path() -> Result<PathBuf> {
+        let data_dir = dirs::data()?;
+        let index_path = data_dir.join(INDEX_FILENAME);
+
+        debug!("Resolved portal index path as {}", index_path.display());
+
+        Ok(index_path)
+    }
+
+    /// Loads the index from its default location.
+    ///
+    /// Returns [`None`] if no index has been created yet.
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::default_path()?;
+
+        if !path.exists() {
+            debug!("No portal index exists at {}", path.display());
+            return Ok(None);
+        }
+
+        info!("Loading portal index from {}", path.display());
+        let file = File::open(&path).context("Failed to open portal index file")?;
+        let index = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to deserialize portal index")?;
+
+        Ok(Some(index))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path()?;
+        let dir = path
+            .parent()
+            .context("Failed to get parent directory of portal index path")?;
+        fs::create_dir_all(dir).context("Failed to create data directory (and parents)")?;
+
+        info!("Saving portal index to {}", path.display());
+
+        // Write to a temporary file first, so that the existing index
+        // survives if writing fails halfway through
+        let temp_path = path.with_extension("json.tmp");
+        let file = File::create(&temp_path).context("Failed to create portal index file")?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self).context("Failed to write portal index to file")?;
+        writer
+            .flush()
+            .context("Failed to write portal index to file")?;
+        fs::rename(&temp_path, &path).context("Failed to replace portal index file")
+    }
+
+    /// Downloads the complete mod list from the portal, removing mods that
+    /// are no longer on it.
+    ///
+    /// Details of mods that did not change are kept.
+    pub fn update_full(&mut self, client: &ApiClient) -> Result<UpdateSummary> {
+        let started_at = Utc::now();
+        let query = SearchQuery {
+            page_size: Some(PageSize::Max),
+            ..Default::default()
+        };
+
+        info!("Downloading complete mod list from the portal");
+        let mut summary = UpdateSummary::default();
+        let mut stale = Vec::new();
+        let mut listed = BTreeSet::new();
+        for result in client.search_all(query) {
+            let result = result.context("Failed to fetch mod list from the portal")?;
+            let name = result.name.clone();
+            listed.insert(name.clone());
+            summary.record(name, self.merge(result), &mut stale);
+        }
+
+        let before = self.mods.len();
+        self.mods.retain(|name, _| listed.contains(name));
+        summary.removed = before - self.mods.len();
+
+        self.refresh_details(client, &stale)?;
+        self.updated_at = Some(started_at);
+        self.full_updated_at = Some(started_at);
+
+        Ok(summary)
+    }
+
+    /// Fetches mods that have been updated since the last update,
+    /// most recently updated first, until reaching mods that did not
+    /// change.
+    ///
+    /// The portal's mod list does not say when a mod was updated, so mods
+    /// are compared to their entry in the index instead. Deleted mods do
+    /// not show up at all, so this falls back to a full update if the
+    /// index is empty or the last full update was a week ago.
+    pub fn update_incremental(&mut self, client: &ApiClient) -> Result<UpdateSummary> {
+        let Some(full_updated_at) = self.full_updated_at.filter(|_| self.updated_at.is_some())
+        else {
+            debug!("Index has no update time, doing a full update");
+            return self.update_full(client);
+        };
+
+        if Utc::now() - full_updated_at > FULL_UPDATE_INTERVAL {
+            debug!(
+                "Last full update was at {}, doing a full update to remove deleted mods",
+                full_updated_at
+            );
+            return self.update_full(client);
+        }
+
+        let started_at = Utc::now();
+        let query = SearchQuery {
+            page_size: Some(PageSize::Custom(INCREMENTAL_PAGE_SIZE)),
+            sort: Some(SortMode::UpdatedAt),
+            sort_order: Some(SortOrder::Descending),
+            ..Default::default()
+        };
+
+        info!("Fetching recently updated mods");
+        let mut summary = UpdateSummary::default();
+        let mut stale = Vec::new();
+        let mut unchanged = 0;
+        for result in client.search_all(query) {
+            let result = result.context("Failed to fetch updated mods from the portal")?;
+
+            // Mods are sorted by when they were last updated, but a single
+            // unchanged mod does not mean the rest are unchanged too: edits
+            // that do not show in the mod list (such as tags) also count.
+            let name = result.name.clone();
+            match self.merge(result) {
+                Change::Unchanged => {
+                    unchanged += 1;
+                    if unchanged >= INCREMENTAL_PAGE_SIZE {
+                        debug!("Reached a page of unchanged mods, stopping");
+                        break;
+                    }
+                }
+                change => {
+                    unchanged = 0;
+                    summary.record(name, change, &mut stale);
+                }
+            }
+        }
+
+        self.refresh_details(client, &stale)?;
+        self.updated_at = Some(started_at);
+
+        Ok(summary)
+    }
+
+    /// Merges a mod from the portal's mod list into the index.
+    ///
+    /// Details of a mod that changed are dropped, as they may be outdated.
+    fn merge(&mut self, mut summary: ModSummary) -> Change {
+        let Some(existing) = self.mods.get_mut(&summary.name) else {
+            self.mods.insert(summary.name.clone(), summary.into());
+            return Change::Added;
+        };
+
+        if is_same_listing(&existing.summary, &summary) {
+            // The mod list has no dependencies, keep those from the details
+            summary.releases = existing.summary.releases.take();
+            existing.summary = summary;
+            return Change::Unchanged;
+        }
+
+        let had_details = existing.tags.is_some();
+        *existing = summary.into();
+        Change::Updated { had_details }
+    }
+
+    /// Fetches details again for mods that changed after they were fetched.
+    fn refresh_details(&mut self, client: &ApiClient, names: &[String]) -> Result<()> {
+        if !names.is_empty() {
+            info!("Refreshing details of {} updated mods", names.len());
+            self.fetch_details(client, names.iter().map(String::as_str))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches full details (such as tags) for the given mods.
+    ///
+    /// The portal's mod listing does not include all details, so this has to
+    /// be done one mod at a time.
+    pub fn fetch_details<'a, I>(&mut self, client: &ApiClient, names: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut count = 0;
+
+        for name in names {
+            match client.info_full(name) {
+                Ok(full) => {
+                    self.mods.insert(name.to_owned(), full.into());
+                    count += 1;
+                }
+                Err(e) => warn!("Failed to fetch details for {}: {}", name, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Searches the index for mods matching the given query.
+    ///
+    /// Every whitespace-separated term in the query has to match at least
+    /// one of the name, title, owner, summary, category or tags of a mod.
+    /// Terms match if they are contained in a field, or if they are similar
+    /// enough to one of the words in it (to allow for typos).
+    ///
+    /// Results are ordered by relevance, with ties broken by download count.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let terms = query
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .collect::<Vec<_>>();
+
+        let mut hits = self
+            .mods
+            .values()
+            .filter_map(|result| {
+                let fields = searchable_fields(result);
+                let mut score = 0.0;
+
+                for term in &terms {
+                    let term_score = fields
+                        .iter()
+                        .map(|(text, weight)| match_term(term, text) * weight)
+                        .sum::<f64>();
+
+                    if term_score == 0.0 {
+                        return None;
+                    }
+
+                    score += term_score;
+                }
+
+                Some(SearchHit { score, result })
+            })
+            .collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(
+                    b.result
+                        .summary
+                        .download_count
+                        .cmp(&a.result.summary.download_count),
+                )
+        });
+
+        hits
+    }
+}
+
+impl UpdateSummary {
+    /// Counts a change to a mod, noting it in `stale` if its details have
+    /// to be fetched again.
+    fn record(&mut self, name: String, change: Change, stale: &mut Vec<String>) {
+        match change {
+            Change::Added => self.added += 1,
+            Change::Updated { had_details } => {
+                self.updated += 1;
+                if had_details {
+                    stale.push(name.clone());
+                }
+            }
+            Change::Unchanged => return,
+        }
+
+        self.changed.push(name);
+    }
+}
+
+impl From<ModSummary> for IndexedMod {
+    fn from(summary: ModSummary) -> Self {
+        Self {
+            summary,
+            tags: None,
+        }
+    }
+}
+
+impl From<ModFull> for IndexedMod {
+    fn from(full: ModFull) -> Self {
+        let tags = full.tags.clone();
+
+        Self {
+            summary: full.into(),
+            tags: Some(tags),
+        }
+    }
+}
+
+/// Whether a mod looks the same in two versions of the portal's mod list.
+///
+/// The download count is ignored, as it changes all the time.
+fn is_same_listing(a: &ModSummary, b: &ModSummary) -> bool {
+    let release = |s: &ModSummary| s.latest().map(|r| (r.version, r.released_at));
+
+    a.owner == b.owner
+        && a.title == b.title
+        && a.summary == b.summary
+        && a.category == b.category
+        && a.thumbnail_path == b.thumbnail_path
+        && release(a) == release(b)
+}
+
+/// Collects the lowercased text of every searchable field of a mod,
+/// along with how much a match in that field is worth.
+fn searchable_fields(result: &IndexedMod) -> Vec<(String, f64)> {
+    let summary = &result.summary;
+    let mut fields = vec![
+        (summary.name.to_lowercase(), 4.0),
+        (summary.owner.to_lowercase(), 2.0),
+        (summary.title.to_lowercase(), 4.0),
+        (summary.summary.to_lowercase(), 1.0),
+        (summary.category.to_string(), 1.5),
+    ];
+
+    if let Some(tags) = &result.tags {
+        for tag in tags {
+            fields.push((tag.to_string(), 2.0));
+        }
+    }
+
+    fields
+}
+
+/// Scores how well a (lowercase) search term matches a (lowercase) text.
+///
+/// Returns `0.0` when there is no match.
+fn match_term(term: &str, text: &str) -> f64 {
+    if text.contains(term) {
+        return 1.0;
+    }
+
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| strsim::jaro_winkler(term, w))
+        .filter(|s| *s >= FUZZY_THRESHOLD)
+        .fold(0.0, f64::max)
+        * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, title: &str, summary: &str, downloads: u32) -> IndexedMod {
+        serde_json::from_value(serde_json::json!({
+            "downloads_count": downloads,
+            "name": name,
+            "owner": "someone",
+            "title": title,
+            "summary": summary,
+        }))
+        .unwrap()
+    }
+
+    fn index(results: Vec<IndexedMod>) -> PortalIndex {
+        PortalIndex {
+            updated_at: None,
+            full_updated_at: None,
+            mods: results
+                .into_iter()
+                .map(|r| (r.summary.name.clone(), r))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_search_matches_substring() {
+        let index = index(vec![
+            result("cybersyn", "Project Cybersyn", "Train dispatcher", 10),
+            result("foo", "Foo", "Bar", 10),
+        ]);
+        let hits = index.search("train");
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].result.summary.name, "cybersyn");
+    }
+
+    #[test]
+    fn test_search_matches_typos() {
+        let index = index(vec![result("ltn", "LTN", "Logistic train network", 10)]);
+
+        assert_eq!(index.search("logistik").len(), 1);
+    }
+
+    #[test]
+    fn test_search_requires_all_terms() {
+        let index = index(vec![
+            result("a", "Trains", "Better trains", 10),
+            result("b", "Train signals", "More signals", 10),
+        ]);
+        let hits = index.search("train signals");
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].result.summary.name, "b");
+    }
+
+    #[test]
+    fn test_search_ranks_by_relevance_then_downloads() {
+        let index = index(vec![
+            result("a", "Something", "Has trains", 1000),
+            result("b", "Trains", "Has trains", 10),
+            result("c", "Other", "Has trains", 5000),
+        ]);
+        let names = index
+            .search("trains")
+            .iter()
+            .map(|h| h.result.summary.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_merge_keeps_details_of_unchanged_mods() {
+        let mut tagged = result("ltn", "LTN", "Logistic train network", 10);
+        tagged.tags = Some(vec![Tag::Trains]);
+        tagged.summary.releases = Some(Vec::new());
+        let mut index = index(vec![tagged]);
+
+        let listed = result("ltn", "LTN", "Logistic train network", 20).summary;
+        assert!(matches!(index.merge(listed), Change::Unchanged));
+        assert_eq!(index.mods["ltn"].summary.download_count, 20);
+        assert_eq!(index.mods["ltn"].tags, Some(vec![Tag::Trains]));
+        assert!(index.mods["ltn"].summary.releases.is_some());
+
+        let listed = result("ltn", "LTN", "Logistic train network 2", 20).summary;
+        assert!(matches!(
+            index.merge(listed),
+            Change::Updated { had_details: true }
+        ));
+        assert_eq!(index.mods["ltn"].tags, None);
+    }
+}
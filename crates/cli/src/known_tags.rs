@@ -0,0 +1,106 @@
+//! Categories and tags seen on the mod portal that this version of facti
+//! was not released with.
+//!
+//! The portal has no list of its categories and tags, so they are learned
+//! from the mods in the local portal index whenever it is updated.
+
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::BufReader,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use facti_api::data::detail::{Category, Tag};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::{dirs, index::PortalIndex};
+
+const KNOWN_TAGS_FILENAME: &str = "known-tags.json";
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownTags {
+    pub categories: BTreeSet<String>,
+    pub tags: BTreeSet<String>,
+}
+
+impl KnownTags {
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(dirs::data()?.join(KNOWN_TAGS_FILENAME))
+    }
+
+    /// Loads the learned categories and tags, which are empty if none
+    /// have been learned yet.
+    pub fn load() -> Result<Self> {
+        let path = Self::default_path()?;
+
+        if !path.exists() {
+            debug!("No known tags exist at {}", path.display());
+            return Ok(Self::default());
+        }
+
+        let file = File::open(&path).context("Failed to open known tags file")?;
+        serde_json::from_reader(BufReader::new(file)).context("Failed to deserialize known tags")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path()?;
+        let dir = path
+            .parent()
+            .context("Failed to get parent directory of known tags path")?;
+        fs::create_dir_all(dir).context("Failed to create data directory (and parents)")?;
+
+        info!("Saving known tags to {}", path.display());
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).context("Failed to write known tags to file")
+    }
+
+    /// Adds the categories and tags used by the mods in the index that
+    /// facti was not released with, returning whether any were new.
+    pub fn learn(&mut self, index: &PortalIndex) -> bool {
+        let mut learned = false;
+
+        for result in index.mods.values() {
+            let category = &result.summary.category;
+            if !category.is_known() {
+                learned |= self.categories.insert(category.to_string());
+            }
+
+            for tag in result.tags.iter().flatten().filter(|t| !t.is_known()) {
+                learned |= self.tags.insert(tag.to_string());
+            }
+        }
+
+        learned
+    }
+
+    pub fn is_known_category(&self, category: &Category) -> bool {
+        category.is_known() || self.categories.contains(&category.to_string())
+    }
+
+    pub fn is_known_tag(&self, tag: &Tag) -> bool {
+        tag.is_known() || self.tags.contains(&tag.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_known() {
+        let known_tags = KnownTags {
+            categories: BTreeSet::from(["space-age".to_owned()]),
+            tags: BTreeSet::from(["planets".to_owned()]),
+        };
+
+        assert!(known_tags.is_known_category(&Category::Content));
+        assert!(known_tags.is_known_category(&Category::Unknown("space-age".to_owned())));
+        assert!(!known_tags.is_known_category(&Category::Unknown("typo".to_owned())));
+        assert!(known_tags.is_known_tag(&Tag::Trains));
+        assert!(known_tags.is_known_tag(&Tag::Unknown("planets".to_owned())));
+        assert!(!known_tags.is_known_tag(&Tag::Unknown("typo".to_owned())));
+    }
+}
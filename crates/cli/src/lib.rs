@@ -7,9 +7,10 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{self, Read, Write},
     path::Path,
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
@@ -25,7 +26,13 @@ use self::{
 mod cli;
 mod config;
 mod dirs;
+mod image;
+mod index;
+mod known_tags;
 mod logging;
+mod modpack;
+mod mods;
+mod progress;
 mod project;
 mod vcs;
 
@@ -71,6 +78,20 @@ pub fn run() -> Result<Option<LogState>> {
         config.factorio_api.portal_base_url.to_owned()
     };
 
+    // The API lives under the portal, so follow the portal unless told otherwise
+    let portal_api_base_url = if let Some(url) = &cli.portal_api_base_url {
+        Some(url.to_owned())
+    } else if let Some(url) = &config.factorio_api.portal_api_base_url {
+        Some(url.to_owned())
+    } else if let Some(url) = &portal_base_url {
+        Some(
+            url.join("api/")
+                .context("Failed to derive portal API base URL")?,
+        )
+    } else {
+        None
+    };
+
     let game_base_url = if let Some(url) = &cli.game_base_url {
         Some(url.to_owned())
     } else {
@@ -85,6 +106,10 @@ pub fn run() -> Result<Option<LogState>> {
         api_builder.portal_base_url(base_url);
     }
 
+    if let Some(base_url) = portal_api_base_url {
+        api_builder.portal_api_base_url(base_url);
+    }
+
     if let Some(base_url) = game_base_url {
         api_builder.game_base_url(base_url);
     }
@@ -93,13 +118,31 @@ pub fn run() -> Result<Option<LogState>> {
         api_builder.api_key(api_key);
     }
 
-    let api_client = api_builder.build();
+    let username = cli
+        .username
+        .as_ref()
+        .or(config.factorio_api.username.as_ref());
+    let token = cli.token.as_ref().or(config.factorio_api.token.as_ref());
+    if let (Some(username), Some(token)) = (username, token) {
+        api_builder.credentials(username, token);
+    }
+
+    configure_http(&mut api_builder, &cli, &config)?;
+
+    let api_client = api_builder.build().context("Failed to set up API client")?;
 
     match cli.command {
-        cli::Commands::Portal(portal) => portal.run(&api_client),
-        cli::Commands::New(new) => new.run(&config),
+        cli::Commands::Portal(portal) => portal.run(&api_client, !cli.verbose.is_quiet()),
+        cli::Commands::Game(game) => game.run(&api_client),
+        cli::Commands::New(new) => new.run(&config, &api_client),
         cli::Commands::Dist(dist) => dist.run(),
-        cli::Commands::Changelog(changelog) => changelog.run(),
+        cli::Commands::Changelog(changelog) => changelog.run(&api_client),
+        cli::Commands::Port(port) => port.run(&api_client),
+        cli::Commands::Deps(deps) => deps.run(&api_client),
+        cli::Commands::Mods(mods) => mods.run(),
+        cli::Commands::Pack(pack) => pack.run(&api_client),
+        cli::Commands::Save(save) => save.run(&api_client),
+        cli::Commands::Blueprint(blueprint) => blueprint.run(),
         cli::Commands::Completion(completion) => completion.run(),
 
         #[cfg(debug_assertions)]
@@ -111,6 +154,53 @@ pub fn run() -> Result<Option<LogState>> {
     Ok(log_guard)
 }
 
+/// Applies the proxy, certificate, timeout and User-Agent settings,
+/// with command line options taking precedence over the config.
+fn configure_http(
+    builder: &mut facti_api::blocking::ApiClientBuilder,
+    cli: &Cli,
+    config: &Config,
+) -> Result<()> {
+    let api_config = &config.factorio_api;
+
+    if let Some(proxy) = cli.proxy.as_ref().or(api_config.proxy.as_ref()) {
+        builder.proxy(proxy.to_owned());
+    }
+
+    for path in api_config.ca_certificates.iter().chain(&cli.ca_certs) {
+        let pem = fs::read(path)
+            .with_context(|| format!("Failed to read CA certificate {}", path.display()))?;
+        builder.root_certificate(pem);
+    }
+
+    if let Some(seconds) = cli.connect_timeout.or(api_config.connect_timeout) {
+        builder.connect_timeout(Duration::from_secs(seconds));
+    }
+
+    if let Some(seconds) = cli.read_timeout.or(api_config.read_timeout) {
+        builder.read_timeout(Duration::from_secs(seconds));
+    }
+
+    let contact = cli
+        .user_agent_contact
+        .as_ref()
+        .or(api_config.user_agent_contact.as_ref());
+    builder.user_agent(user_agent(contact.map(String::as_str)));
+
+    Ok(())
+}
+
+/// Builds the User-Agent to identify facti with, e.g.
+/// `facti/0.3.0 (+mailto:me@example.com)`.
+fn user_agent(contact: Option<&str>) -> String {
+    let name_version = concat!("facti/", env!("CARGO_PKG_VERSION"));
+
+    match contact {
+        Some(contact) => format!("{} (+{})", name_version, contact),
+        None => name_version.to_owned(),
+    }
+}
+
 fn resolve_api_key(cli: &Cli, config: &Config) -> Result<Option<String>> {
     if let Some(api_key) = resolve_cli_api_key(cli)? {
         Ok(Some(api_key))
@@ -0,0 +1,275 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use facti_api::data::portal::{Release, ReleaseInfo};
+use facti_lib::{
+    FactorioVersion, ModInfo,
+    version::{Version, VersionReq},
+};
+use serde::{Deserialize, Serialize};
+
+/// Name of the file describing a modpack.
+pub const MANIFEST_FILENAME: &str = "modpack.toml";
+
+/// Name of the file with the exact releases a modpack resolved to.
+pub const LOCK_FILENAME: &str = "modpack.lock";
+
+/// Name of the manifest inside a modpack archive.
+pub const ARCHIVE_MANIFEST_FILENAME: &str = "modpack.json";
+
+/// Directory inside a modpack archive that holds the mod zips.
+pub const ARCHIVE_MODS_DIR: &str = "mods";
+
+/// Version of the modpack archive format, increased on incompatible changes.
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+const LOCK_HEADER: &str =
+    "# This file is generated by `facti pack lock`, do not edit it by hand.\n\n";
+
+/// The `modpack.toml` manifest, listing the mods of a modpack.
+///
+/// ```toml
+/// name = "Server pack"
+/// factorio_version = "2.0"
+///
+/// [mods]
+/// space-age = ""
+/// flib = ">= 0.15.0"
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Name of the modpack, only used for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The version of Factorio the modpack targets.
+    pub factorio_version: FactorioVersion,
+
+    /// Mods in the modpack, with their version requirement
+    /// (an empty string accepts any version).
+    #[serde(default)]
+    pub mods: BTreeMap<String, VersionReq>,
+}
+
+/// The `modpack.lock` file, with the exact releases of every mod in
+/// a modpack, including the mods they depend on.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub factorio_version: FactorioVersion,
+
+    /// Built-in mods (such as expansions) that are part of the modpack.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub built_in: Vec<String>,
+
+    #[serde(default, rename = "mod")]
+    pub mods: Vec<LockedMod>,
+}
+
+/// A release of a mod in the lockfile.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LockedMod {
+    pub name: String,
+    pub version: Version,
+    pub file_name: String,
+    pub sha1: String,
+    pub download_path: String,
+    pub released_at: DateTime<Utc>,
+}
+
+/// The manifest of a modpack archive created by `facti pack export`,
+/// describing its contents.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// Version of the archive format, see [`ARCHIVE_FORMAT`].
+    pub format: u32,
+
+    /// The version of Factorio the modpack targets, if exported from
+    /// a lockfile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factorio_version: Option<FactorioVersion>,
+
+    pub mods: Vec<ArchivedMod>,
+
+    /// Other files in the archive (such as `mod-list.json`), with their
+    /// SHA-1 checksums.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+/// A mod zip in a modpack archive, stored in [`ARCHIVE_MODS_DIR`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedMod {
+    pub name: String,
+    pub version: Version,
+    pub title: String,
+    pub author: String,
+    pub factorio_version: FactorioVersion,
+    pub file_name: String,
+    pub sha1: String,
+}
+
+impl ArchivedMod {
+    pub fn new(info: &ModInfo, file_name: String, sha1: String) -> Self {
+        Self {
+            name: info.name.clone(),
+            version: info.version,
+            title: info.title.clone(),
+            author: info.author.clone(),
+            factorio_version: info.factorio_version,
+            file_name,
+            sha1,
+        }
+    }
+
+    /// Path of the mod zip inside the archive.
+    pub fn archive_path(&self) -> String {
+        format!("{}/{}", ARCHIVE_MODS_DIR, self.file_name)
+    }
+}
+
+impl Manifest {
+    /// Reads `modpack.toml` from a modpack directory.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILENAME);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+impl Lockfile {
+    /// Reads `modpack.lock` from a modpack directory.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(LOCK_FILENAME);
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read {}, create it with `facti pack lock`",
+                path.display()
+            )
+        })?;
+
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Writes `modpack.lock` to a modpack directory.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(LOCK_FILENAME);
+        let content = toml::to_string_pretty(self).context("Failed to serialize lockfile")?;
+
+        fs::write(&path, format!("{}{}", LOCK_HEADER, content))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Whether the lockfile contains every mod of the manifest, in versions
+    /// that still satisfy its requirements.
+    pub fn is_up_to_date(&self, manifest: &Manifest) -> bool {
+        self.factorio_version == manifest.factorio_version
+            && manifest.mods.iter().all(|(name, req)| {
+                self.built_in.contains(name)
+                    || self.mods.iter().any(|m| {
+                        m.name == *name
+                            && match req {
+                                VersionReq::Latest => true,
+                                VersionReq::Spec(spec) => spec.matches(m.version),
+                            }
+                    })
+            })
+    }
+}
+
+impl LockedMod {
+    pub fn new(name: &str, release: &Release) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: release.version,
+            file_name: release.filename.clone(),
+            sha1: release.sha1.clone(),
+            download_path: release.download_path.clone(),
+            released_at: release.released_at,
+        }
+    }
+
+    /// The release on the portal, for downloading it.
+    pub fn release(&self, factorio_version: FactorioVersion) -> Release {
+        Release {
+            download_path: self.download_path.clone(),
+            filename: self.file_name.clone(),
+            info: ReleaseInfo {
+                factorio_version,
+                dependencies: None,
+            },
+            released_at: self.released_at,
+            version: self.version,
+            sha1: self.sha1.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest: Manifest = toml::from_str(indoc! {r#"
+            name = "Server pack"
+            factorio_version = "2.0"
+
+            [mods]
+            space-age = ""
+            flib = ">= 0.15.0"
+        "#})
+        .unwrap();
+
+        assert_eq!(manifest.factorio_version, FactorioVersion::new(2, 0));
+        assert_eq!(manifest.mods["space-age"], VersionReq::Latest);
+        assert_eq!(
+            manifest.mods["flib"],
+            VersionReq::parse(">= 0.15.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manifest_rejects_unknown_fields() {
+        let result = toml::from_str::<Manifest>(indoc! {r#"
+            factorio_version = "2.0"
+            mod = { flib = "" }
+        "#});
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_up_to_date() {
+        let manifest: Manifest = toml::from_str(indoc! {r#"
+            factorio_version = "2.0"
+
+            [mods]
+            quality = ""
+            flib = ">= 0.15.0"
+        "#})
+        .unwrap();
+        let mut lockfile = Lockfile {
+            factorio_version: FactorioVersion::new(2, 0),
+            built_in: vec!["quality".to_owned()],
+            mods: vec![LockedMod {
+                name: "flib".to_owned(),
+                version: Version::new(0, 15, 1),
+                file_name: "flib_0.15.1.zip".to_owned(),
+                sha1: "abc".to_owned(),
+                download_path: "/download/flib/abc".to_owned(),
+                released_at: DateTime::UNIX_EPOCH,
+            }],
+        };
+
+        assert!(lockfile.is_up_to_date(&manifest));
+
+        lockfile.mods[0].version = Version::new(0, 14, 0);
+        assert!(!lockfile.is_up_to_date(&manifest));
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use facti_api::{blocking::ApiClient, data::portal::Release};
+use facti_lib::{ModInfo, version::Version};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use zip::ZipArchive;
+
+/// Name of the file Factorio keeps track of enabled mods in.
+pub const MOD_LIST_FILENAME: &str = "mod-list.json";
+
+/// Name of the file Factorio stores the startup and map settings of mods in.
+pub const MOD_SETTINGS_FILENAME: &str = "mod-settings.dat";
+
+/// Mods that ship with the game, and are versioned along with it.
+pub const BUILT_IN_MODS: &[&str] = &["base", "core", "elevated-rails", "quality", "space-age"];
+
+/// Built-in mods required by other built-in mods (besides `base`), which
+/// Factorio refuses to load without them.
+const BUILT_IN_DEPENDENCIES: &[(&str, &[&str])] = &[("space-age", &["elevated-rails", "quality"])];
+
+/// The built-in mods a built-in mod requires, other than `base`.
+pub fn built_in_dependencies(name: &str) -> &'static [&'static str] {
+    BUILT_IN_DEPENDENCIES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map_or(&[], |(_, dependencies)| dependencies)
+}
+
+/// A Factorio mods folder.
+#[derive(Debug)]
+pub struct ModsFolder {
+    /// Mods in the folder, by name.
+    ///
+    /// If there are multiple versions of a mod, only the latest one is kept,
+    /// as that is the one Factorio loads.
+    pub mods: BTreeMap<String, InstalledMod>,
+
+    /// Contents of `mod-list.json`, if it exists.
+    pub mod_list: Option<ModList>,
+}
+
+/// A mod in a mods folder.
+#[derive(Debug)]
+pub struct InstalledMod {
+    pub info: ModInfo,
+
+    /// Path to the zip or directory of the mod.
+    pub path: PathBuf,
+}
+
+/// The `mod-list.json` file in a mods folder.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModList {
+    pub mods: Vec<ModListEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModListEntry {
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl ModsFolder {
+    /// Reads the `info.json` of every mod in a mods folder.
+    ///
+    /// Files and directories that are not mods are skipped.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_dir() {
+            bail!("{} is not a directory", path.display());
+        }
+
+        let mut mods = BTreeMap::<String, InstalledMod>::new();
+        let entries =
+            fs::read_dir(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        for entry in entries {
+            let entry_path = entry?.path();
+            let info = match read_info(&entry_path) {
+                Ok(Some(info)) => info,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Skipping {}: {:#}", entry_path.display(), e);
+                    continue;
+                }
+            };
+
+            debug!(
+                "Found {} {} at {}",
+                info.name,
+                info.version,
+                entry_path.display()
+            );
+            if mods
+                .get(&info.name)
+                .is_some_and(|existing| existing.info.version >= info.version)
+            {
+                continue;
+            }
+
+            mods.insert(
+                info.name.clone(),
+                InstalledMod {
+                    info,
+                    path: entry_path,
+                },
+            );
+        }
+
+        let mod_list_path = path.join(MOD_LIST_FILENAME);
+        let mod_list = if mod_list_path.exists() {
+            let file = File::open(&mod_list_path)
+                .with_context(|| format!("Failed to open {}", mod_list_path.display()))?;
+            Some(
+                serde_json::from_reader(BufReader::new(file))
+                    .with_context(|| format!("Failed to parse {}", mod_list_path.display()))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self { mods, mod_list })
+    }
+
+    /// Whether a mod is enabled according to `mod-list.json`.
+    ///
+    /// Mods missing from the list (or if there is no list) are enabled,
+    /// as Factorio enables new mods it finds.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.mod_list
+            .as_ref()
+            .and_then(|list| list.mods.iter().find(|m| m.name == name))
+            .is_none_or(|m| m.enabled)
+    }
+}
+
+impl ModList {
+    /// Builds a list of the built-in mods (except `core`, which is always
+    /// loaded) and the mods in `names`, enabling the ones `enabled` returns
+    /// `true` for.
+    ///
+    /// `base` comes first and the other mods are sorted by name, like in the
+    /// lists Factorio writes.
+    pub fn new<I, F>(names: I, enabled: F) -> Self
+    where
+        I: IntoIterator<Item = String>,
+        F: Fn(&str) -> bool,
+    {
+        let mut names = BUILT_IN_MODS
+            .iter()
+            .filter(|n| **n != "core")
+            .map(|n| n.to_string())
+            .chain(names)
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| (a != "base").cmp(&(b != "base")).then_with(|| a.cmp(b)));
+        names.dedup();
+
+        Self {
+            mods: names
+                .into_iter()
+                .map(|name| ModListEntry {
+                    enabled: enabled(&name),
+                    name,
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the list to `mod-list.json` in a mods folder.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MOD_LIST_FILENAME);
+        let file =
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, self)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        writeln!(writer)?;
+
+        Ok(())
+    }
+}
+
+/// Downloads a release into a mods folder, verifying it against the
+/// checksum from the portal, and returns the path of the written zip.
+pub fn download(client: &ApiClient, name: &str, release: &Release, dir: &Path) -> Result<PathBuf> {
+    let data = fetch(client, name, release)?;
+
+    let path = dir.join(&release.filename);
+    fs::write(&path, data).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Downloads the zip of a release, verifying it against the checksum from
+/// the portal.
+pub fn fetch(client: &ApiClient, name: &str, release: &Release) -> Result<Vec<u8>> {
+    info!("Downloading {} {}", name, release.version);
+    let data = client
+        .download_release(release)
+        .with_context(|| format!("Failed to download {} {}", name, release.version))?;
+
+    let sha1 = sha1_hex(&data);
+    if !sha1.eq_ignore_ascii_case(&release.sha1) {
+        bail!(
+            "Checksum mismatch for {} {}: expected {}, got {}",
+            name,
+            release.version,
+            release.sha1,
+            sha1
+        );
+    }
+
+    Ok(data)
+}
+
+/// Removes the zips of every version of a mod other than `keep` from
+/// a mods folder, as Factorio would load the latest one.
+pub fn remove_other_versions(dir: &Path, name: &str, keep: Version) -> Result<()> {
+    let prefix = format!("{}_", name);
+
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        let version = file_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".zip"))
+            .and_then(|version| Version::parse(version).ok());
+        if version.is_some_and(|v| v != keep) {
+            info!("Removing {}", path.display());
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the file at `path` exists and has the given SHA-1 checksum.
+pub fn has_checksum(path: &Path, sha1: &str) -> Result<bool> {
+    if !path.is_file() {
+        return Ok(false);
+    }
+
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    Ok(sha1_hex(&data).eq_ignore_ascii_case(sha1))
+}
+
+/// Computes the SHA-1 checksum of some data as a lowercase hex string,
+/// as used by the portal to identify releases.
+pub fn sha1_hex(data: &[u8]) -> String {
+    sha1_smol::Sha1::from(data).digest().to_string()
+}
+
+/// Like [`sha1_hex`], reading the data from `reader` in chunks.
+pub fn sha1_hex_reader<R: Read>(mut reader: R) -> io::Result<String> {
+    let mut sha1 = sha1_smol::Sha1::new();
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => return Ok(sha1.digest().to_string()),
+            read => sha1.update(&buffer[..read]),
+        }
+    }
+}
+
+/// Reads the `info.json` of a mod zip or directory, returning [`None`] if
+/// the path does not look like a mod.
+fn read_info(path: &Path) -> Result<Option<ModInfo>> {
+    if path.is_dir() {
+        let info_path = path.join("info.json");
+        if !info_path.is_file() {
+            return Ok(None);
+        }
+
+        let file = File::open(&info_path)?;
+        return Ok(Some(serde_json::from_reader(BufReader::new(file))?));
+    }
+
+    if path.extension().is_none_or(|e| e != "zip") {
+        return Ok(None);
+    }
+
+    read_zip_info(BufReader::new(File::open(path)?)).map(Some)
+}
+
+/// Reads the `info.json` from the top-level directory of a mod zip.
+pub fn read_zip_info<R: Read + Seek>(reader: R) -> Result<ModInfo> {
+    let mut archive = ZipArchive::new(reader)?;
+    let Some(index) =
+        (0..archive.len()).find(|i| archive.name_for_index(*i).is_some_and(is_top_level_info))
+    else {
+        bail!("No info.json in the top-level directory of the zip");
+    };
+
+    let mut content = String::new();
+    archive.by_index(index)?.read_to_string(&mut content)?;
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Whether a path in a mod zip is the `info.json` inside its top-level directory.
+fn is_top_level_info(name: &str) -> bool {
+    let mut parts = name.split('/');
+    matches!(
+        (parts.next(), parts.next(), parts.next()),
+        (Some(_), Some("info.json"), None)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_top_level_info() {
+        assert!(is_top_level_info("my-mod_1.0.0/info.json"));
+        assert!(!is_top_level_info("info.json"));
+        assert!(!is_top_level_info("my-mod_1.0.0/sub/info.json"));
+    }
+
+    #[test]
+    fn test_sha1_hex() {
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            sha1_hex_reader(&b"abc"[..]).unwrap(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Minimal progress bar for long-running transfers, drawn on stderr.
+
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{Arc, Mutex},
+};
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// A progress bar reporting bytes transferred out of a known total.
+///
+/// When disabled, all updates are ignored. This is used when the user asked
+/// for quiet output or stderr is not a terminal, so that logs and redirected
+/// output are not cluttered with redraws.
+#[derive(Clone)]
+pub struct ProgressBar {
+    label: String,
+    state: Option<Arc<Mutex<State>>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Last drawn percentage, to avoid redrawing on every chunk.
+    percent: Option<u64>,
+    finished: bool,
+}
+
+impl ProgressBar {
+    pub fn new<T: Into<String>>(label: T, enabled: bool) -> Self {
+        Self {
+            label: label.into(),
+            state: (enabled && io::stderr().is_terminal()).then(Default::default),
+        }
+    }
+
+    /// Returns a callback to pass to the API client upload methods.
+    pub fn callback(&self) -> impl Fn(u64, u64) + Send + Sync + 'static {
+        let bar = self.clone();
+        move |sent, total| bar.update(sent, total)
+    }
+
+    pub fn update(&self, sent: u64, total: u64) {
+        let Some(state) = &self.state else {
+            return;
+        };
+
+        let mut state = state.lock().unwrap();
+        let percent = (sent * 100).checked_div(total).unwrap_or(100).min(100);
+
+        if state.finished || state.percent == Some(percent) {
+            return;
+        }
+
+        state.percent = Some(percent);
+        let filled = BAR_WIDTH * percent as usize / 100;
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r{} [{}{}] {:>3}% {} / {}",
+            self.label,
+            "=".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            percent,
+            format_bytes(sent),
+            format_bytes(total)
+        );
+        let _ = stderr.flush();
+    }
+
+    /// Ends the bar, moving further output to the next line.
+    pub fn finish(&self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+
+        let mut state = state.lock().unwrap();
+        if !state.finished && state.percent.is_some() {
+            let _ = writeln!(io::stderr());
+        }
+        state.finished = true;
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(50 * 1024 * 1024), "50.0 MiB");
+    }
+}
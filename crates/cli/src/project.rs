@@ -1,11 +1,13 @@
 use std::{
-    fs::File,
+    fs::{self, File},
     io::BufReader,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result, bail};
 use facti_lib::ModInfo;
+use serde::Serialize;
+use serde_json::{Map, Value, ser::PrettyFormatter};
 use tracing::{debug, error};
 
 use crate::vcs::{self, Vcs};
@@ -53,6 +55,53 @@ impl Project {
     pub fn dist_path(&self) -> PathBuf {
         self.path.join("dist")
     }
+
+    /// Edits the fields of `info.json` in place, leaving the ones `edit`
+    /// does not touch as they are.
+    ///
+    /// The order of the fields, the indentation and the trailing newline
+    /// of the file are kept, so that only the edited fields show up in a
+    /// diff. [`mod_info`][Self::mod_info] is not updated.
+    pub fn update_info<F>(&self, edit: F) -> Result<()>
+    where
+        F: FnOnce(&mut Map<String, Value>) -> Result<()>,
+    {
+        let path = &self.mod_info_path;
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let Some(object) = value.as_object_mut() else {
+            bail!("{} does not contain a JSON object", path.display());
+        };
+        edit(object)?;
+
+        let formatter = PrettyFormatter::with_indent(indentation(&content).as_bytes());
+        let mut output = Vec::new();
+        value.serialize(&mut serde_json::Serializer::with_formatter(
+            &mut output,
+            formatter,
+        ))?;
+        if content.ends_with('\n') {
+            output.push(b'\n');
+        }
+
+        fs::write(path, output).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Finds the indentation used in a JSON document, defaulting to two spaces
+/// if it is not indented at all.
+fn indentation(content: &str) -> &str {
+    content
+        .lines()
+        .find_map(|line| {
+            let trimmed = line.trim_start();
+            (!trimmed.is_empty() && trimmed.len() < line.len())
+                .then(|| &line[..line.len() - trimmed.len()])
+        })
+        .unwrap_or("  ")
 }
 
 fn resolve_paths(path: &Path) -> Result<(PathBuf, PathBuf)> {
@@ -95,3 +144,33 @@ fn resolve_paths(path: &Path) -> Result<(PathBuf, PathBuf)> {
 fn path_has_infojson(path: &Path) -> bool {
     path.join("info.json").exists()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_info_keeps_formatting() {
+        let dir = std::env::temp_dir().join(format!("facti-project-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("info.json"),
+            "{\n\t\"name\": \"my-mod\",\n\t\"version\": \"1.0.0\",\n\t\"title\": \"My Mod\",\n\t\"author\": \"someone\"\n}",
+        )
+        .unwrap();
+        let project = Project::load(&dir).unwrap();
+
+        project
+            .update_info(|info| {
+                info.insert("version".to_owned(), "1.1.0".into());
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("info.json")).unwrap(),
+            "{\n\t\"name\": \"my-mod\",\n\t\"version\": \"1.1.0\",\n\t\"title\": \"My Mod\",\n\t\"author\": \"someone\"\n}"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,12 +1,75 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::Vcs;
 use anyhow::{Context, Result, bail};
-use git2::Repository;
+use chrono::{DateTime, Utc};
+use git2::{IndexAddOption, Repository, Signature, Time};
 use tracing::{debug, info};
 
 pub struct Git;
 
+impl Git {
+    /// Commits the entire working tree of the repository at `path`,
+    /// including deleted files, as if it was authored by `name` and `email`
+    /// at `time`.
+    pub fn commit_all(
+        path: &Path,
+        message: &str,
+        name: &str,
+        email: &str,
+        time: DateTime<Utc>,
+    ) -> Result<()> {
+        let repo = Repository::open(path)
+            .with_context(|| format!("Failed to open Git repository at {}", path.display()))?;
+
+        let mut index = repo.index().context("Failed to get Git index")?;
+        index
+            .add_all(["*"], IndexAddOption::DEFAULT, None)
+            .context("Failed to add files to Git index")?;
+        index
+            .update_all(["*"], None)
+            .context("Failed to update Git index")?;
+        index.write().context("Failed to write Git index")?;
+
+        let tree_id = index.write_tree().context("Failed to write Git tree")?;
+        let tree = repo.find_tree(tree_id)?;
+
+        let parent = match repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let signature = Signature::new(name, email, &Time::new(time.timestamp(), 0))
+            .context("Failed to create Git signature")?;
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            parent.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+        )
+        .context("Failed to create Git commit")?;
+
+        debug!("Committed \"{}\" in {}", message, path.display());
+
+        Ok(())
+    }
+
+    /// Creates a lightweight tag pointing at `HEAD`.
+    pub fn tag(path: &Path, name: &str) -> Result<()> {
+        let repo = Repository::open(path)
+            .with_context(|| format!("Failed to open Git repository at {}", path.display()))?;
+        let head = repo.head()?.peel(git2::ObjectType::Commit)?;
+
+        repo.tag_lightweight(name, &head, false)
+            .with_context(|| format!("Failed to create Git tag {}", name))?;
+
+        Ok(())
+    }
+}
+
 impl Vcs for Git {
     fn init(path: &std::path::Path) -> Result<()> {
         if let Some(repo_path) =
@@ -0,0 +1,113 @@
+use facti_lib::blueprint;
+use facti_mock_portal::MockPortal;
+use serde_json::Value;
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+const BELT: &str = "0eNp1UNuKwjAQ/Zd5TsXUttI87m+ISFqH3UA6KcnorpT8uxMLisi+JWfm3GaBwV9wjo4YzAJuDJTAHBZI7pusLxjZCcEAR0tpDpGrAT1DVuDojH9gdD4qQGLHDlfu43M70WUaMMqC+kdDwRyS0AIVH5HabloFNzCV3rRicHYRx3XcZPWhWz91S2q2xNUYpsGR5RA/xet3ceFwDP404I+9OiHIVlrt0vtbGr2q5lzq/kqyMjlopVWt9FEwxzhJltc9FXgrPQX7WtteMaZHnrar+6bv22a777r9Luc7Dr2CBQ==";
+
+fn stdout(output: std::process::Output) -> String {
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Decodes a blueprint string to a JSON value, without going through
+/// the typed structures.
+fn raw(string: &str) -> Value {
+    serde_json::from_str(&blueprint::decode_json(string).unwrap()).unwrap()
+}
+
+fn roundtrip(test: &str, format: &str) {
+    let portal = MockPortal::start().unwrap();
+    let input = temp_file(test, "belt.txt", BELT.as_bytes());
+    let decoded = temp_file(test, "belt.decoded", b"");
+
+    stdout(facti(
+        &portal,
+        &[
+            "blueprint",
+            "decode",
+            input.to_str().unwrap(),
+            "--to",
+            format,
+            "--output",
+            decoded.to_str().unwrap(),
+        ],
+    ));
+    let encoded = stdout(facti(
+        &portal,
+        &[
+            "blueprint",
+            "encode",
+            decoded.to_str().unwrap(),
+            "--from",
+            format,
+        ],
+    ));
+
+    assert!(encoded.starts_with('0'));
+    assert_eq!(raw(&encoded), raw(BELT));
+}
+
+#[test]
+fn test_decode() {
+    let portal = MockPortal::start().unwrap();
+    let input = temp_file("blueprint-decode", "belt.txt", BELT.as_bytes());
+
+    let decoded = json(facti(
+        &portal,
+        &["blueprint", "decode", input.to_str().unwrap()],
+    ));
+
+    assert_eq!(decoded, raw(BELT));
+    assert_eq!(decoded["blueprint"]["label"], "Belt");
+}
+
+#[test]
+fn test_roundtrip_json() {
+    roundtrip("blueprint-json", "json");
+}
+
+#[test]
+fn test_roundtrip_toml() {
+    roundtrip("blueprint-toml", "toml");
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_roundtrip_yaml() {
+    roundtrip("blueprint-yaml", "yaml");
+}
+
+#[test]
+fn test_validate() {
+    let portal = MockPortal::start().unwrap();
+    let valid = temp_file("blueprint-validate", "belt.txt", BELT.as_bytes());
+    let invalid = temp_file("blueprint-validate", "broken.txt", b"0not a blueprint");
+
+    let output = stdout(facti(
+        &portal,
+        &["blueprint", "validate", valid.to_str().unwrap()],
+    ));
+    assert!(output.contains("blueprint \"Belt\" (Factorio 2.0.10)"));
+
+    let output = facti(
+        &portal,
+        &[
+            "blueprint",
+            "validate",
+            valid.to_str().unwrap(),
+            invalid.to_str().unwrap(),
+        ],
+    );
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("broken.txt: invalid"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 of 2"));
+}
@@ -0,0 +1,62 @@
+use facti_lib::{ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+const RELEASED: &str = "\
+---------------------------------------------------------------------------------------------------
+Version: 1.0.0
+  Features:
+    - Initial release
+";
+
+const LOCAL: &str = "\
+---------------------------------------------------------------------------------------------------
+Version: 1.0.0
+  Features:
+    - First release
+";
+
+#[test]
+fn test_diff_portal() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let info = ModInfo::builder("alpha", Version::new(1, 0, 0), "Alpha", "someone").build();
+        let mut store = portal.store();
+        store.publish(&mod_zip(&info)).unwrap();
+        let info = ModInfo::builder("alpha", Version::new(1, 1, 0), "Alpha", "someone").build();
+        store.upload("alpha", &mod_zip(&info)).unwrap();
+        store.get_mut("alpha").unwrap().changelog = Some(RELEASED.to_owned());
+    }
+
+    let info = ModInfo::builder("alpha", Version::new(1, 1, 0), "Alpha", "someone").build();
+    let info_path = temp_file(
+        "changelog-diff",
+        "info.json",
+        &serde_json::to_vec(&info).unwrap(),
+    );
+    let dir = info_path.parent().unwrap();
+    std::fs::write(dir.join("changelog.txt"), LOCAL).unwrap();
+
+    let diff = json(facti(
+        &portal,
+        &[
+            "changelog",
+            "diff",
+            "--portal",
+            "--json",
+            "--path",
+            dir.to_str().unwrap(),
+        ],
+    ));
+
+    assert_eq!(diff["missing_locally"], serde_json::json!([]));
+    assert_eq!(diff["edited"][0]["version"], "1.0.0");
+    assert_eq!(
+        diff["edited"][0]["added"],
+        serde_json::json!([["Features", "First release"]])
+    );
+    assert_eq!(diff["undocumented"], serde_json::json!(["1.1.0"]));
+}
@@ -0,0 +1,54 @@
+//! Helpers shared by the CLI integration tests.
+
+// Not every test file uses every helper
+#![allow(dead_code)]
+
+use std::process::{Command, Output};
+
+use facti_mock_portal::MockPortal;
+use serde_json::Value;
+
+/// Runs the CLI against the mock portal, isolated from any user configuration.
+pub fn facti(portal: &MockPortal, args: &[&str]) -> Output {
+    facti_with_env(portal, args, &[])
+}
+
+/// Like [`facti`], with extra environment variables set.
+pub fn facti_with_env(portal: &MockPortal, args: &[&str], env: &[(&str, &str)]) -> Output {
+    let config = std::env::temp_dir().join(format!(
+        "facti-cli-{}-nonexistent-config.toml",
+        std::process::id()
+    ));
+
+    Command::new(env!("CARGO_BIN_EXE_facti"))
+        .args(["--log-level", "off"])
+        .args(args)
+        .env("FACTI_CONFIG", config)
+        .env("FACTI_PORTAL_BASE_URL", portal.portal_base_url().as_str())
+        .env("FACTI_GAME_BASE_URL", portal.game_base_url().as_str())
+        .env_remove("FACTI_PORTAL_API_BASE_URL")
+        .env_remove("FACTI_API_KEY")
+        .env_remove("FACTI_API_KEY_FILE")
+        .env_remove("FACTI_USERNAME")
+        .env_remove("FACTI_TOKEN")
+        .envs(env.iter().copied())
+        .output()
+        .unwrap()
+}
+
+pub fn json(output: Output) -> Value {
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+pub fn temp_file(test: &str, file_name: &str, data: &[u8]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("facti-cli-{}-{}", std::process::id(), test));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(file_name);
+    std::fs::write(&path, data).unwrap();
+    path
+}
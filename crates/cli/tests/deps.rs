@@ -0,0 +1,153 @@
+use facti_lib::{
+    FactorioVersion, ModInfo,
+    dependency::Dependency,
+    version::{Version, VersionReq},
+};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+fn release(version: Version, factorio_version: FactorioVersion) -> Vec<u8> {
+    mod_zip(
+        &ModInfo::builder("flib", version, "Flib", "someone")
+            .factorio_version(factorio_version)
+            .dependencies(&[Dependency::required(
+                "base",
+                VersionReq::parse(">= 1.1.0").unwrap(),
+            )])
+            .build(),
+    )
+}
+
+#[test]
+fn test_graph() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store
+            .publish(&release(Version::new(0, 12, 0), FactorioVersion::new(1, 1)))
+            .unwrap();
+        store
+            .upload(
+                "flib",
+                &release(Version::new(0, 15, 0), FactorioVersion::new(2, 0)),
+            )
+            .unwrap();
+    }
+
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone")
+        .factorio_version(FactorioVersion::new(1, 1))
+        .dependencies(&[
+            Dependency::required("flib", VersionReq::parse(">= 0.12.0").unwrap()),
+            Dependency::optional("optional-mod", VersionReq::Latest, true),
+            Dependency::required("missing-mod", VersionReq::Latest),
+            Dependency::incompatible("bad-mod"),
+        ])
+        .build();
+    let info_path = temp_file(
+        "deps-graph",
+        "info.json",
+        &serde_json::to_vec(&info).unwrap(),
+    );
+    let dir = info_path.parent().unwrap().to_str().unwrap();
+
+    let graph = json(facti(
+        &portal,
+        &["deps", "graph", "--format", "json", "--path", dir],
+    ));
+
+    let nodes = graph["nodes"].as_array().unwrap();
+    let node = |name: &str| nodes.iter().find(|n| n["name"] == name).unwrap();
+    assert_eq!(node("my-mod")["kind"], "root");
+    assert_eq!(node("flib")["kind"], "mod");
+    assert_eq!(node("flib")["version"], "0.12.0");
+    assert_eq!(node("base")["kind"], "built-in");
+    assert_eq!(node("optional-mod")["kind"], "unresolved");
+    assert_eq!(node("missing-mod")["kind"], "missing");
+    assert_eq!(node("bad-mod")["kind"], "unresolved");
+
+    let edges = graph["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 5);
+    assert_eq!(edges[0]["kind"], "required");
+    assert_eq!(edges[0]["requirement"], ">= 0.12.0");
+    assert_eq!(edges[1]["kind"], "hidden-optional");
+    assert_eq!(edges[3]["kind"], "incompatible");
+
+    let output = facti(&portal, &["deps", "graph", "--path", dir]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("digraph dependencies {"));
+}
+
+#[test]
+fn test_graph_intersects_requirements() {
+    let lib = |name: &str, requirement: &str| {
+        mod_zip(
+            &ModInfo::builder(name, Version::new(1, 0, 0), name, "someone")
+                .factorio_version(FactorioVersion::new(1, 1))
+                .dependencies(&[Dependency::required(
+                    "flib",
+                    VersionReq::parse(requirement).unwrap(),
+                )])
+                .build(),
+        )
+    };
+
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store
+            .publish(&release(Version::new(0, 12, 0), FactorioVersion::new(1, 1)))
+            .unwrap();
+        store
+            .upload(
+                "flib",
+                &release(Version::new(0, 13, 0), FactorioVersion::new(1, 1)),
+            )
+            .unwrap();
+        store.publish(&lib("lib-a", "< 0.13.0")).unwrap();
+    }
+
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone")
+        .factorio_version(FactorioVersion::new(1, 1))
+        .dependencies(&[
+            Dependency::required("flib", VersionReq::Latest),
+            Dependency::required("lib-a", VersionReq::Latest),
+        ])
+        .build();
+    let info_path = temp_file(
+        "deps-graph-intersect",
+        "info.json",
+        &serde_json::to_vec(&info).unwrap(),
+    );
+    let dir = info_path.parent().unwrap().to_str().unwrap();
+
+    let graph = json(facti(
+        &portal,
+        &["deps", "graph", "--format", "json", "--path", dir],
+    ));
+    let nodes = graph["nodes"].as_array().unwrap();
+    let node = |name: &str| nodes.iter().find(|n| n["name"] == name).unwrap();
+    assert_eq!(node("flib")["kind"], "mod");
+    assert_eq!(node("flib")["version"], "0.12.0");
+
+    // A requirement no release of flib satisfies together with the others
+    portal.store().publish(&lib("lib-b", ">= 0.13.0")).unwrap();
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone")
+        .factorio_version(FactorioVersion::new(1, 1))
+        .dependencies(&[
+            Dependency::required("lib-a", VersionReq::Latest),
+            Dependency::required("lib-b", VersionReq::Latest),
+        ])
+        .build();
+    std::fs::write(&info_path, serde_json::to_vec(&info).unwrap()).unwrap();
+
+    let graph = json(facti(
+        &portal,
+        &["deps", "graph", "--format", "json", "--path", dir],
+    ));
+    let nodes = graph["nodes"].as_array().unwrap();
+    let node = |name: &str| nodes.iter().find(|n| n["name"] == name).unwrap();
+    assert_eq!(node("flib")["kind"], "missing");
+}
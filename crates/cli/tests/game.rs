@@ -0,0 +1,70 @@
+use facti_api::data::game::{LatestRelease, LatestReleases};
+use facti_lib::{FactorioVersion, ModInfo, version::Version};
+use facti_mock_portal::MockPortal;
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+fn set_latest(portal: &MockPortal, stable: Version, experimental: Version) {
+    let release = |version| LatestRelease {
+        alpha: version,
+        demo: version,
+        headless: version,
+    };
+
+    portal.store().set_latest_releases(LatestReleases {
+        stable: release(stable),
+        experimental: release(experimental),
+    });
+}
+
+/// Writes a mod project targeting the given game version, returning its path.
+fn project(test: &str, factorio_version: FactorioVersion) -> String {
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone")
+        .factorio_version(factorio_version)
+        .build();
+    let path = temp_file(test, "info.json", &serde_json::to_vec(&info).unwrap());
+
+    path.parent().unwrap().to_str().unwrap().to_owned()
+}
+
+#[test]
+fn test_latest() {
+    let portal = MockPortal::start().unwrap();
+    set_latest(&portal, Version::new(2, 0, 28), Version::new(2, 0, 30));
+
+    let latest = json(facti(&portal, &["game", "--json", "latest"]));
+    assert_eq!(latest["stable"]["alpha"], "2.0.28");
+    assert_eq!(latest["experimental"]["headless"], "2.0.30");
+}
+
+#[test]
+fn test_check_current() {
+    let portal = MockPortal::start().unwrap();
+    set_latest(&portal, Version::new(2, 0, 28), Version::new(2, 0, 30));
+    let path = project("game-current", FactorioVersion::new(2, 0));
+
+    let report = json(facti(
+        &portal,
+        &["game", "--json", "check", "--path", &path],
+    ));
+    assert_eq!(report["name"], "my-mod");
+    assert_eq!(report["status"], "current");
+}
+
+#[test]
+fn test_check_outdated() {
+    let portal = MockPortal::start().unwrap();
+    set_latest(&portal, Version::new(2, 0, 28), Version::new(2, 1, 0));
+    let path = project("game-outdated", FactorioVersion::new(1, 1));
+
+    let report = json(facti(
+        &portal,
+        &["game", "--json", "check", "--path", &path],
+    ));
+    assert_eq!(report["factorio_version"], "1.1");
+    assert_eq!(report["stable"], "2.0");
+    assert_eq!(report["experimental"], "2.1");
+    assert_eq!(report["status"], "outdated");
+}
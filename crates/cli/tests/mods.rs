@@ -0,0 +1,119 @@
+use facti_lib::{
+    ModInfo,
+    dependency::Dependency,
+    version::{Version, VersionReq},
+};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+use self::common::{facti, json};
+
+mod common;
+
+fn info(name: &str, version: Version, dependencies: &[Dependency]) -> ModInfo {
+    ModInfo::builder(name, version, name, "someone")
+        .dependencies(dependencies)
+        .build()
+}
+
+/// Creates an empty mods folder unique to the calling test.
+fn mods_folder(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("facti-cli-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn add_zip(dir: &std::path::Path, info: &ModInfo) {
+    std::fs::write(
+        dir.join(format!("{}_{}.zip", info.name, info.version)),
+        mod_zip(info),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_order() {
+    let portal = MockPortal::start().unwrap();
+    let dir = mods_folder("mods-order");
+    let latest = VersionReq::Latest;
+
+    add_zip(&dir, &info("lib", Version::new(1, 0, 0), &[]));
+    add_zip(
+        &dir,
+        &info(
+            "addon",
+            Version::new(1, 0, 0),
+            &[Dependency::required("lib", latest)],
+        ),
+    );
+    add_zip(
+        &dir,
+        &info(
+            "addon",
+            Version::new(1, 1, 0),
+            &[Dependency::required("lib", latest)],
+        ),
+    );
+    add_zip(&dir, &info("disabled", Version::new(1, 0, 0), &[]));
+
+    let unpacked = dir.join("unpacked");
+    std::fs::create_dir(&unpacked).unwrap();
+    std::fs::write(
+        unpacked.join("info.json"),
+        serde_json::to_vec(&info("unpacked", Version::new(2, 0, 0), &[])).unwrap(),
+    )
+    .unwrap();
+
+    std::fs::write(
+        dir.join("mod-list.json"),
+        r#"{"mods": [{"name": "base", "enabled": true}, {"name": "disabled", "enabled": false}]}"#,
+    )
+    .unwrap();
+
+    let order = json(facti(
+        &portal,
+        &["mods", "order", "--json", dir.to_str().unwrap()],
+    ));
+    assert_eq!(
+        order,
+        serde_json::json!([
+            {"name": "lib", "version": "1.0.0"},
+            {"name": "unpacked", "version": "2.0.0"},
+            {"name": "addon", "version": "1.1.0"},
+        ])
+    );
+
+    let order = json(facti(
+        &portal,
+        &["mods", "order", "--all", "--json", dir.to_str().unwrap()],
+    ));
+    assert_eq!(order[0]["name"], "disabled");
+}
+
+#[test]
+fn test_order_reports_cycles() {
+    let portal = MockPortal::start().unwrap();
+    let dir = mods_folder("mods-order-cycle");
+    let latest = VersionReq::Latest;
+
+    add_zip(
+        &dir,
+        &info(
+            "a",
+            Version::new(1, 0, 0),
+            &[Dependency::required("b", latest)],
+        ),
+    );
+    add_zip(
+        &dir,
+        &info(
+            "b",
+            Version::new(1, 0, 0),
+            &[Dependency::required("a", latest)],
+        ),
+    );
+
+    let output = facti(&portal, &["mods", "order", dir.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("a -> b -> a"));
+}
@@ -0,0 +1,68 @@
+use facti_lib::{ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+use self::common::facti;
+
+mod common;
+
+fn release(version: Version) -> Vec<u8> {
+    mod_zip(&ModInfo::builder("alpha", version, "Alpha", "someone").build())
+}
+
+#[test]
+fn test_new_from_portal() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store.publish(&release(Version::new(1, 0, 0))).unwrap();
+        store
+            .upload("alpha", &release(Version::new(1, 1, 0)))
+            .unwrap();
+    }
+
+    let dir = std::env::temp_dir().join(format!("facti-cli-{}-new", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("alpha");
+    let path_str = path.to_str().unwrap();
+
+    let output = facti(&portal, &["new", path_str, "--from-portal", "alpha"]);
+    assert!(!output.status.success());
+    assert!(!path.exists());
+
+    let output = facti(
+        &portal,
+        &[
+            "--username",
+            "someone",
+            "--token",
+            "token",
+            "new",
+            path_str,
+            "--from-portal",
+            "alpha",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let info: ModInfo =
+        serde_json::from_slice(&std::fs::read(path.join("src/info.json")).unwrap()).unwrap();
+    assert_eq!(info.version, Version::new(1, 1, 0));
+
+    let repo = git2::Repository::open(&path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message().unwrap(), "Release 1.1.0");
+    assert_eq!(head.author().name().unwrap(), "facti");
+    assert_eq!(head.parent(0).unwrap().message().unwrap(), "Release 1.0.0");
+
+    let tag_names = repo.tag_names(None).unwrap();
+    let mut tags = tag_names
+        .iter()
+        .map(|t| t.unwrap().unwrap())
+        .collect::<Vec<_>>();
+    tags.sort();
+    assert_eq!(tags, vec!["v1.0.0", "v1.1.0"]);
+}
@@ -0,0 +1,331 @@
+use facti_lib::{
+    FactorioVersion, ModInfo,
+    dependency::Dependency,
+    version::{Version, VersionReq},
+};
+use facti_mock_portal::{MockPortal, mod_zip};
+use serde_json::Value;
+
+use self::common::facti;
+
+mod common;
+
+fn release(name: &str, version: Version, dependencies: &[Dependency]) -> Vec<u8> {
+    mod_zip(
+        &ModInfo::builder(name, version, name, "someone")
+            .factorio_version(FactorioVersion::new(2, 0))
+            .dependencies(dependencies)
+            .build(),
+    )
+}
+
+/// Creates an empty directory unique to the calling test.
+fn temp_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("facti-cli-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn assert_success(output: &std::process::Output) {
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_lock_and_install() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store
+            .publish(&release("lib", Version::new(1, 0, 0), &[]))
+            .unwrap();
+        store
+            .upload("lib", &release("lib", Version::new(1, 1, 0), &[]))
+            .unwrap();
+        store
+            .upload("lib", &release("lib", Version::new(2, 0, 0), &[]))
+            .unwrap();
+        store
+            .publish(&release(
+                "addon",
+                Version::new(1, 0, 0),
+                &[
+                    Dependency::required("base", VersionReq::Latest),
+                    Dependency::required("lib", VersionReq::parse("< 2.0.0").unwrap()),
+                ],
+            ))
+            .unwrap();
+    }
+
+    let pack = temp_dir("pack");
+    std::fs::write(
+        pack.join("modpack.toml"),
+        "factorio_version = \"2.0\"\n\n[mods]\naddon = \"\"\nquality = \"\"\n",
+    )
+    .unwrap();
+    let pack_str = pack.to_str().unwrap();
+
+    let output = facti(&portal, &["pack", "lock", "--path", pack_str]);
+    assert_success(&output);
+
+    let lock: toml::Table =
+        toml::from_str(&std::fs::read_to_string(pack.join("modpack.lock")).unwrap()).unwrap();
+    let locked = lock["mod"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["name"].as_str().unwrap(), m["version"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(locked, vec![("addon", "1.0.0"), ("lib", "1.1.0")]);
+    assert_eq!(lock["built_in"].as_array().unwrap().len(), 2);
+
+    let mods = temp_dir("pack-mods");
+    std::fs::write(mods.join("lib_2.0.0.zip"), b"stale").unwrap();
+    std::fs::write(
+        mods.join("other_1.0.0.zip"),
+        mod_zip(&ModInfo::builder("other", Version::new(1, 0, 0), "Other", "someone").build()),
+    )
+    .unwrap();
+    let mods_str = mods.to_str().unwrap();
+
+    let output = facti(&portal, &["pack", "install", "--path", pack_str, mods_str]);
+    assert!(!output.status.success(), "install needs credentials");
+
+    let credentials = ["--username", "someone", "--token", "token"];
+    let install = [
+        &credentials[..],
+        &["pack", "install", "--path", pack_str, mods_str],
+    ]
+    .concat();
+    assert_success(&facti(&portal, &install));
+
+    assert!(mods.join("addon_1.0.0.zip").is_file());
+    assert!(mods.join("lib_1.1.0.zip").is_file());
+    assert!(!mods.join("lib_2.0.0.zip").exists());
+
+    let mod_list: Value =
+        serde_json::from_slice(&std::fs::read(mods.join("mod-list.json")).unwrap()).unwrap();
+    let enabled = |name: &str| {
+        mod_list["mods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["name"] == name)
+            .unwrap()["enabled"]
+            .as_bool()
+            .unwrap()
+    };
+    assert_eq!(mod_list["mods"][0]["name"], "base");
+    assert!(enabled("base"));
+    assert!(enabled("addon"));
+    assert!(enabled("lib"));
+    assert!(enabled("quality"));
+    assert!(!enabled("space-age"));
+    assert!(!enabled("other"));
+
+    // Installing again keeps the zips that are already there
+    let downloads = portal.requests().len();
+    assert_success(&facti(&portal, &install));
+    assert_eq!(portal.requests().len(), downloads);
+}
+
+#[test]
+fn test_lock_enables_dependencies_of_built_in_mods() {
+    let portal = MockPortal::start().unwrap();
+    portal
+        .store()
+        .publish(&release("flib", Version::new(0, 15, 0), &[]))
+        .unwrap();
+
+    // The example from the README
+    let pack = temp_dir("pack-built-in");
+    std::fs::write(
+        pack.join("modpack.toml"),
+        "name = \"Server pack\"\nfactorio_version = \"2.0\"\n\n[mods]\nspace-age = \"\"\nflib = \">= 0.15.0\"\n",
+    )
+    .unwrap();
+    let pack_str = pack.to_str().unwrap();
+    assert_success(&facti(&portal, &["pack", "lock", "--path", pack_str]));
+
+    let lock: toml::Table =
+        toml::from_str(&std::fs::read_to_string(pack.join("modpack.lock")).unwrap()).unwrap();
+    let built_in = lock["built_in"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n.as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(built_in, vec!["elevated-rails", "quality", "space-age"]);
+
+    let mods = temp_dir("pack-built-in-mods");
+    assert_success(&facti(
+        &portal,
+        &[
+            "--username",
+            "someone",
+            "--token",
+            "token",
+            "pack",
+            "install",
+            "--path",
+            pack_str,
+            mods.to_str().unwrap(),
+        ],
+    ));
+
+    let mod_list: Value =
+        serde_json::from_slice(&std::fs::read(mods.join("mod-list.json")).unwrap()).unwrap();
+    assert!(
+        mod_list["mods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|m| m["enabled"] == true)
+    );
+}
+
+#[test]
+fn test_lock_reports_conflicting_requirements() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store
+            .publish(&release("lib", Version::new(1, 0, 0), &[]))
+            .unwrap();
+        store
+            .publish(&release(
+                "addon",
+                Version::new(1, 0, 0),
+                &[Dependency::required(
+                    "lib",
+                    VersionReq::parse(">= 2.0.0").unwrap(),
+                )],
+            ))
+            .unwrap();
+    }
+
+    let pack = temp_dir("pack-conflict");
+    std::fs::write(
+        pack.join("modpack.toml"),
+        "factorio_version = \"2.0\"\n\n[mods]\naddon = \"\"\n",
+    )
+    .unwrap();
+
+    let output = facti(&portal, &["pack", "lock", "--path", pack.to_str().unwrap()]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("addon requires >= 2.0.0"));
+    assert!(!pack.join("modpack.lock").exists());
+}
+
+#[test]
+fn test_export_and_import() {
+    let portal = MockPortal::start().unwrap();
+    let source = temp_dir("pack-export-source");
+    let lib = release("lib", Version::new(1, 0, 0), &[]);
+    std::fs::write(source.join("lib_1.0.0.zip"), &lib).unwrap();
+    std::fs::write(
+        source.join("mod-list.json"),
+        r#"{"mods": [{"name": "base", "enabled": true}, {"name": "lib", "enabled": false}]}"#,
+    )
+    .unwrap();
+    std::fs::write(source.join("mod-settings.dat"), b"\x02\x00settings").unwrap();
+
+    let archive = temp_dir("pack-export").join("pack.zip");
+    let archive_str = archive.to_str().unwrap();
+    assert_success(&facti(
+        &portal,
+        &[
+            "pack",
+            "export",
+            archive_str,
+            "--mods-dir",
+            source.to_str().unwrap(),
+        ],
+    ));
+
+    let target = temp_dir("pack-import");
+    std::fs::write(target.join("lib_0.9.0.zip"), b"old").unwrap();
+    assert_success(&facti(
+        &portal,
+        &["pack", "import", archive_str, target.to_str().unwrap()],
+    ));
+
+    assert_eq!(std::fs::read(target.join("lib_1.0.0.zip")).unwrap(), lib);
+    assert!(!target.join("lib_0.9.0.zip").exists());
+    for file in ["mod-list.json", "mod-settings.dat"] {
+        assert_eq!(
+            std::fs::read(target.join(file)).unwrap(),
+            std::fs::read(source.join(file)).unwrap()
+        );
+    }
+
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(&archive).unwrap()).unwrap();
+    let manifest: Value = serde_json::from_reader(zip.by_name("modpack.json").unwrap()).unwrap();
+    assert_eq!(manifest["mods"][0]["name"], "lib");
+    assert_eq!(manifest["mods"][0]["title"], "lib");
+    assert_eq!(manifest["mods"][0]["factorio_version"], "2.0");
+}
+
+#[test]
+fn test_import_rejects_checksum_mismatch() {
+    use std::io::{Read, Write};
+
+    let portal = MockPortal::start().unwrap();
+    let source = temp_dir("pack-tamper-source");
+    std::fs::write(
+        source.join("lib_1.0.0.zip"),
+        release("lib", Version::new(1, 0, 0), &[]),
+    )
+    .unwrap();
+
+    let dir = temp_dir("pack-tamper");
+    let archive = dir.join("pack.zip");
+    assert_success(&facti(
+        &portal,
+        &[
+            "pack",
+            "export",
+            archive.to_str().unwrap(),
+            "--mods-dir",
+            source.to_str().unwrap(),
+        ],
+    ));
+
+    // Copy the archive, replacing the mod zip with another release
+    let tampered = dir.join("tampered.zip");
+    let mut original = zip::ZipArchive::new(std::fs::File::open(&archive).unwrap()).unwrap();
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&tampered).unwrap());
+    for i in 0..original.len() {
+        let mut entry = original.by_index(i).unwrap();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).unwrap();
+        if entry.name() == "mods/lib_1.0.0.zip" {
+            data = release("lib", Version::new(1, 0, 1), &[]);
+        }
+        writer
+            .start_file(entry.name(), zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&data).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let target = dir.join("mods");
+    let output = facti(
+        &portal,
+        &[
+            "pack",
+            "import",
+            tampered.to_str().unwrap(),
+            target.to_str().unwrap(),
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+    assert!(!target.exists());
+}
@@ -0,0 +1,121 @@
+use facti_lib::{
+    FactorioVersion, ModInfo,
+    dependency::Dependency,
+    version::{Version, VersionReq},
+};
+use facti_mock_portal::{MockPortal, mod_zip};
+use serde_json::Value;
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+fn release(name: &str, version: Version, factorio_version: FactorioVersion) -> Vec<u8> {
+    mod_zip(
+        &ModInfo::builder(name, version, name, "someone")
+            .factorio_version(factorio_version)
+            .build(),
+    )
+}
+
+fn seed(portal: &MockPortal) {
+    let mut store = portal.store();
+    store
+        .publish(&release(
+            "flib",
+            Version::new(0, 12, 0),
+            FactorioVersion::new(1, 1),
+        ))
+        .unwrap();
+    store
+        .upload(
+            "flib",
+            &release("flib", Version::new(0, 15, 0), FactorioVersion::new(2, 0)),
+        )
+        .unwrap();
+    store
+        .publish(&release(
+            "old-mod",
+            Version::new(1, 0, 0),
+            FactorioVersion::new(1, 1),
+        ))
+        .unwrap();
+}
+
+/// Writes a mod project targeting Factorio 1.1, returning the path to it.
+fn project(test: &str) -> std::path::PathBuf {
+    let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "someone")
+        .factorio_version(FactorioVersion::new(1, 1))
+        .dependencies(&[
+            Dependency::required("base", VersionReq::parse(">= 1.1.0").unwrap()),
+            Dependency::required("flib", VersionReq::parse(">= 0.12.0").unwrap()),
+            Dependency::required("old-mod", VersionReq::Latest),
+            Dependency::optional("missing-mod", VersionReq::Latest, false),
+            Dependency::incompatible("bad-mod"),
+        ])
+        .build();
+
+    temp_file(test, "info.json", &serde_json::to_vec(&info).unwrap())
+}
+
+fn dependency<'a>(report: &'a Value, name: &str) -> &'a Value {
+    report["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == name)
+        .unwrap()
+}
+
+#[test]
+fn test_port() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal);
+    let info_path = project("port");
+    let path = info_path.parent().unwrap().to_str().unwrap();
+
+    let report = json(facti(
+        &portal,
+        &["port", "--to", "2.0", "--json", "--path", path],
+    ));
+    assert_eq!(report["from"], "1.1");
+    assert_eq!(report["to"], "2.0");
+    assert_eq!(report["dependencies"].as_array().unwrap().len(), 4);
+
+    let base = dependency(&report, "base");
+    assert_eq!(base["readiness"], "built-in");
+    assert_eq!(base["suggested_requirement"], ">= 2.0.0");
+
+    let flib = dependency(&report, "flib");
+    assert_eq!(flib["readiness"], "ready");
+    assert_eq!(flib["latest"], "0.15.0");
+    assert_eq!(flib["suggested_requirement"], ">= 0.15.0");
+
+    assert_eq!(dependency(&report, "old-mod")["readiness"], "missing");
+
+    let missing = dependency(&report, "missing-mod");
+    assert_eq!(missing["readiness"], "unknown");
+    assert_eq!(missing["optional"], true);
+
+    let info: Value = serde_json::from_slice(&std::fs::read(&info_path).unwrap()).unwrap();
+    assert_eq!(info["factorio_version"], "2.0");
+}
+
+#[test]
+fn test_port_dry_run() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal);
+    let info_path = project("port-dry-run");
+    let path = info_path.parent().unwrap().to_str().unwrap();
+
+    let output = facti(
+        &portal,
+        &["port", "--to", "2.0", "--dry-run", "--path", path],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1 required dependency is not available for Factorio 2.0 yet"));
+
+    let info: Value = serde_json::from_slice(&std::fs::read(&info_path).unwrap()).unwrap();
+    assert_eq!(info["factorio_version"], "1.1");
+}
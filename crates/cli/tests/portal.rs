@@ -0,0 +1,666 @@
+use facti_api::data::detail::{Category, Tag};
+use facti_lib::{ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+fn seed(portal: &MockPortal, names: &[&str]) {
+    let mut store = portal.store();
+    for name in names {
+        let info =
+            ModInfo::builder(*name, Version::new(1, 2, 3), name.to_uppercase(), "someone").build();
+        store.publish(&mod_zip(&info)).unwrap();
+    }
+}
+
+#[test]
+fn test_search() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha", "beta", "gamma"]);
+
+    let output = json(facti(
+        &portal,
+        &["portal", "--json", "search", "alpha", "gamma"],
+    ));
+    let names = output["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["alpha", "gamma"]);
+}
+
+#[test]
+fn test_show() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    let output = json(facti(
+        &portal,
+        &["portal", "--json", "show", "--full", "alpha"],
+    ));
+
+    assert_eq!(output["name"], "alpha");
+    assert_eq!(output["releases"][0]["version"], "1.2.3");
+}
+
+#[test]
+fn test_show_unknown_mod_fails() {
+    let portal = MockPortal::start().unwrap();
+
+    let output = facti(&portal, &["portal", "show", "missing"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_user_agent_contact() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    json(facti(
+        &portal,
+        &[
+            "--user-agent-contact",
+            "mailto:ci@example.com",
+            "portal",
+            "--json",
+            "show",
+            "alpha",
+        ],
+    ));
+
+    let user_agent = portal.requests()[0].user_agent.clone().unwrap();
+    assert!(user_agent.starts_with("facti/"));
+    assert!(user_agent.ends_with(" (+mailto:ci@example.com)"));
+}
+
+#[test]
+fn test_missing_ca_cert() {
+    let portal = MockPortal::start().unwrap();
+
+    let output = facti(
+        &portal,
+        &[
+            "--ca-cert",
+            "/nonexistent/facti-ca.pem",
+            "portal",
+            "show",
+            "alpha",
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(portal.requests().is_empty());
+}
+
+/// Writes a file to a temporary directory unique to the test.
+#[test]
+fn test_upload() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    let info = ModInfo::builder("alpha", Version::new(1, 3, 0), "ALPHA", "someone").build();
+    let path = temp_file("upload", "alpha_1.3.0.zip", &mod_zip(&info));
+
+    let output = facti(
+        &portal,
+        &[
+            "-q",
+            "--api-key",
+            "secret",
+            "portal",
+            "upload",
+            path.to_str().unwrap(),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stderr.is_empty());
+
+    let store = portal.store();
+    let releases = &store.get("alpha").unwrap().releases;
+    assert_eq!(releases.len(), 2);
+}
+
+#[test]
+fn test_upload_requires_name() {
+    let portal = MockPortal::start().unwrap();
+    let path = temp_file("upload-name", "release.zip", b"not a zip");
+
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "upload",
+            path.to_str().unwrap(),
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(portal.requests().is_empty());
+}
+
+#[test]
+fn test_images_upload() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    let path = temp_file("images", "screenshot.png", b"\x89PNG\r\n\x1a\nfake");
+
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "images",
+            "upload",
+            "--no-process",
+            "alpha",
+            path.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(portal.store().gallery("alpha").len(), 1);
+}
+
+#[test]
+fn test_images_upload_converts_bmp() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    // A 1x1 pixel, 24 bit BMP with a single red pixel
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&58u32.to_le_bytes());
+    bmp.extend_from_slice(&[0, 0, 0, 0]);
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&1i32.to_le_bytes());
+    bmp.extend_from_slice(&1i32.to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&[0; 24]);
+    bmp.extend_from_slice(&[0, 0, 255, 0]);
+    let path = temp_file("images-bmp", "screenshot.bmp", &bmp);
+
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "images",
+            "upload",
+            "alpha",
+            path.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success());
+
+    let gallery = portal.store().gallery("alpha");
+    assert_eq!(gallery.len(), 1);
+    let image = portal
+        .store()
+        .file(&format!("assets/{}.png", gallery[0]))
+        .unwrap()
+        .to_vec();
+    assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+#[test]
+fn test_images_upload_rejects_unknown_format() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    let path = temp_file("images-unknown", "screenshot.webp", b"RIFF\0\0\0\0WEBP");
+
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "images",
+            "upload",
+            "alpha",
+            path.to_str().unwrap(),
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(portal.store().gallery("alpha").is_empty());
+}
+
+// The index location can only be overridden through the XDG variables
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_index_tags() {
+    use self::common::facti_with_env;
+
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha", "beta"]);
+    {
+        let mut store = portal.store();
+        let alpha = store.get_mut("alpha").unwrap();
+        alpha.category = Category::Unknown("space-age".to_owned());
+        alpha.tags = vec![Tag::Trains, Tag::Unknown("planets".to_owned())];
+    }
+
+    let data = std::env::temp_dir().join(format!("facti-cli-{}-index-tags", std::process::id()));
+    let env = [("XDG_DATA_HOME", data.to_str().unwrap())];
+
+    let output = facti_with_env(&portal, &["portal", "index", "update", "--details"], &env);
+    assert!(output.status.success());
+
+    let taxonomy = json(facti_with_env(
+        &portal,
+        &["portal", "--json", "index", "used-tags"],
+        &env,
+    ));
+    let find = |kind: &str, name: &str| {
+        taxonomy[kind]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|u| u["name"] == name)
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(find("categories", "space-age")["known"], false);
+    assert_eq!(find("categories", "space-age")["mods"], 1);
+    assert_eq!(find("tags", "trains")["known"], true);
+    assert_eq!(find("tags", "trains")["mods"], 1);
+    assert_eq!(find("tags", "planets")["known"], false);
+    assert_eq!(find("tags", "planets")["learned"], true);
+    assert_eq!(find("tags", "cheats")["mods"], 0);
+    assert_eq!(find("tags", "cheats")["learned"], false);
+}
+
+// The index location can only be overridden through the XDG variables
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_search_query() {
+    use self::common::facti_with_env;
+
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha", "beta", "gamma"]);
+    {
+        let mut store = portal.store();
+        store.get_mut("alpha").unwrap().summary = "Faster trains".to_owned();
+        store.get_mut("gamma").unwrap().tags = vec![Tag::Trains];
+    }
+
+    let data = std::env::temp_dir().join(format!("facti-cli-{}-search-query", std::process::id()));
+    let env = [("XDG_DATA_HOME", data.to_str().unwrap())];
+    let search = |query: &str| {
+        json(facti_with_env(
+            &portal,
+            &["portal", "--json", "search", "--query", query],
+            &env,
+        ))
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>()
+    };
+
+    let output = facti_with_env(&portal, &["portal", "index", "update", "--details"], &env);
+    assert!(output.status.success());
+    assert_eq!(search("trains"), vec!["gamma", "alpha"]);
+    assert_eq!(search("alhpa"), vec!["alpha"]);
+
+    // Tags of mods that did not change survive an incremental update
+    portal.store().get_mut("alpha").unwrap().summary = "Faster boats".to_owned();
+    let output = facti_with_env(&portal, &["portal", "index", "update"], &env);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0 added, 1 updated"));
+    assert_eq!(search("trains"), vec!["gamma"]);
+
+    // Deprecated mods are no longer listed, and removed by a full update
+    portal.store().set_deprecated("beta", true).unwrap();
+    let output = facti_with_env(&portal, &["portal", "index", "update", "--full"], &env);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 removed"));
+    assert!(search("beta").is_empty());
+
+    // Filters of the portal search do not apply to local searches
+    for extra in [&["--deprecated"][..], &["-f", "1.1"], &["alpha"]] {
+        let mut args = vec!["portal", "search", "--query", "alpha"];
+        args.extend_from_slice(extra);
+        assert!(!facti_with_env(&portal, &args, &env).status.success());
+    }
+}
+
+#[test]
+fn test_pull() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    {
+        let mut store = portal.store();
+        let alpha = store.get_mut("alpha").unwrap();
+        alpha.summary = "Short summary".to_owned();
+        alpha.description = "# Alpha\n\nLong description".to_owned();
+        alpha.category = Category::Tweaks;
+        alpha.tags = vec![Tag::Trains];
+    }
+
+    let image = temp_file("pull", "screenshot.png", b"\x89PNG\r\n\x1a\nfake");
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "images",
+            "upload",
+            "--no-process",
+            "alpha",
+            image.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success());
+
+    let info = ModInfo::builder("alpha", Version::new(1, 2, 3), "ALPHA", "someone").build();
+    let info_path = temp_file("pull", "info.json", &serde_json::to_vec(&info).unwrap());
+    let dir = info_path.parent().unwrap();
+
+    let output = facti(
+        &portal,
+        &["portal", "pull", "--path", dir.to_str().unwrap()],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let info: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&info_path).unwrap()).unwrap();
+    let package = &info["package"];
+    assert_eq!(package["information"], "portal/information.md");
+    assert_eq!(package["summary"], "Short summary");
+    assert_eq!(package["category"], "tweaks");
+    assert_eq!(package["tags"], serde_json::json!(["trains"]));
+
+    let gallery = package["gallery"].as_array().unwrap();
+    assert_eq!(gallery.len(), 1);
+    assert_eq!(
+        std::fs::read(dir.join(gallery[0].as_str().unwrap())).unwrap(),
+        b"\x89PNG\r\n\x1a\nfake"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("portal/information.md")).unwrap(),
+        "# Alpha\n\nLong description"
+    );
+}
+
+#[test]
+fn test_edit() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    let changes = temp_file(
+        "edit",
+        "details.toml",
+        b"title = \"Alpha Mod\"\ntags = [\"trains\"]\ndescription = \"Line\\nNew line\"\n",
+    );
+    let edit = |extra: &[&str]| {
+        let mut args = vec![
+            "--api-key",
+            "secret",
+            "portal",
+            "edit",
+            "alpha",
+            "--from",
+            changes.to_str().unwrap(),
+            "--summary",
+            "From a flag",
+        ];
+        args.extend_from_slice(extra);
+        facti(&portal, &args)
+    };
+
+    let output = edit(&["--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("title:\n  - ALPHA\n  + Alpha Mod"));
+    assert!(stdout.contains("  + New line"));
+    assert_eq!(portal.store().get("alpha").unwrap().title, "ALPHA");
+
+    let output = edit(&[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let store = portal.store();
+    let alpha = store.get("alpha").unwrap();
+    assert_eq!(alpha.title, "Alpha Mod");
+    assert_eq!(alpha.summary, "From a flag");
+    assert_eq!(alpha.tags, vec![Tag::Trains]);
+}
+
+#[test]
+fn test_edit_no_tags_clears_tags() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    portal.store().get_mut("alpha").unwrap().tags = vec![Tag::Trains, Tag::Logistics];
+
+    let output = facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "edit",
+            "alpha",
+            "--no-tags",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(portal.store().get("alpha").unwrap().tags.is_empty());
+}
+
+#[test]
+fn test_edit_rejects_unknown_fields() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    let changes = temp_file("edit-unknown", "details.json", br#"{"titel": "Typo"}"#);
+    let output = facti(
+        &portal,
+        &[
+            "portal",
+            "edit",
+            "alpha",
+            "--from",
+            changes.to_str().unwrap(),
+        ],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_bulk() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha", "beta", "gamma"]);
+    {
+        let mut store = portal.store();
+        store.get_mut("beta").unwrap().tags = vec![Tag::Trains];
+        store.get_mut("gamma").unwrap().owner = "someone-else".to_owned();
+    }
+
+    let bulk = |extra: &[&str]| {
+        let mut args = vec![
+            "--api-key",
+            "secret",
+            "portal",
+            "--json",
+            "bulk",
+            "--owner",
+            "facti",
+            "--add-tag",
+            "trains",
+            "--source-url",
+            "https://example.com/{name}",
+        ];
+        args.extend_from_slice(extra);
+        json(facti(&portal, &args))
+    };
+
+    let plan = bulk(&["--dry-run"]);
+    assert_eq!(plan[0]["name"], "alpha");
+    assert_eq!(plan[0]["status"], "planned");
+    assert_eq!(plan.as_array().unwrap().len(), 2);
+    assert!(portal.store().get("alpha").unwrap().tags.is_empty());
+
+    let result = bulk(&["--jobs", "2"]);
+    assert_eq!(result[0]["status"], "updated");
+    assert_eq!(result[1]["status"], "updated");
+
+    let store = portal.store();
+    let alpha = store.get("alpha").unwrap();
+    assert_eq!(alpha.tags, vec![Tag::Trains]);
+    assert_eq!(
+        alpha.source_url.as_ref().unwrap().as_str(),
+        "https://example.com/alpha"
+    );
+    assert!(store.get("gamma").unwrap().tags.is_empty());
+    drop(store);
+
+    assert_eq!(bulk(&[])[1]["status"], "unchanged");
+}
+
+#[test]
+fn test_bulk_removes_last_tag() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    portal.store().get_mut("alpha").unwrap().tags = vec![Tag::Trains];
+
+    let result = json(facti(
+        &portal,
+        &[
+            "--api-key",
+            "secret",
+            "portal",
+            "--json",
+            "bulk",
+            "--owner",
+            "facti",
+            "--remove-tag",
+            "trains",
+        ],
+    ));
+
+    assert_eq!(result[0]["status"], "updated");
+    assert!(portal.store().get("alpha").unwrap().tags.is_empty());
+}
+
+#[test]
+fn test_bulk_deprecated_is_unchanged_once_set() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+
+    let bulk = || {
+        json(facti(
+            &portal,
+            &[
+                "--api-key",
+                "secret",
+                "portal",
+                "--json",
+                "bulk",
+                "--owner",
+                "facti",
+                "--deprecated",
+                "true",
+            ],
+        ))
+    };
+
+    assert_eq!(bulk()[0]["status"], "updated");
+    assert!(portal.store().is_deprecated("alpha"));
+    assert_eq!(bulk()[0]["status"], "unchanged");
+}
+
+#[test]
+fn test_edit_compares_parsed_homepage() {
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["alpha"]);
+    portal.store().get_mut("alpha").unwrap().homepage = Some("https://Example.com".to_owned());
+
+    let output = facti(
+        &portal,
+        &[
+            "portal",
+            "edit",
+            "alpha",
+            "--homepage",
+            "https://example.com",
+            "--dry-run",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No changes"));
+}
+
+// The index location can only be overridden through the XDG variables
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_rdeps() {
+    use facti_lib::{dependency::Dependency, version::VersionReq};
+
+    use self::common::facti_with_env;
+
+    let portal = MockPortal::start().unwrap();
+    seed(&portal, &["lib"]);
+    {
+        let mut store = portal.store();
+        let addon = ModInfo::builder("addon", Version::new(1, 0, 0), "Addon", "someone")
+            .dependency(Dependency::required(
+                "lib",
+                VersionReq::parse("< 2.0.0").unwrap(),
+            ))
+            .build();
+        store.publish(&mod_zip(&addon)).unwrap();
+        let rival = ModInfo::builder("rival", Version::new(1, 0, 0), "Rival", "someone")
+            .dependency(Dependency::incompatible("lib"))
+            .build();
+        store.publish(&mod_zip(&rival)).unwrap();
+    }
+
+    let data = std::env::temp_dir().join(format!("facti-cli-{}-rdeps", std::process::id()));
+    let env = [("XDG_DATA_HOME", data.to_str().unwrap())];
+
+    let output = facti_with_env(&portal, &["portal", "index", "update", "--details"], &env);
+    assert!(output.status.success());
+
+    let dependents = json(facti_with_env(
+        &portal,
+        &["portal", "--json", "rdeps", "lib", "--version", "2.0.0"],
+        &env,
+    ));
+
+    assert_eq!(dependents.as_array().unwrap().len(), 2);
+    assert_eq!(dependents[0]["name"], "addon");
+    assert_eq!(dependents[0]["kind"], "required");
+    assert_eq!(dependents[0]["requirement"], "< 2.0.0");
+    assert_eq!(dependents[0]["accepts"], false);
+    assert_eq!(dependents[1]["name"], "rival");
+    assert_eq!(dependents[1]["kind"], "incompatible");
+}
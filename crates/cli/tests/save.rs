@@ -0,0 +1,210 @@
+use std::io::Write;
+
+use facti_lib::{FactorioVersion, ModInfo, version::Version};
+use facti_mock_portal::{MockPortal, mod_zip};
+use flate2::{Compression, write::ZlibEncoder};
+use serde_json::Value;
+
+use self::common::{facti, json, temp_file};
+
+mod common;
+
+fn string(out: &mut Vec<u8>, s: &str) {
+    out.push(s.len() as u8);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Builds a save made with Factorio 2.0.28 with the given mods, with the
+/// map data split into zlib compressed chunks like Factorio does.
+fn save_zip(mods: &[(&str, [u8; 3])]) -> Vec<u8> {
+    let mut header = Vec::new();
+    for part in [2u16, 0, 28, 12345] {
+        header.extend_from_slice(&part.to_le_bytes());
+    }
+    header.push(0);
+    string(&mut header, "");
+    string(&mut header, "freeplay");
+    string(&mut header, "base");
+    header.extend_from_slice(&[1, 0, 0]);
+    string(&mut header, "");
+    header.extend_from_slice(&[0, 0, 0, 0, 2, 0, 28]);
+    header.extend_from_slice(&12345u16.to_le_bytes());
+    header.push(1);
+    header.push(mods.len() as u8);
+    for (name, version) in mods {
+        string(&mut header, name);
+        header.extend_from_slice(version);
+        header.extend_from_slice(&0u32.to_le_bytes());
+    }
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (i, chunk) in header.chunks(32).enumerate() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(chunk).unwrap();
+        zip.start_file(format!("my-save/level.dat{}", i), options)
+            .unwrap();
+        zip.write_all(&encoder.finish().unwrap()).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+fn release(name: &str, version: Version) -> Vec<u8> {
+    mod_zip(
+        &ModInfo::builder(name, version, name, "someone")
+            .factorio_version(FactorioVersion::new(2, 0))
+            .build(),
+    )
+}
+
+/// Creates an empty directory unique to the calling test.
+fn temp_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("facti-cli-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_info() {
+    let portal = MockPortal::start().unwrap();
+    let save = temp_file(
+        "save-info",
+        "my-save.zip",
+        &save_zip(&[("base", [2, 0, 28]), ("flib", [0, 15, 0])]),
+    );
+
+    let info = json(facti(
+        &portal,
+        &["save", "info", "--json", save.to_str().unwrap()],
+    ));
+
+    assert_eq!(info["factorio_version"], "2.0.28");
+    assert_eq!(info["level_name"], "freeplay");
+    assert_eq!(info["mods"][1]["name"], "flib");
+    assert_eq!(info["mods"][1]["version"], "0.15.0");
+}
+
+#[test]
+fn test_info_rejects_non_save() {
+    let portal = MockPortal::start().unwrap();
+    let path = temp_file("save-invalid", "not-a-save.zip", b"not a zip");
+
+    let output = facti(&portal, &["save", "info", path.to_str().unwrap()]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_sync_mods() {
+    let portal = MockPortal::start().unwrap();
+    {
+        let mut store = portal.store();
+        store
+            .publish(&release("lib", Version::new(1, 0, 0)))
+            .unwrap();
+        store
+            .upload("lib", &release("lib", Version::new(1, 1, 0)))
+            .unwrap();
+    }
+
+    let save = temp_file(
+        "save-sync",
+        "my-save.zip",
+        &save_zip(&[
+            ("base", [2, 0, 28]),
+            ("quality", [2, 0, 28]),
+            ("addon", [1, 0, 0]),
+            ("lib", [1, 0, 0]),
+        ]),
+    );
+    let save_str = save.to_str().unwrap();
+
+    let mods = temp_dir("save-sync-mods");
+    let addon = release("addon", Version::new(1, 0, 0));
+    std::fs::write(mods.join("addon_1.0.0.zip"), &addon).unwrap();
+    std::fs::write(
+        mods.join("lib_1.1.0.zip"),
+        release("lib", Version::new(1, 1, 0)),
+    )
+    .unwrap();
+    std::fs::write(
+        mods.join("other_1.0.0.zip"),
+        release("other", Version::new(1, 0, 0)),
+    )
+    .unwrap();
+    let mods_str = mods.to_str().unwrap();
+
+    let output = facti(&portal, &["save", "sync-mods", "-n", save_str, mods_str]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would download lib 1.0.0"));
+    assert!(stdout.contains("Would disable other"));
+    assert!(!mods.join("mod-list.json").exists());
+    assert!(mods.join("lib_1.1.0.zip").exists());
+
+    let output = facti(
+        &portal,
+        &[
+            "--username",
+            "someone",
+            "--token",
+            "token",
+            "save",
+            "sync-mods",
+            save_str,
+            mods_str,
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "facti failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(mods.join("lib_1.0.0.zip").is_file());
+    assert!(!mods.join("lib_1.1.0.zip").exists());
+    assert_eq!(std::fs::read(mods.join("addon_1.0.0.zip")).unwrap(), addon);
+
+    let mod_list: Value =
+        serde_json::from_slice(&std::fs::read(mods.join("mod-list.json")).unwrap()).unwrap();
+    let enabled = mod_list["mods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|m| m["enabled"].as_bool().unwrap())
+        .map(|m| m["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(enabled, vec!["base", "addon", "lib", "quality"]);
+    assert_eq!(mod_list["mods"].as_array().unwrap().len(), 7);
+}
+
+#[test]
+fn test_sync_mods_fails_for_unknown_release() {
+    let portal = MockPortal::start().unwrap();
+    portal
+        .store()
+        .publish(&release("lib", Version::new(1, 0, 0)))
+        .unwrap();
+
+    let save = temp_file(
+        "save-sync-unknown",
+        "my-save.zip",
+        &save_zip(&[("base", [2, 0, 28]), ("lib", [0, 9, 0])]),
+    );
+    let mods = temp_dir("save-sync-unknown-mods");
+
+    let output = facti(
+        &portal,
+        &[
+            "save",
+            "sync-mods",
+            save.to_str().unwrap(),
+            mods.to_str().unwrap(),
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lib 0.9.0"));
+    assert!(!mods.join("mod-list.json").exists());
+}
@@ -0,0 +1,523 @@
+//! Decoding and encoding Factorio blueprint strings.
+//!
+//! A blueprint string is a version byte (currently always `0`), followed by
+//! the base64 encoding of zlib compressed JSON. The JSON is an object with
+//! a single key naming the kind of item it holds, like
+//! `{"blueprint": {...}}`.
+//!
+//! The structures in this module type the fields shared by most blueprints,
+//! and keep every other field in an `extra` map, so that encoding a decoded
+//! string gives back the same JSON (though not necessarily the same string,
+//! as the order of keys and the compression can differ).
+
+use std::io::{Read, Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::BlueprintError;
+
+/// The only version of the blueprint string format there is so far.
+const FORMAT_VERSION: char = '0';
+
+/// The contents of a blueprint string.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlueprintData {
+    Blueprint(Blueprint),
+    BlueprintBook(BlueprintBook),
+    DeconstructionPlanner(DeconstructionPlanner),
+    UpgradePlanner(UpgradePlanner),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Blueprint {
+    /// Name of the item, always `blueprint`.
+    pub item: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<Color>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<Vec<Icon>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<Entity>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tiles: Option<Vec<Tile>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snap_to_grid: Option<Position>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absolute_snapping: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_relative_to_grid: Option<Position>,
+
+    /// Version of Factorio the blueprint was made with, see [`map_version`].
+    pub version: u64,
+
+    /// Fields not covered above, like wires and train schedules.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlueprintBook {
+    /// Name of the item, always `blueprint-book`.
+    pub item: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<Color>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<Vec<Icon>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blueprints: Option<Vec<BookEntry>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_index: Option<u32>,
+
+    /// Version of Factorio the book was made with, see [`map_version`].
+    pub version: u64,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An item in a slot of a blueprint book.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BookEntry {
+    /// Index of the slot, starting from 0.
+    pub index: u32,
+
+    #[serde(flatten)]
+    pub data: BlueprintData,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeconstructionPlanner {
+    /// Name of the item, always `deconstruction-planner`.
+    pub item: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<DeconstructionSettings>,
+
+    pub version: u64,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeconstructionSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<Vec<Icon>>,
+
+    /// 0 to only deconstruct the entities in `entity_filters`, 1 to
+    /// deconstruct everything else.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_filter_mode: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_filters: Option<Vec<Filter>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trees_and_rocks_only: Option<bool>,
+
+    /// 0 to only deconstruct the tiles in `tile_filters`, 1 to deconstruct
+    /// everything else.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_filter_mode: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_filters: Option<Vec<Filter>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_selection_mode: Option<u8>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A filter in a slot of a deconstruction planner.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Filter {
+    pub name: String,
+
+    /// Index of the slot, starting from 1.
+    pub index: u32,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpgradePlanner {
+    /// Name of the item, always `upgrade-planner`.
+    pub item: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<UpgradeSettings>,
+
+    pub version: u64,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<Vec<Icon>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mappers: Option<Vec<UpgradeMapper>>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A rule of an upgrade planner, replacing one entity or item with another.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeMapper {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<SignalId>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<SignalId>,
+
+    /// Index of the rule, starting from 0.
+    pub index: u32,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    /// Number of the entity, unique within the blueprint.
+    pub entity_number: u32,
+    pub name: String,
+    pub position: Position,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+
+    /// Fields specific to the type of entity, like recipes or circuit
+    /// conditions.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tile {
+    pub name: String,
+    pub position: Position,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Icon {
+    pub signal: SignalId,
+
+    /// Index of the icon, starting from 1.
+    pub index: u32,
+}
+
+/// Identifies an item, fluid, virtual signal or other prototype.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignalId {
+    /// Type of the prototype, left out for items.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    #[serde(serialize_with = "number::serialize")]
+    pub x: f64,
+
+    #[serde(serialize_with = "number::serialize")]
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Color {
+    #[serde(serialize_with = "number::serialize")]
+    pub r: f64,
+
+    #[serde(serialize_with = "number::serialize")]
+    pub g: f64,
+
+    #[serde(serialize_with = "number::serialize")]
+    pub b: f64,
+
+    #[serde(
+        serialize_with = "number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub a: Option<f64>,
+}
+
+impl BlueprintData {
+    /// Decodes a blueprint string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid blueprint string, or its
+    /// JSON does not match the structures in this module.
+    pub fn decode(s: &str) -> Result<Self, BlueprintError> {
+        Ok(serde_json::from_str(&decode_json(s)?)?)
+    }
+
+    /// Encodes the data as a blueprint string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be serialized to JSON, which can
+    /// only happen if an `extra` map holds a non-finite number.
+    pub fn encode(&self) -> Result<String, BlueprintError> {
+        Ok(encode_json(&serde_json::to_string(self)?))
+    }
+
+    /// The label of the blueprint, book or planner, if it has one.
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Self::Blueprint(b) => b.label.as_deref(),
+            Self::BlueprintBook(b) => b.label.as_deref(),
+            Self::DeconstructionPlanner(p) => p.label.as_deref(),
+            Self::UpgradePlanner(p) => p.label.as_deref(),
+        }
+    }
+
+    /// The raw version of Factorio the data was made with, see
+    /// [`map_version`].
+    pub fn version(&self) -> u64 {
+        match self {
+            Self::Blueprint(b) => b.version,
+            Self::BlueprintBook(b) => b.version,
+            Self::DeconstructionPlanner(p) => p.version,
+            Self::UpgradePlanner(p) => p.version,
+        }
+    }
+}
+
+/// Decodes a blueprint string to the JSON it contains, without parsing it.
+///
+/// Whitespace around the string is ignored.
+///
+/// # Errors
+///
+/// Returns an error if the string is empty, uses an unsupported version,
+/// or is not valid base64 encoded and zlib compressed UTF-8.
+pub fn decode_json(s: &str) -> Result<String, BlueprintError> {
+    let s = s.trim();
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(FORMAT_VERSION) => {}
+        Some(version) => return Err(BlueprintError::UnsupportedVersion(version)),
+        None => return Err(BlueprintError::Empty),
+    }
+
+    let compressed = STANDARD.decode(chars.as_str())?;
+    let mut json = String::new();
+    ZlibDecoder::new(compressed.as_slice()).read_to_string(&mut json)?;
+
+    Ok(json)
+}
+
+/// Encodes JSON as a blueprint string, compressing it like Factorio does.
+///
+/// The JSON is not checked to be a valid blueprint.
+pub fn encode_json(json: &str) -> String {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    // Writing to a Vec cannot fail
+    encoder
+        .write_all(json.as_bytes())
+        .expect("compressing to memory");
+    let compressed = encoder.finish().expect("compressing to memory");
+
+    let mut encoded = String::from(FORMAT_VERSION);
+    STANDARD.encode_string(compressed, &mut encoded);
+    encoded
+}
+
+/// Splits the version number of a blueprint into the major, minor, patch
+/// and build parts of the Factorio version, which are 16 bits each.
+pub fn map_version(version: u64) -> [u16; 4] {
+    [
+        (version >> 48) as u16,
+        (version >> 32) as u16,
+        (version >> 16) as u16,
+        version as u16,
+    ]
+}
+
+/// Serializes whole numbers as integers, like Factorio does, so that they
+/// are not written back as floats.
+mod number {
+    use serde::Serializer;
+
+    /// Largest whole number an `f64` can represent exactly.
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.fract() == 0.0 && value.abs() <= MAX_EXACT {
+            serializer.serialize_i64(*value as i64)
+        } else {
+            serializer.serialize_f64(*value)
+        }
+    }
+
+    pub fn serialize_option<S: Serializer>(
+        value: &Option<f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A blueprint in the format Factorio 2.0.10 exports, with two entities
+    /// connected by a wire.
+    const BELT: &str = "0eNp1UNuKwjAQ/Zd5TsXUttI87m+ISFqH3UA6KcnorpT8uxMLisi+JWfm3GaBwV9wjo4YzAJuDJTAHBZI7pusLxjZCcEAR0tpDpGrAT1DVuDojH9gdD4qQGLHDlfu43M70WUaMMqC+kdDwRyS0AIVH5HabloFNzCV3rRicHYRx3XcZPWhWz91S2q2xNUYpsGR5RA/xet3ceFwDP404I+9OiHIVlrt0vtbGr2q5lzq/kqyMjlopVWt9FEwxzhJltc9FXgrPQX7WtteMaZHnrar+6bv22a777r9Luc7Dr2CBQ==";
+
+    #[test]
+    fn test_decode() {
+        let BlueprintData::Blueprint(blueprint) = BlueprintData::decode(BELT).unwrap() else {
+            panic!("Expected a blueprint");
+        };
+
+        assert_eq!(blueprint.label.as_deref(), Some("Belt"));
+        assert_eq!(map_version(blueprint.version), [2, 0, 10, 1]);
+
+        let entities = blueprint.entities.unwrap();
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].name, "transport-belt");
+        assert_eq!(entities[0].position, Position { x: 0.5, y: -1.5 });
+        assert_eq!(entities[0].direction, Some(4));
+        assert!(entities[1].extra.contains_key("control_behavior"));
+        assert!(blueprint.extra.contains_key("wires"));
+    }
+
+    #[test]
+    fn test_roundtrip_is_lossless() {
+        let original: Value = serde_json::from_str(&decode_json(BELT).unwrap()).unwrap();
+
+        let encoded = BlueprintData::decode(BELT).unwrap().encode().unwrap();
+        let roundtrip: Value = serde_json::from_str(&decode_json(&encoded).unwrap()).unwrap();
+
+        assert_eq!(roundtrip, original);
+    }
+
+    #[test]
+    fn test_book() {
+        let json = r#"{"blueprint_book": {"item": "blueprint-book", "active_index": 1,
+            "blueprints": [
+                {"index": 0, "blueprint": {"item": "blueprint", "version": 1}},
+                {"index": 1, "upgrade_planner": {"item": "upgrade-planner", "version": 1,
+                    "settings": {"mappers": [{"index": 0,
+                        "from": {"type": "entity", "name": "transport-belt"},
+                        "to": {"type": "entity", "name": "fast-transport-belt"}}]}}}
+            ],
+            "version": 1}}"#;
+
+        let data = BlueprintData::decode(&encode_json(json)).unwrap();
+        let BlueprintData::BlueprintBook(book) = &data else {
+            panic!("Expected a blueprint book");
+        };
+
+        let entries = book.blueprints.as_ref().unwrap();
+        assert!(matches!(entries[0].data, BlueprintData::Blueprint(_)));
+        let BlueprintData::UpgradePlanner(planner) = &entries[1].data else {
+            panic!("Expected an upgrade planner");
+        };
+        let mapper = &planner.settings.as_ref().unwrap().mappers.as_ref().unwrap()[0];
+        assert_eq!(
+            mapper.to.as_ref().unwrap().name.as_deref(),
+            Some("fast-transport-belt")
+        );
+
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::from_str::<Value>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_whole_numbers_stay_integers() {
+        let position = Position { x: 3.0, y: -0.5 };
+
+        assert_eq!(
+            serde_json::to_string(&position).unwrap(),
+            r#"{"x":3,"y":-0.5}"#
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(decode_json("  "), Err(BlueprintError::Empty)));
+        assert!(matches!(
+            decode_json("1eNp1"),
+            Err(BlueprintError::UnsupportedVersion('1'))
+        ));
+        assert!(matches!(
+            decode_json("0not base64!"),
+            Err(BlueprintError::Base64(_))
+        ));
+        assert!(matches!(
+            BlueprintData::decode(&encode_json(r#"{"blueprint": {}}"#)),
+            Err(BlueprintError::Json(_))
+        ));
+    }
+}
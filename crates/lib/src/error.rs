@@ -58,3 +58,54 @@ pub enum ParseDependencyError {
     #[error("The dependency string \"{0}\" does not match the RegEx")]
     RegexMismatch(String),
 }
+
+#[derive(Error, Debug)]
+pub enum LoadOrderError {
+    /// Mods depend on each other in one or more cycles, each listed as the
+    /// names of the mods in it, starting and ending with the same mod.
+    #[error("Mods depend on each other in a cycle:{}", format_cycles(.0))]
+    Cycles(Vec<Vec<String>>),
+}
+
+fn format_cycles(cycles: &[Vec<String>]) -> String {
+    cycles
+        .iter()
+        .map(|cycle| format!("\n  {}", cycle.join(" -> ")))
+        .collect()
+}
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("Failed to read the save archive")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Failed to read the save")]
+    Io(#[from] std::io::Error),
+
+    #[error("The save has no level.dat")]
+    MissingLevel,
+
+    #[error("The save header ended unexpectedly")]
+    UnexpectedEnd,
+
+    #[error("The save header contains a string that is not valid UTF-8")]
+    InvalidString(#[source] std::string::FromUtf8Error),
+}
+
+#[derive(Error, Debug)]
+pub enum BlueprintError {
+    #[error("The blueprint string is empty")]
+    Empty,
+
+    #[error("Unsupported blueprint string version {0:?}, only version 0 is supported")]
+    UnsupportedVersion(char),
+
+    #[error("The blueprint string is not valid base64")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("Failed to decompress the blueprint string")]
+    Zlib(#[from] std::io::Error),
+
+    #[error("Invalid blueprint JSON")]
+    Json(#[from] serde_json::Error),
+}
@@ -1,10 +1,13 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod blueprint;
 pub mod changelog;
 pub mod dependency;
 pub mod error;
+pub mod load_order;
 pub mod modinfo;
+pub mod save;
 mod semver;
 mod serde;
 pub mod version;
@@ -0,0 +1,266 @@
+//! Computing the order Factorio loads mods in.
+//!
+//! Factorio loads `core` first and `base` second, followed by all other mods
+//! ordered by their dependency depth, and then by the natural ordering of
+//! their names (so `mod2` comes before `mod10`).
+//!
+//! The depth of a mod is one more than the deepest mod it depends on, where
+//! only required dependencies and optional dependencies that are present
+//! count. [Independent][DependencyMode::Independent] dependencies and
+//! incompatibilities do not affect the load order.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+};
+
+use crate::{
+    ModInfo,
+    dependency::{Compatibility, DependencyMode},
+    error::LoadOrderError,
+};
+
+/// Mods that are always loaded before any other mod, in order.
+const FIRST: [&str; 2] = ["core", "base"];
+
+/// Computes the load order of a set of mods.
+///
+/// `core` and `base` are treated as loaded first whether or not they are
+/// part of `mods`, so depending on them is always fine. Dependencies on
+/// other mods that are not part of `mods` are ignored.
+///
+/// # Errors
+///
+/// Returns [`LoadOrderError::Cycles`] if mods depend on each other in a
+/// cycle, as they cannot be loaded in any order.
+///
+/// # Examples
+///
+/// ```
+/// # use facti_lib::{ModInfo, dependency::Dependency, load_order::load_order, version::{Version, VersionReq}};
+/// let library = ModInfo::builder("library", Version::new(1, 0, 0), "Library", "someone")
+///     .dependency(Dependency::required("base", VersionReq::Latest))
+///     .build();
+/// let addon = ModInfo::builder("addon", Version::new(1, 0, 0), "Addon", "someone")
+///     .dependency(Dependency::required("library", VersionReq::Latest))
+///     .build();
+/// let other = ModInfo::builder("other", Version::new(1, 0, 0), "Other", "someone").build();
+///
+/// let mods = [addon, library, other];
+/// let order = load_order(&mods)?
+///     .iter()
+///     .map(|m| m.name.as_str())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(order, vec!["library", "other", "addon"]);
+/// # Ok::<(), facti_lib::error::LoadOrderError>(())
+/// ```
+pub fn load_order(mods: &[ModInfo]) -> Result<Vec<&ModInfo>, LoadOrderError> {
+    let by_name = mods
+        .iter()
+        .map(|m| (m.name.as_str(), m))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut depths = HashMap::new();
+    let mut cycles = Vec::new();
+    for name in by_name.keys() {
+        let mut path = Vec::new();
+        depth(name, &by_name, &mut depths, &mut path, &mut cycles);
+    }
+
+    if !cycles.is_empty() {
+        return Err(LoadOrderError::Cycles(cycles));
+    }
+
+    let mut order = by_name.into_values().collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        let first = |m: &ModInfo| FIRST.iter().position(|f| *f == m.name);
+        match (first(a), first(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => depths[a.name.as_str()]
+                .cmp(&depths[b.name.as_str()])
+                .then_with(|| natural_cmp(&a.name, &b.name)),
+        }
+    });
+
+    Ok(order)
+}
+
+/// Names of the mods `info` has to be loaded after.
+fn load_after<'a>(info: &'a ModInfo, by_name: &BTreeMap<&str, &ModInfo>) -> Vec<&'a str> {
+    info.dependencies
+        .iter()
+        .filter(|d| {
+            matches!(
+                d.compatibility,
+                Compatibility::Compatible(
+                    DependencyMode::Required | DependencyMode::Optional { .. },
+                    _
+                )
+            )
+        })
+        .map(|d| d.name.as_str())
+        .filter(|name| !FIRST.contains(name) && by_name.contains_key(name))
+        .collect()
+}
+
+/// Computes the dependency depth of a mod, recording any cycles found
+/// through it.
+///
+/// `path` holds the mods currently being visited, to detect cycles.
+fn depth<'a>(
+    name: &'a str,
+    by_name: &BTreeMap<&'a str, &'a ModInfo>,
+    depths: &mut HashMap<&'a str, usize>,
+    path: &mut Vec<&'a str>,
+    cycles: &mut Vec<Vec<String>>,
+) -> usize {
+    if let Some(depth) = depths.get(name) {
+        return *depth;
+    }
+
+    if let Some(start) = path.iter().position(|n| *n == name) {
+        let mut cycle = path[start..]
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>();
+        cycle.push(name.to_owned());
+        cycles.push(cycle);
+        return 0;
+    }
+
+    path.push(name);
+    let mut result = 0;
+    for dependency in load_after(by_name[name], by_name) {
+        result = result.max(depth(dependency, by_name, depths, path, cycles) + 1);
+    }
+    path.pop();
+
+    depths.insert(name, result);
+    result
+}
+
+/// Compares two names in natural order, treating runs of digits as numbers
+/// and ignoring case, so that `mod2` comes before `Mod10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a_chars);
+                let y = take_number(&mut b_chars);
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Takes a run of digits, without leading zeros.
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        if !(number.is_empty() && c == '0') {
+            number.push(c);
+        }
+    }
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dependency::Dependency,
+        version::{Version, VersionReq},
+    };
+
+    fn info(name: &str, dependencies: &[Dependency]) -> ModInfo {
+        ModInfo::builder(name, Version::new(1, 0, 0), name, "someone")
+            .dependencies(dependencies)
+            .build()
+    }
+
+    fn names(order: Vec<&ModInfo>) -> Vec<&str> {
+        order.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("mod2", "mod10"), Ordering::Less);
+        assert_eq!(natural_cmp("Mod10", "mod2"), Ordering::Greater);
+        assert_eq!(natural_cmp("abc", "ABD"), Ordering::Less);
+        assert_eq!(natural_cmp("mod", "mod-extra"), Ordering::Less);
+        assert_eq!(natural_cmp("mod02", "mod2"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_load_order() {
+        let latest = VersionReq::Latest;
+        let mods = [
+            info("zz-addon", &[Dependency::required("lib", latest)]),
+            info("lib", &[Dependency::required("base", latest)]),
+            info(
+                "mod10",
+                &[
+                    Dependency::optional("zz-addon", latest, false),
+                    Dependency::optional("not-installed", latest, false),
+                ],
+            ),
+            info("mod2", &[Dependency::independent("mod10", latest)]),
+            info("base", &[Dependency::required("core", latest)]),
+            info("core", &[]),
+        ];
+
+        assert_eq!(
+            names(load_order(&mods).unwrap()),
+            vec!["core", "base", "lib", "mod2", "zz-addon", "mod10"]
+        );
+    }
+
+    #[test]
+    fn test_incompatible_does_not_affect_order() {
+        let mods = [
+            info("a", &[Dependency::incompatible("b")]),
+            info("b", &[Dependency::incompatible("a")]),
+        ];
+
+        assert_eq!(names(load_order(&mods).unwrap()), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let latest = VersionReq::Latest;
+        let mods = [
+            info("a", &[Dependency::required("b", latest)]),
+            info("b", &[Dependency::optional("c", latest, true)]),
+            info("c", &[Dependency::required("a", latest)]),
+            info("d", &[Dependency::required("a", latest)]),
+        ];
+
+        let error = load_order(&mods).unwrap_err();
+        let LoadOrderError::Cycles(cycles) = &error;
+        assert_eq!(cycles, &vec![vec!["a", "b", "c", "a"]]);
+        assert_eq!(
+            error.to_string(),
+            "Mods depend on each other in a cycle:\n  a -> b -> c -> a"
+        );
+    }
+}
@@ -159,6 +159,22 @@ pub struct ModPackageInfo {
     /// be ignored if this is set to `true`.
     #[serde(default, skip_serializing_if = "is_false")]
     pub description_as_summary: bool,
+
+    /// Category to set on the mod portal page, e.g. `utilities`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// Tags to set on the mod portal page, e.g. `trains`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// License to set on the mod portal page, e.g. `default_mit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    /// URL of the mod's source code repository to set on the mod portal page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<Url>,
 }
 
 fn is_false(b: &bool) -> bool {
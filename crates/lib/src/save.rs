@@ -0,0 +1,392 @@
+//! Reading metadata from Factorio save files.
+//!
+//! A save file is a zip archive with a directory named after the save,
+//! containing (among other things) the map data in `level.dat`. Since
+//! Factorio 1.1 the map data is split into zlib compressed chunks named
+//! `level.dat0`, `level.dat1` and so on.
+//!
+//! The map data starts with a header describing the game version the save
+//! was made with and the mods that were active, which is what this module
+//! reads. The rest of the map data is not parsed.
+
+use std::io::{Read, Seek};
+
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use zip::ZipArchive;
+
+use crate::{error::SaveError, version::Version};
+
+/// Name of the file with the map data in older saves.
+const LEVEL_FILENAME: &str = "level.dat";
+
+/// Metadata of a save, read from the header of its map data.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveInfo {
+    /// Version of Factorio the save was made with.
+    pub factorio_version: Version,
+
+    /// Build number of the Factorio version.
+    pub build: u16,
+
+    /// Name of the campaign, empty for freeplay saves.
+    pub campaign: String,
+
+    /// Name of the level (scenario) within the campaign.
+    pub level_name: String,
+
+    /// Name of the mod providing the scenario, usually `base`.
+    pub base_mod: String,
+
+    /// Mods that were active in the save, in the order they were loaded.
+    pub mods: Vec<SaveMod>,
+}
+
+/// A mod that was active in a save.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveMod {
+    pub name: String,
+    pub version: Version,
+
+    /// CRC of the mod's files, as computed by Factorio.
+    pub crc: u32,
+}
+
+impl SaveInfo {
+    /// Reads the metadata of a save from its zip archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be read, has no map data,
+    /// or the header of the map data is invalid.
+    pub fn read<R: Read + Seek>(reader: R) -> Result<Self, SaveError> {
+        let mut archive = ZipArchive::new(reader)?;
+
+        let chunks = chunk_names(&archive);
+        if chunks.is_empty() {
+            let Some(name) = find_entry(&archive, LEVEL_FILENAME) else {
+                return Err(SaveError::MissingLevel);
+            };
+
+            let mut data = Vec::new();
+            archive.by_name(&name)?.read_to_end(&mut data)?;
+            return Self::parse(&data);
+        }
+
+        // The header is usually in the first chunk, but read more chunks
+        // if it turns out to be longer
+        let mut data = Vec::new();
+        for name in chunks {
+            debug!("Reading {}", name);
+            let mut chunk = Vec::new();
+            archive.by_name(&name)?.read_to_end(&mut chunk)?;
+            inflate(&chunk, &mut data)?;
+
+            match Self::parse(&data) {
+                Err(SaveError::UnexpectedEnd) => continue,
+                result => return result,
+            }
+        }
+
+        Err(SaveError::UnexpectedEnd)
+    }
+
+    /// Parses the header at the start of (decompressed) map data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SaveError::UnexpectedEnd`] if `data` ends before the end of
+    /// the header, or another error if the header is invalid.
+    pub fn parse(data: &[u8]) -> Result<Self, SaveError> {
+        let mut reader = Reader { data, position: 0 };
+
+        let major = reader.u16()?;
+        let minor = reader.u16()?;
+        let patch = reader.u16()?;
+        let build = reader.u16()?;
+        let factorio_version = Version::new(major.into(), minor.into(), patch.into());
+
+        // Unknown, always 0 in the saves seen so far
+        reader.u8()?;
+
+        let campaign = reader.string()?;
+        let level_name = reader.string()?;
+        let base_mod = reader.string()?;
+
+        // Difficulty, finished, player won
+        reader.skip(3)?;
+        // Next level
+        reader.string()?;
+        // Can continue, finished but continuing, saving replay,
+        // allow non-admin debug options
+        reader.skip(4)?;
+        // Version the save was loaded from (3 x u8) and its build
+        reader.skip(3)?;
+        reader.u16()?;
+        // Allowed commands
+        reader.u8()?;
+
+        let count = reader.optimized_u32()?;
+        let mut mods = Vec::with_capacity(count.min(1024) as usize);
+        for _ in 0..count {
+            let name = reader.string()?;
+            let major = reader.optimized_u16()?;
+            let minor = reader.optimized_u16()?;
+            let patch = reader.optimized_u16()?;
+            let crc = reader.u32()?;
+
+            mods.push(SaveMod {
+                name,
+                version: Version::new(major.into(), minor.into(), patch.into()),
+                crc,
+            });
+        }
+
+        Ok(Self {
+            factorio_version,
+            build,
+            campaign,
+            level_name,
+            base_mod,
+            mods,
+        })
+    }
+}
+
+/// Names of the `level.dat<N>` chunks in a save, in order.
+fn chunk_names<R: Read + Seek>(archive: &ZipArchive<R>) -> Vec<String> {
+    let mut chunks = archive
+        .file_names()
+        .filter_map(|name| {
+            let file_name = name.rsplit('/').next()?;
+            let index = file_name
+                .strip_prefix(LEVEL_FILENAME)?
+                .parse::<u32>()
+                .ok()?;
+            Some((index, name.to_owned()))
+        })
+        .collect::<Vec<_>>();
+    chunks.sort();
+
+    chunks.into_iter().map(|(_, name)| name).collect()
+}
+
+fn find_entry<R: Read + Seek>(archive: &ZipArchive<R>, file_name: &str) -> Option<String> {
+    archive
+        .file_names()
+        .find(|name| name.rsplit('/').next() == Some(file_name))
+        .map(str::to_owned)
+}
+
+/// Appends the decompressed contents of a chunk to `out`.
+///
+/// Chunks that are not zlib compressed are appended as-is.
+fn inflate(chunk: &[u8], out: &mut Vec<u8>) -> Result<(), SaveError> {
+    if chunk.first() == Some(&0x78) {
+        ZlibDecoder::new(chunk).read_to_end(out)?;
+    } else {
+        out.extend_from_slice(chunk);
+    }
+
+    Ok(())
+}
+
+/// Reads the little-endian primitives of Factorio's binary formats.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], SaveError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(SaveError::UnexpectedEnd)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), SaveError> {
+        self.take(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A `u16` stored in a single byte if it is below 255.
+    fn optimized_u16(&mut self) -> Result<u16, SaveError> {
+        match self.u8()? {
+            0xFF => self.u16(),
+            value => Ok(value.into()),
+        }
+    }
+
+    /// A `u32` stored in a single byte if it is below 255.
+    fn optimized_u32(&mut self) -> Result<u32, SaveError> {
+        match self.u8()? {
+            0xFF => self.u32(),
+            value => Ok(value.into()),
+        }
+    }
+
+    /// A UTF-8 string prefixed with its length as an optimized `u32`.
+    fn string(&mut self) -> Result<String, SaveError> {
+        let length = self.optimized_u32()? as usize;
+        let bytes = self.take(length)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(SaveError::InvalidString)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::{Compression, write::ZlibEncoder};
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.push(s.len() as u8);
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Builds the header of a save made with Factorio 2.0.28.
+    fn header(mods: &[(&str, [u16; 3], u32)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for part in [2u16, 0, 28, 54321] {
+            out.extend_from_slice(&part.to_le_bytes());
+        }
+        out.push(0);
+        string(&mut out, "");
+        string(&mut out, "freeplay");
+        string(&mut out, "base");
+        out.extend_from_slice(&[1, 0, 0]);
+        string(&mut out, "");
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&[2, 0, 28]);
+        out.extend_from_slice(&54321u16.to_le_bytes());
+        out.push(1);
+
+        out.push(mods.len() as u8);
+        for (name, version, crc) in mods {
+            string(&mut out, name);
+            for part in version {
+                if *part < 255 {
+                    out.push(*part as u8);
+                } else {
+                    out.push(0xFF);
+                    out.extend_from_slice(&part.to_le_bytes());
+                }
+            }
+            out.extend_from_slice(&crc.to_le_bytes());
+        }
+
+        out.extend_from_slice(b"rest of the map data");
+        out
+    }
+
+    fn save_zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = header(&[("base", [2, 0, 28], 1), ("flib", [0, 300, 1], 42)]);
+
+        let info = SaveInfo::parse(&data).unwrap();
+
+        assert_eq!(info.factorio_version, Version::new(2, 0, 28));
+        assert_eq!(info.build, 54321);
+        assert_eq!(info.level_name, "freeplay");
+        assert_eq!(info.base_mod, "base");
+        assert_eq!(
+            info.mods,
+            vec![
+                SaveMod {
+                    name: "base".to_owned(),
+                    version: Version::new(2, 0, 28),
+                    crc: 1
+                },
+                SaveMod {
+                    name: "flib".to_owned(),
+                    version: Version::new(0, 300, 1),
+                    crc: 42
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let data = header(&[("base", [2, 0, 28], 1)]);
+
+        assert!(matches!(
+            SaveInfo::parse(&data[..30]),
+            Err(SaveError::UnexpectedEnd)
+        ));
+    }
+
+    #[test]
+    fn test_read_uncompressed() {
+        let zip = save_zip(&[("my-save/level.dat", header(&[("base", [1, 1, 110], 1)]))]);
+
+        let info = SaveInfo::read(Cursor::new(zip)).unwrap();
+
+        assert_eq!(info.mods[0].version, Version::new(1, 1, 110));
+    }
+
+    #[test]
+    fn test_read_chunks() {
+        let data = header(&[("base", [2, 0, 28], 1), ("flib", [0, 15, 0], 2)]);
+        let (first, second) = data.split_at(40);
+        let zip = save_zip(&[
+            ("my-save/level.dat1", zlib(second)),
+            ("my-save/level.dat0", zlib(first)),
+            ("my-save/level-init.dat", vec![0; 8]),
+        ]);
+
+        let info = SaveInfo::read(Cursor::new(zip)).unwrap();
+
+        assert_eq!(info.mods.len(), 2);
+        assert_eq!(info.mods[1].name, "flib");
+    }
+
+    #[test]
+    fn test_read_missing_level() {
+        let zip = save_zip(&[("my-save/script.dat", vec![])]);
+
+        assert!(matches!(
+            SaveInfo::read(Cursor::new(zip)),
+            Err(SaveError::MissingLevel)
+        ));
+    }
+}
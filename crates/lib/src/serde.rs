@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize, de::Visitor};
 
-use super::{FactorioVersion, dependency::Dependency, version::Version};
+use super::{
+    FactorioVersion,
+    dependency::Dependency,
+    version::{Version, VersionReq},
+};
 
 impl Serialize for Version {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -87,6 +91,42 @@ impl<'de> Deserialize<'de> for FactorioVersion {
     }
 }
 
+impl Serialize for VersionReq {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionReq {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VersionReqVisitor;
+
+        impl Visitor<'_> for VersionReqVisitor {
+            type Value = VersionReq;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a valid version requirement string ('[op version]')")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                VersionReq::parse(v)
+                    .map_err(|_| serde::de::Error::custom("invalid version requirement"))
+            }
+        }
+
+        deserializer.deserialize_str(VersionReqVisitor)
+    }
+}
+
 impl Serialize for Dependency {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -124,8 +164,6 @@ impl<'de> Deserialize<'de> for Dependency {
 
 #[cfg(test)]
 mod tests {
-    use crate::version::VersionReq;
-
     use super::*;
 
     #[test]
@@ -156,6 +194,21 @@ mod tests {
         assert_eq!(deserialized, version);
     }
 
+    #[test]
+    fn test_serialize_version_req() {
+        let req = VersionReq::parse(">= 1.2.3").unwrap();
+        assert_eq!(serde_json::to_string(&req).unwrap(), "\">= 1.2.3\"");
+        assert_eq!(serde_json::to_string(&VersionReq::Latest).unwrap(), "\"\"");
+    }
+
+    #[test]
+    fn test_deserialize_version_req() {
+        let deserialized: VersionReq = serde_json::from_str("\"< 2.0.0\"").unwrap();
+        assert_eq!(deserialized, VersionReq::parse("< 2.0.0").unwrap());
+        let latest: VersionReq = serde_json::from_str("\"\"").unwrap();
+        assert_eq!(latest, VersionReq::Latest);
+    }
+
     #[test]
     fn test_serialize_dependency() {
         let dependency =
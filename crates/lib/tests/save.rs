@@ -0,0 +1,31 @@
+use std::{fs, fs::File, path::Path};
+
+use facti_lib::save::SaveInfo;
+
+/// Reads every save in `tests/save` and compares it to the `.json` file
+/// next to it, which holds the expected [`SaveInfo`].
+///
+/// See `tests/save/README.md` for how to add a save.
+#[test]
+fn test_saves() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/save");
+    let mut count = 0;
+
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "zip") {
+            continue;
+        }
+
+        let actual = SaveInfo::read(File::open(&path).unwrap())
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        let expected: SaveInfo =
+            serde_json::from_str(&fs::read_to_string(path.with_extension("json")).unwrap())
+                .unwrap();
+
+        assert_eq!(actual, expected, "{}", path.display());
+        count += 1;
+    }
+
+    assert!(count > 0, "No saves in {} to test against", dir.display());
+}
@@ -0,0 +1,412 @@
+//! Routing and handling of requests to the mock portal.
+
+use facti_api::data::portal::ModShort;
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::{
+    State,
+    http::{Request, Response},
+    multipart::Form,
+    store::{self, DetailsEdit, SearchFilter, StoreError},
+};
+
+/// Page size used by the portal when none is given.
+const DEFAULT_PAGE_SIZE: usize = 25;
+
+/// Kind of file an upload token is valid for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum UploadKind {
+    Release,
+    Publish,
+    Image,
+}
+
+pub(crate) fn handle(state: &State, request: &Request) -> Response {
+    let segments = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "mods"]) => search(state, request),
+        ("GET", ["api", "mods", name]) => info(state, name, false),
+        ("GET", ["api", "mods", name, "full"]) => info(state, name, true),
+        ("POST", ["api", "v2", "mods", action @ ("upload" | "init_publish")]) => {
+            authorized(state, request, |r| init_upload(state, r, action))
+        }
+        ("POST", ["api", "v2", "mods", "edit_details"]) => {
+            authorized(state, request, |r| edit_details(state, r))
+        }
+        ("POST", ["api", "v2", "mods", "images", "add"]) => {
+            authorized(state, request, |r| init_upload(state, r, "images/add"))
+        }
+        ("POST", ["api", "v2", "mods", "images", "edit"]) => {
+            authorized(state, request, |r| edit_images(state, r))
+        }
+        ("POST", ["upload", kind, token]) => finish_upload(state, request, kind, token),
+        ("GET", ["game", "latest-releases"]) => {
+            let latest = state.store().latest_releases();
+            Response::json(200, &json!(latest))
+        }
+        ("GET", ["mod", name]) => mod_page(state, name),
+        ("GET", ["download", ..] | ["assets", ..]) => download(state, request),
+        _ => error(404, "InvalidRequest", "Not found"),
+    }
+}
+
+fn search(state: &State, request: &Request) -> Response {
+    let page = request
+        .query("page")
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    let filter = SearchFilter {
+        hide_deprecated: request.query("hide_deprecated") != Some("false"),
+        namelist: request
+            .query("namelist")
+            .map(|n| n.split(',').map(str::to_owned).collect()),
+        version: request.query("version").and_then(|v| v.parse().ok()),
+        sort: request.query("sort").map(str::to_owned),
+        ascending: request.query("sort_order") == Some("asc"),
+    };
+
+    let store = state.store();
+    let results = store.search(&filter);
+    let count = results.len();
+    let page_size = match request.query("page_size") {
+        Some("max") => count.max(1),
+        Some(size) => match size.parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => return error(400, "InvalidRequest", "Invalid page_size"),
+        },
+        None => DEFAULT_PAGE_SIZE,
+    };
+    let page_count = count.div_ceil(page_size).max(1);
+
+    let results = results
+        .into_iter()
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .map(|r| {
+            json!({
+                "downloads_count": r.download_count,
+                "latest_release": r.latest_release(),
+                "name": r.name,
+                "owner": r.owner,
+                "summary": r.summary,
+                "title": r.title,
+                "category": r.category,
+                "thumbnail": r.thumbnail_path,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let link = |page: usize| {
+        let mut url = state.api_base_url.join("mods").unwrap();
+        url.query_pairs_mut()
+            .extend_pairs(request.query.iter().filter(|(k, _)| k != "page"))
+            .append_pair("page", &page.to_string());
+        url
+    };
+
+    Response::json(
+        200,
+        &json!({
+            "pagination": {
+                "count": count,
+                "links": {
+                    "first": (page > 1).then(|| link(1)),
+                    "prev": (page > 1).then(|| link(page - 1)),
+                    "next": (page < page_count).then(|| link(page + 1)),
+                    "last": (page < page_count).then(|| link(page_count)),
+                },
+                "page": page,
+                "page_count": page_count,
+                "page_size": page_size,
+            },
+            "results": results,
+        }),
+    )
+}
+
+fn info(state: &State, name: &str, full: bool) -> Response {
+    let store = state.store();
+    let Some(result) = store.get(name) else {
+        return store_error(&StoreError::UnknownMod("Mod not found".to_owned()));
+    };
+
+    let value = if full {
+        json!(result)
+    } else {
+        json!(ModShort::from(result.clone()))
+    };
+
+    Response::json(200, &value)
+}
+
+/// Checks the bearer token of a request to an authenticated endpoint
+/// before handing it off to `handler`.
+fn authorized<F>(state: &State, request: &Request, handler: F) -> Response
+where
+    F: FnOnce(&Request) -> Response,
+{
+    let api_key = request
+        .header("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    match api_key {
+        Some(key) if state.store().check_api_key(key) => handler(request),
+        _ => error(
+            403,
+            "InvalidApiKey",
+            "Missing or invalid API key for the current endpoint",
+        ),
+    }
+}
+
+fn init_upload(state: &State, request: &Request, action: &str) -> Response {
+    let Some(name) = form(request).and_then(|f| f.text("mod")) else {
+        return error(400, "InvalidRequest", "Missing mod field");
+    };
+
+    let exists = state.store().get(&name).is_some();
+    let (kind, path) = match action {
+        "upload" => (UploadKind::Release, "release"),
+        "init_publish" => (UploadKind::Publish, "publish"),
+        _ => (UploadKind::Image, "image"),
+    };
+
+    if kind == UploadKind::Publish && exists {
+        return error(400, "InvalidRequest", "Mod already exists");
+    }
+
+    if kind != UploadKind::Publish && !exists {
+        return store_error(&StoreError::UnknownMod("Mod not found".to_owned()));
+    }
+
+    let token = state.new_upload(kind, name);
+    let upload_url = state
+        .portal_base_url
+        .join(&format!("upload/{}/{}", path, token))
+        .unwrap();
+
+    Response::json(200, &json!({ "upload_url": upload_url }))
+}
+
+fn finish_upload(state: &State, request: &Request, kind: &str, token: &str) -> Response {
+    let Some((upload_kind, name)) = state.take_upload(token) else {
+        return error(403, "InvalidRequest", "Invalid or expired upload token");
+    };
+
+    let Some(form) = form(request) else {
+        return error(400, "InvalidRequest", "Expected a multipart form");
+    };
+
+    let mut store = state.store();
+
+    match (upload_kind, kind) {
+        (UploadKind::Release, "release") => {
+            let Some(file) = form.field("file").filter(|f| f.file_name.is_some()) else {
+                return error(400, "InvalidModUpload", "Missing file");
+            };
+
+            match store.upload(&name, &file.data) {
+                Ok(_) => Response::json(200, &json!({ "success": true })),
+                Err(e) => store_error(&e),
+            }
+        }
+        (UploadKind::Publish, "publish") => {
+            let Some(file) = form.field("file").filter(|f| f.file_name.is_some()) else {
+                return error(400, "InvalidModUpload", "Missing file");
+            };
+
+            match store::read_info(&file.data) {
+                Ok(info) if info.name != name => {
+                    return error(
+                        400,
+                        "InvalidModUpload",
+                        "Mod name in info.json does not match the published mod",
+                    );
+                }
+                Err(e) => return store_error(&e),
+                Ok(_) => {}
+            }
+
+            if let Err(e) = store.publish(&file.data) {
+                return store_error(&e);
+            }
+
+            let edit = DetailsEdit {
+                description: form.text("description"),
+                category: form.text("category"),
+                license: form.text("license"),
+                source_url: form.text("source_url"),
+                ..Default::default()
+            };
+
+            if let Err(e) = store.edit_details(&name, edit) {
+                return store_error(&e);
+            }
+
+            let url = state
+                .portal_base_url
+                .join(&format!("mod/{}", name))
+                .unwrap();
+            Response::json(200, &json!({ "success": true, "url": url }))
+        }
+        (UploadKind::Image, "image") => {
+            let Some(image) = form.field("image").filter(|f| f.file_name.is_some()) else {
+                return error(400, "InvalidImageUpload", "Missing image");
+            };
+
+            match store.add_image(&name, image.data.clone()) {
+                Ok(id) => Response::json(200, &image_json(state, &id)),
+                Err(e) => store_error(&e),
+            }
+        }
+        _ => error(403, "InvalidRequest", "Invalid or expired upload token"),
+    }
+}
+
+fn edit_details(state: &State, request: &Request) -> Response {
+    let Some(form) = form(request) else {
+        return error(400, "InvalidRequest", "Expected a multipart form");
+    };
+    let Some(name) = form.text("mod") else {
+        return error(400, "InvalidRequest", "Missing mod field");
+    };
+
+    let tags = form.texts("tags");
+    let edit = DetailsEdit {
+        title: form.text("title"),
+        summary: form.text("summary"),
+        description: form.text("description"),
+        category: form.text("category"),
+        // A missing tags field keeps the tags, a single empty one clears them
+        tags: (!tags.is_empty()).then(|| tags.into_iter().filter(|t| !t.is_empty()).collect()),
+        license: form.text("license"),
+        homepage: form.text("homepage"),
+        deprecated: form.text("deprecated").map(|d| d == "true"),
+        source_url: form.text("source_url"),
+    };
+
+    match state.store().edit_details(&name, edit) {
+        Ok(()) => Response::json(
+            200,
+            &json!({ "success": true, "url": format!("/api/mods/{}/full", name) }),
+        ),
+        Err(e) => store_error(&e),
+    }
+}
+
+fn edit_images(state: &State, request: &Request) -> Response {
+    let Some(form) = form(request) else {
+        return error(400, "InvalidRequest", "Expected a multipart form");
+    };
+    let Some(name) = form.text("mod") else {
+        return error(400, "InvalidRequest", "Missing mod field");
+    };
+
+    let ids = form
+        .text("images")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|id| !id.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    if let Err(e) = state.store().set_gallery(&name, ids.clone()) {
+        return store_error(&e);
+    }
+
+    let images = ids
+        .iter()
+        .map(|id| image_json(state, id))
+        .collect::<Vec<_>>();
+
+    Response::json(200, &json!({ "success": true, "images": images }))
+}
+
+/// Renders the parts of a mod page that are used to list its images.
+fn mod_page(state: &State, name: &str) -> Response {
+    let store = state.store();
+    let Some(result) = store.get(name) else {
+        return Response::new(404, "text/html", "<html><body>Not found</body></html>");
+    };
+
+    let images = store
+        .gallery(name)
+        .iter()
+        .map(|id| {
+            format!(
+                "<img src=\"{base}assets/{id}.thumb.png\" data-filename=\"{id}\">",
+                base = state.portal_base_url,
+                id = id
+            )
+        })
+        .collect::<String>();
+
+    let html = format!(
+        "<html><head><title>{title}</title></head><body>\
+         <div class=\"mod-page-info\"><h2>{title}</h2>\
+         <div class=\"gallery\">{images}</div></div>\
+         </body></html>",
+        title = result.title,
+        images = images
+    );
+
+    Response::new(200, "text/html", html)
+}
+
+fn download(state: &State, request: &Request) -> Response {
+    // Releases can only be downloaded with the credentials of an account,
+    // which are not checked any further
+    if request.path.starts_with("/download/")
+        && (request.query("username").is_none() || request.query("token").is_none())
+    {
+        return error(403, "Forbidden", "Missing username or token");
+    }
+
+    // Thumbnails are served as the full image
+    let path = request.path.replace(".thumb.png", ".png");
+    let content_type = if path.ends_with(".png") {
+        "image/png"
+    } else {
+        "application/zip"
+    };
+
+    match state.store().file(&path) {
+        Some(data) => Response::new(200, content_type, data),
+        None => error(404, "InvalidRequest", "Not found"),
+    }
+}
+
+fn image_json(state: &State, id: &str) -> Value {
+    let url = |suffix: &str| -> Url {
+        state
+            .portal_base_url
+            .join(&format!("assets/{}{}", id, suffix))
+            .unwrap()
+    };
+
+    json!({
+        "id": id,
+        "url": url(".png"),
+        "thumbnail": url(".thumb.png"),
+    })
+}
+
+fn form(request: &Request) -> Option<Form> {
+    Form::parse(request.header("content-type")?, &request.body)
+}
+
+fn store_error(error: &StoreError) -> Response {
+    self::error(error.status(), error.name(), error.message())
+}
+
+pub(crate) fn error(status: u16, error: &str, message: &str) -> Response {
+    Response::json(status, &json!({ "error": error, "message": message }))
+}
@@ -0,0 +1,216 @@
+//! In-process mock of the Factorio mod portal and game APIs.
+//!
+//! [`MockPortal`] starts an HTTP server on a random local port with an
+//! in-memory [`Store`] of mods, which implements the endpoints used by the
+//! `facti-api` clients. Point a client (or the facti CLI, through
+//! `FACTI_PORTAL_BASE_URL`) at it to test code end-to-end without touching
+//! the real portal.
+//!
+//! ```
+//! # use facti_lib::{ModInfo, version::Version};
+//! # use facti_mock_portal::{MockPortal, mod_zip};
+//! let portal = MockPortal::start().unwrap();
+//! let info = ModInfo::builder("my-mod", Version::new(1, 0, 0), "My Mod", "me").build();
+//! portal.store().publish(&mod_zip(&info)).unwrap();
+//!
+//! // Configure the client with portal.portal_base_url() and friends...
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+
+use tracing::{debug, warn};
+use url::Url;
+
+use self::{handlers::UploadKind, http::Request};
+
+pub use self::store::{DEFAULT_OWNER, Store, StoreError, mod_zip};
+
+mod handlers;
+mod http;
+mod multipart;
+mod store;
+
+/// A request received by the mock portal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoggedRequest {
+    pub method: String,
+    pub path: String,
+    pub user_agent: Option<String>,
+}
+
+/// A running mock portal server.
+///
+/// The server is shut down when this is dropped.
+pub struct MockPortal {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+pub(crate) struct State {
+    pub portal_base_url: Url,
+    pub api_base_url: Url,
+    store: Mutex<Store>,
+    failures: Mutex<VecDeque<u16>>,
+    requests: Mutex<Vec<LoggedRequest>>,
+    uploads: Mutex<HashMap<String, (UploadKind, String)>>,
+    next_token: AtomicU64,
+}
+
+impl MockPortal {
+    /// Starts a mock portal with an empty store on a random local port.
+    pub fn start() -> io::Result<Self> {
+        Self::with_store(Store::new())
+    }
+
+    /// Starts a mock portal with the given store on a random local port.
+    pub fn with_store(store: Store) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        let addr = listener.local_addr()?;
+        let portal_base_url = Url::parse(&format!("http://{}/", addr)).unwrap();
+        let api_base_url = portal_base_url.join("api/").unwrap();
+
+        let state = Arc::new(State {
+            portal_base_url,
+            api_base_url,
+            store: Mutex::new(store),
+            failures: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+            uploads: Mutex::new(HashMap::new()),
+            next_token: AtomicU64::new(1),
+        });
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let state = Arc::clone(&state);
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || serve(listener, state, shutdown))
+        };
+
+        debug!("Started mock portal on {}", addr);
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Base URL for the mod portal (non-API resources).
+    ///
+    /// The API and game endpoints are also served relative to this,
+    /// so this is the only URL the CLI needs to be given.
+    pub fn portal_base_url(&self) -> Url {
+        self.state.portal_base_url.clone()
+    }
+
+    /// Base URL for the mod portal API.
+    pub fn portal_api_base_url(&self) -> Url {
+        self.state.api_base_url.clone()
+    }
+
+    /// Base URL for the game API.
+    pub fn game_base_url(&self) -> Url {
+        self.state.portal_base_url.join("game/").unwrap()
+    }
+
+    /// Locks the store for inspection or modification.
+    pub fn store(&self) -> MutexGuard<'_, Store> {
+        self.state.store()
+    }
+
+    /// Makes the next request fail with the given HTTP status.
+    ///
+    /// Calling this several times queues up several failures, which is useful
+    /// for testing retries.
+    pub fn fail_next(&self, status: u16) {
+        self.state.failures.lock().unwrap().push_back(status);
+    }
+
+    /// Requests received so far, in the order they were received.
+    pub fn requests(&self) -> Vec<LoggedRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockPortal {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // Wake up the accept loop so it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl State {
+    pub fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().unwrap()
+    }
+
+    pub fn new_upload(&self, kind: UploadKind, name: String) -> String {
+        let token = format!("{:016x}", self.next_token.fetch_add(1, Ordering::SeqCst));
+        self.uploads
+            .lock()
+            .unwrap()
+            .insert(token.clone(), (kind, name));
+        token
+    }
+
+    pub fn take_upload(&self, token: &str) -> Option<(UploadKind, String)> {
+        self.uploads.lock().unwrap().remove(token)
+    }
+}
+
+fn serve(listener: TcpListener, state: Arc<State>, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+
+        match stream {
+            Ok(stream) => {
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &state) {
+                        warn!("Failed to handle mock portal request: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept mock portal connection: {}", e),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &State) -> io::Result<()> {
+    let request = Request::read(&stream)?;
+    debug!("Mock portal received {} {}", request.method, request.path);
+
+    state.requests.lock().unwrap().push(LoggedRequest {
+        method: request.method.clone(),
+        path: request.path.clone(),
+        user_agent: request.header("user-agent").map(str::to_owned),
+    });
+
+    let failure = state.failures.lock().unwrap().pop_front();
+    let response = match failure {
+        Some(status) => handlers::error(status, "InternalError", "Injected failure"),
+        None => handlers::handle(state, &request),
+    };
+
+    response.write(&stream)
+}
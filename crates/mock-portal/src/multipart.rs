@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+/// A single field of a `multipart/form-data` body.
+///
+/// File uploads are told apart from text fields by having a file name.
+#[derive(Debug)]
+pub(crate) struct Field {
+    pub file_name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Fields of a `multipart/form-data` body, in the order they were sent.
+#[derive(Debug, Default)]
+pub(crate) struct Form {
+    fields: Vec<(String, Field)>,
+}
+
+impl Form {
+    /// Parses a `multipart/form-data` body with the given `Content-Type` header.
+    ///
+    /// Returns [`None`] if the content type is not multipart or the body is
+    /// malformed.
+    pub fn parse(content_type: &str, body: &[u8]) -> Option<Self> {
+        let boundary = content_type
+            .split(';')
+            .map(str::trim)
+            .find_map(|p| p.strip_prefix("boundary="))?
+            .trim_matches('"');
+        let delimiter = format!("--{}", boundary).into_bytes();
+
+        let mut form = Form::default();
+
+        for part in split(body, &delimiter).into_iter().skip(1) {
+            if part.starts_with(b"--") {
+                break;
+            }
+
+            let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+            let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+            let header_end = find(part, b"\r\n\r\n")?;
+            let headers = String::from_utf8_lossy(&part[..header_end]);
+            let data = part[header_end + 4..].to_vec();
+
+            let disposition = headers
+                .lines()
+                .find(|l| l.to_ascii_lowercase().starts_with("content-disposition:"))?
+                .to_owned();
+            let params = disposition_params(&disposition);
+            let name = params.get("name")?.to_owned();
+            let file_name = params.get("filename").cloned();
+
+            form.fields.push((name, Field { file_name, data }));
+        }
+
+        Some(form)
+    }
+
+    /// Gets the first field with the given name.
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, f)| f)
+    }
+
+    /// Gets the first field with the given name as text.
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.field(name)
+            .map(|f| String::from_utf8_lossy(&f.data).into_owned())
+    }
+
+    /// Gets all fields with the given name as text.
+    pub fn texts(&self, name: &str) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, f)| String::from_utf8_lossy(&f.data).into_owned())
+            .collect()
+    }
+}
+
+fn disposition_params(header: &str) -> HashMap<String, String> {
+    header
+        .split(';')
+        .skip(1)
+        .filter_map(|p| p.trim().split_once('='))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().trim_matches('"').to_owned()))
+        .collect()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn split<'a>(mut haystack: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+
+    while let Some(index) = find(haystack, delimiter) {
+        parts.push(&haystack[..index]);
+        haystack = &haystack[index + delimiter.len()..];
+    }
+
+    parts.push(haystack);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_form() {
+        let body = b"--XYZ\r\n\
+            Content-Disposition: form-data; name=\"mod\"\r\n\r\n\
+            my-mod\r\n\
+            --XYZ\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"my-mod_1.0.0.zip\"\r\n\
+            Content-Type: application/zip\r\n\r\n\
+            \x00\x01\x02\r\n\
+            --XYZ--\r\n";
+        let form = Form::parse("multipart/form-data; boundary=XYZ", body).unwrap();
+
+        assert_eq!(form.text("mod").unwrap(), "my-mod");
+        let file = form.field("file").unwrap();
+        assert_eq!(file.file_name.as_deref(), Some("my-mod_1.0.0.zip"));
+        assert_eq!(file.data, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,573 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    io::{Cursor, Read, Write},
+};
+
+use chrono::Utc;
+use facti_api::data::{
+    detail::{Category, Tag},
+    game::{LatestRelease, LatestReleases},
+    portal::{License, ModFull, Release, ReleaseInfo},
+};
+use facti_lib::{FactorioVersion, ModInfo, version::Version};
+use serde::de::DeserializeOwned;
+use url::Url;
+use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+/// Owner given to mods published through the mock, unless changed with
+/// [`Store::set_owner`].
+pub const DEFAULT_OWNER: &str = "facti";
+
+/// In-memory state of the mock portal.
+///
+/// Tests can seed and inspect the state through
+/// [`MockPortal::store`][crate::MockPortal::store].
+pub struct Store {
+    mods: BTreeMap<String, StoredMod>,
+    files: HashMap<String, Vec<u8>>,
+    api_key: Option<String>,
+    owner: String,
+    latest_releases: LatestReleases,
+}
+
+struct StoredMod {
+    result: ModFull,
+    gallery: Vec<String>,
+    uploaded_images: Vec<String>,
+}
+
+/// Errors returned when the store rejects a change.
+///
+/// These map directly onto the errors the real portal responds with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum StoreError {
+    /// The mod does not exist.
+    UnknownMod(String),
+
+    /// The request was malformed or refers to something that does not exist.
+    InvalidRequest(String),
+
+    /// The uploaded file is not a valid mod zip.
+    InvalidModUpload(String),
+
+    /// The release described by the uploaded `info.json` is not acceptable.
+    InvalidModRelease(String),
+}
+
+/// Filters and ordering for [`Store::search`].
+#[derive(Debug, Default)]
+pub(crate) struct SearchFilter {
+    pub hide_deprecated: bool,
+    pub namelist: Option<Vec<String>>,
+    pub version: Option<FactorioVersion>,
+    pub sort: Option<String>,
+    pub ascending: bool,
+}
+
+/// Fields to change with [`Store::edit_details`].
+#[derive(Debug, Default)]
+pub(crate) struct DetailsEdit {
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub deprecated: Option<bool>,
+    pub source_url: Option<String>,
+}
+
+type Result<T> = core::result::Result<T, StoreError>;
+
+impl Store {
+    pub fn new() -> Self {
+        let version = Version::new(1, 1, 110);
+        let release = LatestRelease {
+            alpha: version,
+            demo: version,
+            headless: version,
+        };
+
+        Self {
+            mods: BTreeMap::new(),
+            files: HashMap::new(),
+            api_key: None,
+            owner: DEFAULT_OWNER.to_owned(),
+            latest_releases: LatestReleases {
+                stable: release,
+                experimental: release,
+            },
+        }
+    }
+
+    /// Sets the API key that authenticated endpoints require.
+    ///
+    /// When no key is set (the default), any bearer token is accepted,
+    /// but one still has to be sent.
+    pub fn set_api_key<T: Into<String>>(&mut self, api_key: Option<T>) {
+        self.api_key = api_key.map(Into::into);
+    }
+
+    /// Sets the owner given to newly published mods.
+    pub fn set_owner<T: Into<String>>(&mut self, owner: T) {
+        self.owner = owner.into();
+    }
+
+    /// Sets the game versions returned by the latest releases endpoint.
+    pub fn set_latest_releases(&mut self, latest_releases: LatestReleases) {
+        self.latest_releases = latest_releases;
+    }
+
+    pub fn latest_releases(&self) -> LatestReleases {
+        self.latest_releases
+    }
+
+    /// Inserts (or replaces) a mod as-is, without any releases being
+    /// available for download.
+    pub fn insert_mod(&mut self, result: ModFull) {
+        self.mods.insert(
+            result.name.clone(),
+            StoredMod {
+                result,
+                gallery: Vec::new(),
+                uploaded_images: Vec::new(),
+            },
+        );
+    }
+
+    /// Publishes a new mod from the contents of a mod zip.
+    ///
+    /// Fails if a mod with the same name already exists.
+    pub fn publish(&mut self, zip: &[u8]) -> Result<Release> {
+        let info = read_info(zip)?;
+
+        if self.mods.contains_key(&info.name) {
+            return Err(StoreError::InvalidRequest(format!(
+                "Mod {} already exists",
+                info.name
+            )));
+        }
+
+        self.insert_mod(new_mod(&info, &self.owner));
+        self.add_release(&info, zip)
+    }
+
+    /// Adds a new release to an existing mod from the contents of a mod zip.
+    ///
+    /// The name in the zip's `info.json` has to match `name`, and its version
+    /// must not have been released before.
+    pub fn upload(&mut self, name: &str, zip: &[u8]) -> Result<Release> {
+        let info = read_info(zip)?;
+
+        if info.name != name {
+            return Err(StoreError::InvalidModUpload(format!(
+                "Mod name in info.json ({}) does not match {}",
+                info.name, name
+            )));
+        }
+
+        self.add_release(&info, zip)
+    }
+
+    /// Gets a mod with all of its details.
+    pub fn get(&self, name: &str) -> Option<&ModFull> {
+        self.mods.get(name).map(|m| &m.result)
+    }
+
+    /// Gets a mod with all of its details for modification.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ModFull> {
+        self.mods.get_mut(name).map(|m| &mut m.result)
+    }
+
+    /// Names of all mods in the store.
+    pub fn names(&self) -> Vec<String> {
+        self.mods.keys().cloned().collect()
+    }
+
+    /// Checks whether a mod has been marked as deprecated.
+    pub fn is_deprecated(&self, name: &str) -> bool {
+        self.mods.get(name).is_some_and(|m| m.result.deprecated)
+    }
+
+    /// Marks a mod as deprecated (or not).
+    pub fn set_deprecated(&mut self, name: &str, deprecated: bool) -> Result<()> {
+        self.stored_mut(name)?.result.deprecated = deprecated;
+        Ok(())
+    }
+
+    /// IDs of the images in a mod's gallery, in display order.
+    pub fn gallery(&self, name: &str) -> Vec<String> {
+        self.mods
+            .get(name)
+            .map(|m| m.gallery.clone())
+            .unwrap_or_default()
+    }
+
+    /// Gets a served file (release zip or image) by its path on the portal.
+    pub fn file(&self, path: &str) -> Option<&[u8]> {
+        self.files
+            .get(path.trim_start_matches('/'))
+            .map(Vec::as_slice)
+    }
+
+    pub(crate) fn check_api_key(&self, api_key: &str) -> bool {
+        self.api_key.as_ref().is_none_or(|k| k == api_key)
+    }
+
+    pub(crate) fn search(&self, filter: &SearchFilter) -> Vec<&ModFull> {
+        let mut results = self
+            .mods
+            .values()
+            .filter(|m| !(filter.hide_deprecated && m.result.deprecated))
+            .filter(|m| {
+                filter
+                    .namelist
+                    .as_ref()
+                    .is_none_or(|names| names.contains(&m.result.name))
+            })
+            .filter(|m| {
+                filter.version.is_none_or(|version| {
+                    m.result
+                        .latest_release()
+                        .is_some_and(|r| r.info.factorio_version == version)
+                })
+            })
+            .map(|m| &m.result)
+            .collect::<Vec<_>>();
+
+        match filter.sort.as_deref() {
+            Some("created_at") => results.sort_by_key(|r| r.created_at),
+            Some("updated_at") => {
+                results.sort_by_key(|r| r.latest_release().map(|l| l.released_at))
+            }
+            _ => results.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+
+        if filter.sort.is_some() && !filter.ascending {
+            results.reverse();
+        }
+
+        results
+    }
+
+    pub(crate) fn edit_details(&mut self, name: &str, edit: DetailsEdit) -> Result<()> {
+        let category = edit
+            .category
+            .map(|c| parse_value::<Category>(&c))
+            .transpose()?;
+        let tags = edit
+            .tags
+            .map(|tags| tags.iter().map(|t| parse_value::<Tag>(t)).collect())
+            .transpose()?;
+        let source_url = edit
+            .source_url
+            .map(|u| Url::parse(&u).map_err(|e| StoreError::InvalidRequest(e.to_string())))
+            .transpose()?;
+        let license = edit.license.map(license).transpose()?;
+
+        let result = &mut self.stored_mut(name)?.result;
+
+        if let Some(title) = edit.title {
+            result.title = title;
+        }
+
+        if let Some(summary) = edit.summary {
+            result.summary = summary;
+        }
+
+        if let Some(description) = edit.description {
+            result.description = description;
+        }
+
+        if let Some(category) = category {
+            result.category = category;
+        }
+
+        if let Some(tags) = tags {
+            result.tags = tags;
+        }
+
+        if let Some(license) = license {
+            result.license = Some(license);
+        }
+
+        if let Some(homepage) = edit.homepage {
+            result.homepage = Some(homepage);
+        }
+
+        if let Some(source_url) = source_url {
+            result.source_url = Some(source_url);
+        }
+
+        if let Some(deprecated) = edit.deprecated {
+            result.deprecated = deprecated;
+        }
+
+        Ok(())
+    }
+
+    /// Stores an uploaded image and adds it to the end of the mod's gallery,
+    /// returning its ID.
+    pub(crate) fn add_image(&mut self, name: &str, data: Vec<u8>) -> Result<String> {
+        let id = sha1_smol::Sha1::from(&data).digest().to_string();
+        let stored = self.stored_mut(name)?;
+
+        if !stored.uploaded_images.contains(&id) {
+            stored.uploaded_images.push(id.clone());
+        }
+
+        if !stored.gallery.contains(&id) {
+            stored.gallery.push(id.clone());
+        }
+
+        self.files.insert(image_path(&id), data);
+        self.refresh_thumbnail(name);
+
+        Ok(id)
+    }
+
+    /// Replaces the gallery of a mod with the given images.
+    ///
+    /// Every image must have been uploaded to the mod before.
+    pub(crate) fn set_gallery(&mut self, name: &str, ids: Vec<String>) -> Result<()> {
+        let stored = self.stored_mut(name)?;
+
+        if let Some(unknown) = ids.iter().find(|id| !stored.uploaded_images.contains(id)) {
+            return Err(StoreError::InvalidRequest(format!(
+                "Unknown image {} for mod {}",
+                unknown, name
+            )));
+        }
+
+        stored.gallery = ids;
+        self.refresh_thumbnail(name);
+
+        Ok(())
+    }
+
+    fn refresh_thumbnail(&mut self, name: &str) {
+        if let Some(stored) = self.mods.get_mut(name) {
+            stored.result.thumbnail_path = stored
+                .gallery
+                .first()
+                .map(|id| format!("/assets/{}.thumb.png", id));
+        }
+    }
+
+    fn add_release(&mut self, info: &ModInfo, zip: &[u8]) -> Result<Release> {
+        let stored = self.stored_mut(&info.name)?;
+        let releases = &mut stored.result.releases;
+
+        if releases.iter().any(|r| r.version == info.version) {
+            return Err(StoreError::InvalidModRelease(format!(
+                "Version {} of {} has already been released",
+                info.version, info.name
+            )));
+        }
+
+        let sha1 = sha1_smol::Sha1::from(zip).digest().to_string();
+        let release = Release {
+            download_path: format!("/download/{}/{}", info.name, sha1),
+            filename: format!("{}_{}.zip", info.name, info.version),
+            info: ReleaseInfo {
+                factorio_version: info.factorio_version,
+                dependencies: Some(info.dependencies.clone()),
+            },
+            released_at: Utc::now(),
+            version: info.version,
+            sha1,
+        };
+
+        releases.push(release.clone());
+        self.files
+            .insert(release.download_path[1..].to_owned(), zip.to_vec());
+
+        Ok(release)
+    }
+
+    fn stored_mut(&mut self, name: &str) -> Result<&mut StoredMod> {
+        self.mods
+            .get_mut(name)
+            .ok_or_else(|| StoreError::UnknownMod(format!("Mod {} does not exist", name)))
+    }
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StoreError {
+    /// The error name the portal uses for this error.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoreError::UnknownMod(_) => "UnknownMod",
+            StoreError::InvalidRequest(_) => "InvalidRequest",
+            StoreError::InvalidModUpload(_) => "InvalidModUpload",
+            StoreError::InvalidModRelease(_) => "InvalidModRelease",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            StoreError::UnknownMod(m)
+            | StoreError::InvalidRequest(m)
+            | StoreError::InvalidModUpload(m)
+            | StoreError::InvalidModRelease(m) => m,
+        }
+    }
+
+    pub(crate) fn status(&self) -> u16 {
+        match self {
+            StoreError::UnknownMod(_) => 404,
+            _ => 400,
+        }
+    }
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name(), self.message())
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Builds a mod zip containing only the given `info.json`,
+/// laid out the way the game expects.
+pub fn mod_zip(info: &ModInfo) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let path = format!("{}_{}/info.json", info.name, info.version);
+
+    zip.start_file(path, SimpleFileOptions::default())
+        .expect("writing to memory cannot fail");
+    zip.write_all(
+        serde_json::to_string_pretty(info)
+            .expect("ModInfo is always serializable")
+            .as_bytes(),
+    )
+    .expect("writing to memory cannot fail");
+
+    zip.finish()
+        .expect("writing to memory cannot fail")
+        .into_inner()
+}
+
+fn image_path(id: &str) -> String {
+    format!("assets/{}.png", id)
+}
+
+/// Reads the `info.json` from the top-level directory of a mod zip.
+pub(crate) fn read_info(zip: &[u8]) -> Result<ModInfo> {
+    let invalid = |e: &dyn Display| StoreError::InvalidModUpload(e.to_string());
+    let mut archive = ZipArchive::new(Cursor::new(zip)).map_err(|e| invalid(&e))?;
+
+    let index = (0..archive.len())
+        .find(|&i| {
+            archive.name_for_index(i).is_some_and(|n| {
+                n.split_once('/')
+                    .is_some_and(|(dir, file)| !dir.is_empty() && file == "info.json")
+            })
+        })
+        .ok_or_else(|| invalid(&"Missing info.json"))?;
+
+    let mut contents = String::new();
+    archive
+        .by_index(index)
+        .map_err(|e| invalid(&e))?
+        .read_to_string(&mut contents)
+        .map_err(|e| invalid(&e))?;
+
+    serde_json::from_str(&contents).map_err(|e| StoreError::InvalidModRelease(e.to_string()))
+}
+
+fn new_mod(info: &ModInfo, owner: &str) -> ModFull {
+    ModFull {
+        download_count: 0,
+        name: info.name.clone(),
+        owner: owner.to_owned(),
+        releases: Vec::new(),
+        summary: info.description.clone().unwrap_or_default(),
+        title: info.title.clone(),
+        category: Category::None,
+        thumbnail_path: None,
+        changelog: None,
+        created_at: Utc::now(),
+        description: String::new(),
+        source_url: None,
+        homepage: info.homepage.as_ref().map(Url::to_string),
+        tags: Vec::new(),
+        license: None,
+        deprecated: false,
+    }
+}
+
+fn license(id: String) -> Result<License> {
+    Ok(License {
+        id: parse_value(&id)?,
+        name: id.clone(),
+        title: id.clone(),
+        description: String::new(),
+        url: String::new(),
+    })
+}
+
+/// Parses a value the way it would be deserialized from the portal's JSON.
+fn parse_value<T: DeserializeOwned>(value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_owned()))
+        .map_err(|_| StoreError::InvalidRequest(format!("Invalid value {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(version: Version) -> ModInfo {
+        ModInfo::builder("my-mod", version, "My Mod", "me").build()
+    }
+
+    #[test]
+    fn test_publish_and_upload() {
+        let mut store = Store::new();
+        store
+            .publish(&mod_zip(&info(Version::new(1, 0, 0))))
+            .unwrap();
+        let release = store
+            .upload("my-mod", &mod_zip(&info(Version::new(1, 1, 0))))
+            .unwrap();
+
+        let result = store.get("my-mod").unwrap();
+        assert_eq!(result.owner, DEFAULT_OWNER);
+        assert_eq!(result.releases.len(), 2);
+        assert_eq!(
+            result.latest_release().unwrap().version,
+            Version::new(1, 1, 0)
+        );
+        assert!(store.file(&release.download_path).is_some());
+    }
+
+    #[test]
+    fn test_upload_rejects_duplicate_version() {
+        let mut store = Store::new();
+        let zip = mod_zip(&info(Version::new(1, 0, 0)));
+        store.publish(&zip).unwrap();
+
+        assert!(matches!(
+            store.upload("my-mod", &zip),
+            Err(StoreError::InvalidModRelease(_))
+        ));
+    }
+
+    #[test]
+    fn test_upload_requires_existing_mod() {
+        let mut store = Store::new();
+
+        assert!(matches!(
+            store.upload("my-mod", &mod_zip(&info(Version::new(1, 0, 0)))),
+            Err(StoreError::UnknownMod(_))
+        ));
+    }
+}